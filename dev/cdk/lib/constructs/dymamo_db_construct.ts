@@ -1,11 +1,18 @@
 import { Construct } from 'constructs'
 import { ITable, Table, Attribute, BillingMode } from 'aws-cdk-lib/aws-dynamodb'
 
+export interface SecondaryIndexProps {
+  readonly indexName: string
+  readonly partitionKey: Attribute
+  readonly sortKey?: Attribute
+}
+
 export interface DynamoDBConstructProps {
   readonly tableName: string
   readonly partitionKey: Attribute
   readonly billingMode?: BillingMode
   readonly shouldReuse?: boolean
+  readonly secondaryIndexes?: SecondaryIndexProps[]
 }
 
 export class DynamoDBConstruct extends Construct {
@@ -14,10 +21,16 @@ export class DynamoDBConstruct extends Construct {
   constructor(scope: Construct, id: string, props: DynamoDBConstructProps) {
     super(scope, id)
 
+    const secondaryIndexes = props.secondaryIndexes ?? [];
     if (props.shouldReuse) {
-      this.table = Table.fromTableName(this, id, props.tableName);
+      this.table = Table.fromTableAttributes(this, id, {
+        tableName: props.tableName,
+        globalIndexes: secondaryIndexes.map(index => index.indexName)
+      });
     } else {
-      this.table = new Table(this, id, props)
+      const table = new Table(this, id, props)
+      secondaryIndexes.forEach(index => table.addGlobalSecondaryIndex(index));
+      this.table = table;
     }
   }
-}
\ No newline at end of file
+}
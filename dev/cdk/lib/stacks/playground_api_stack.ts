@@ -4,6 +4,8 @@ import { DynamoDBConstruct } from '../constructs/dymamo_db_construct';
 import { AttributeType } from 'aws-cdk-lib/aws-dynamodb';
 import { LambdaDeploymentConfig } from 'aws-cdk-lib/aws-codedeploy'
 
+const MAX_INDEX_STAGE = 2;
+
 export interface PlaygroundApiStackProps extends StackProps {
     readonly prefix: string
     readonly isDev: boolean
@@ -20,20 +22,157 @@ export class PlaygroundApiStack extends Stack {
             isDev: props.isDev
         }).withFunctionUrl()
 
+        // Indexes are partitioned by low-cardinality attributes, so every item of a type or status
+        // lands on the same partition key, and a single hot status shares one partition's
+        // throughput. The date sort key orders a partition by the stored date strings, but the API
+        // only queries by partition key: items written before dates had a fixed format, including
+        // legacy dd/mm/yyyy values, do not sort in time order until they are rewritten.
         const tables = [
             {
                 tableName: 'Account',
                 partitionKey: {
                     name: 'id',
                     type: AttributeType.STRING
-                }
+                },
+                secondaryIndexes: [
+                    {
+                        indexName: 'AccountTypeIndex',
+                        variable: 'ACCOUNT_TYPE_INDEX',
+                        stage: 1,
+                        partitionKey: {
+                            name: 'type',
+                            type: AttributeType.STRING
+                        },
+                        sortKey: {
+                            name: 'open_date',
+                            type: AttributeType.STRING
+                        }
+                    },
+                    {
+                        indexName: 'AccountStatusIndex',
+                        variable: 'ACCOUNT_STATUS_INDEX',
+                        stage: 2,
+                        partitionKey: {
+                            name: 'status',
+                            type: AttributeType.STRING
+                        },
+                        sortKey: {
+                            name: 'open_date',
+                            type: AttributeType.STRING
+                        }
+                    }
+                ]
+            },
+            {
+                tableName: 'Transaction',
+                partitionKey: {
+                    name: 'id',
+                    type: AttributeType.STRING
+                },
+                secondaryIndexes: [
+                    {
+                        indexName: 'TransactionTypeIndex',
+                        variable: 'TRANSACTION_TYPE_INDEX',
+                        stage: 1,
+                        partitionKey: {
+                            name: 'type',
+                            type: AttributeType.STRING
+                        },
+                        sortKey: {
+                            name: 'date',
+                            type: AttributeType.STRING
+                        }
+                    },
+                    {
+                        indexName: 'TransactionStatusIndex',
+                        variable: 'TRANSACTION_STATUS_INDEX',
+                        stage: 2,
+                        partitionKey: {
+                            name: 'status',
+                            type: AttributeType.STRING
+                        },
+                        sortKey: {
+                            name: 'date',
+                            type: AttributeType.STRING
+                        }
+                    }
+                ]
+            },
+            {
+                tableName: 'Expense',
+                partitionKey: {
+                    name: 'id',
+                    type: AttributeType.STRING
+                },
+                secondaryIndexes: [
+                    {
+                        indexName: 'ExpenseTypeIndex',
+                        variable: 'EXPENSE_TYPE_INDEX',
+                        stage: 1,
+                        partitionKey: {
+                            name: 'type',
+                            type: AttributeType.STRING
+                        },
+                        sortKey: {
+                            name: 'date_added',
+                            type: AttributeType.STRING
+                        }
+                    },
+                    {
+                        indexName: 'ExpenseStatusIndex',
+                        variable: 'EXPENSE_STATUS_INDEX',
+                        stage: 2,
+                        partitionKey: {
+                            name: 'status',
+                            type: AttributeType.STRING
+                        },
+                        sortKey: {
+                            name: 'date_added',
+                            type: AttributeType.STRING
+                        }
+                    }
+                ]
+            },
+            {
+                tableName: 'FxRate',
+                partitionKey: {
+                    name: 'id',
+                    type: AttributeType.STRING
+                },
+                secondaryIndexes: [
+                    {
+                        indexName: 'FxRateSourceIndex',
+                        variable: 'FX_RATE_SOURCE_INDEX',
+                        stage: 1,
+                        partitionKey: {
+                            name: 'source',
+                            type: AttributeType.STRING
+                        },
+                        sortKey: {
+                            name: 'valid_from',
+                            type: AttributeType.STRING
+                        }
+                    }
+                ]
             }
         ];
 
+        // CloudFormation creates or deletes at most one global secondary index per table in each
+        // update, so indexes are rolled out one stage per deploy (`cdk deploy -c indexStage=1`,
+        // then `indexStage=2`). Indexes of later stages are disabled in the lambda meanwhile,
+        // making listings by their attribute fall back to a Scan.
+        const indexStage = Number(this.node.tryGetContext('indexStage') ?? MAX_INDEX_STAGE);
+
         tables.forEach(table => {
+            const secondaryIndexes = table.secondaryIndexes.filter(index => index.stage <= indexStage);
+            table.secondaryIndexes
+                .filter(index => index.stage > indexStage)
+                .forEach(index => playground_api_lambda.addEnvironment(index.variable, ''));
+
             const created_table = new DynamoDBConstruct(this, `${table.tableName}Table`, {
                 tableName: table.tableName,
                 partitionKey: table.partitionKey,
+                secondaryIndexes: secondaryIndexes,
                 shouldReuse: props.isDev
             });
 
@@ -1,18 +1,63 @@
 use crate::controller::account_controller as accounts;
-use crate::model::account::{Account, AccountBalance, AccountStatus, AccountType};
-use crate::model::{Currency, CurrencyCode};
+use crate::controller::expense_controller as expenses;
+use crate::controller::problem::ProblemDetails;
+use crate::controller::report_controller as reports;
+use crate::controller::transaction_controller as transactions;
+use crate::model::account::{Account, AccountBalance, AccountRequest, AccountStatus, AccountType};
+use crate::model::expense::{
+    DuePayment, Expense, ExpensePayment, ExpenseRequest, ExpenseStatus, ExpenseType, ExpenseValue,
+    PaymentStatus, PaymentUpdate, Recurrence,
+};
+use crate::model::page::AccountPage;
+use crate::model::report::{
+    CashFlowEntry, CashFlowMonth, CashFlowReport, CashFlowTotals, NetWorthGroup, NetWorthReport,
+};
+use crate::model::transaction::{
+    Transaction, TransactionOperation, TransactionRequest, TransactionStatus, TransactionType,
+    Transfer, TransferRequest,
+};
+use crate::model::{Currency, CurrencyCode, ResourceId};
 use utoipa::OpenApi;
 
 #[derive(OpenApi)]
 #[openapi(
     paths(
-        accounts::list_accounts
+        accounts::list_accounts,
+        accounts::get_account,
+        accounts::create_account,
+        accounts::update_account,
+        accounts::add_account_balance,
+        accounts::list_account_balances,
+        transactions::list_transactions,
+        transactions::get_transaction,
+        transactions::create_transaction,
+        transactions::update_transaction,
+        transactions::create_transfer,
+        expenses::list_expenses,
+        expenses::get_expense,
+        expenses::create_expense,
+        expenses::update_expense,
+        expenses::list_due_payments,
+        expenses::update_expense_payment,
+        expenses::schedule_expense_payments,
+        reports::get_net_worth,
+        reports::get_cash_flow
     ),
     components(
-        schemas(Currency, CurrencyCode, Account, AccountBalance, AccountStatus, AccountType)
+        schemas(
+            Currency, CurrencyCode, ResourceId, Account, AccountBalance, AccountRequest,
+            AccountStatus, AccountType, Transaction, TransactionRequest, TransactionOperation,
+            TransactionType, TransactionStatus, Transfer, TransferRequest, Expense, ExpenseRequest, ExpenseValue,
+            ExpensePayment, ExpenseType, ExpenseStatus, PaymentStatus, AccountPage,
+            PaymentUpdate, DuePayment, Recurrence, ProblemDetails, NetWorthReport, NetWorthGroup,
+            CashFlowReport, CashFlowMonth, CashFlowEntry, CashFlowTotals
+        )
     ),
     tags(
-        (name = "accounts", description = "Account management endpoints.")
+        (name = "accounts", description = "Account management endpoints."),
+        (name = "transactions", description = "Transaction management endpoints."),
+        (name = "expenses", description = "Expense management endpoints."),
+        (name = "reports", description = "Reporting endpoints.")
     )
 )]
 pub struct ApiDoc;
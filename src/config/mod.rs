@@ -1,2 +1,3 @@
 pub mod api_docs;
+pub mod settings;
 pub mod telemetry;
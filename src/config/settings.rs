@@ -0,0 +1,126 @@
+use crate::repository::{DecodeMode, FxRateSource, RetryPolicy, StorageBackend};
+use std::env;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::warn;
+
+static DECODE_MODE_VARIABLE: &str = "DECODE_MODE";
+static STORAGE_BACKEND_VARIABLE: &str = "STORAGE_BACKEND";
+static STORAGE_PATH_VARIABLE: &str = "STORAGE_PATH";
+static DEFAULT_STORAGE_PATH: &str = "playground-data.jsonl";
+static RETRY_MAX_ATTEMPTS_VARIABLE: &str = "RETRY_MAX_ATTEMPTS";
+static RETRY_BASE_DELAY_VARIABLE: &str = "RETRY_BASE_DELAY_MS";
+static RETRY_MAX_DELAY_VARIABLE: &str = "RETRY_MAX_DELAY_MS";
+static OPERATION_DEADLINE_VARIABLE: &str = "OPERATION_DEADLINE_MS";
+static FX_RATE_PROVIDER_VARIABLE: &str = "FX_RATE_PROVIDER";
+static FX_RATE_PATH_VARIABLE: &str = "FX_RATE_PATH";
+static DEFAULT_FX_RATE_PATH: &str = "fx-rates.json";
+static SECONDARY_INDEXES: [(&str, &str, &str, &str); 7] = [
+    ("Account", "type", "ACCOUNT_TYPE_INDEX", "AccountTypeIndex"),
+    (
+        "Account",
+        "status",
+        "ACCOUNT_STATUS_INDEX",
+        "AccountStatusIndex",
+    ),
+    (
+        "Transaction",
+        "type",
+        "TRANSACTION_TYPE_INDEX",
+        "TransactionTypeIndex",
+    ),
+    (
+        "Transaction",
+        "status",
+        "TRANSACTION_STATUS_INDEX",
+        "TransactionStatusIndex",
+    ),
+    ("Expense", "type", "EXPENSE_TYPE_INDEX", "ExpenseTypeIndex"),
+    (
+        "Expense",
+        "status",
+        "EXPENSE_STATUS_INDEX",
+        "ExpenseStatusIndex",
+    ),
+    (
+        "FxRate",
+        "source",
+        "FX_RATE_SOURCE_INDEX",
+        "FxRateSourceIndex",
+    ),
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    pub decode_mode: DecodeMode,
+    pub storage_backend: StorageBackend,
+    pub storage_path: PathBuf,
+    pub retry_policy: RetryPolicy,
+    pub secondary_indexes: Vec<SecondaryIndex>,
+    pub fx_rate_source: FxRateSource,
+    pub fx_rate_path: PathBuf,
+}
+
+/// Global secondary index of `table_name` partitioned by `attribute_name`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecondaryIndex {
+    pub table_name: String,
+    pub attribute_name: String,
+    pub index_name: String,
+}
+
+impl Settings {
+    pub fn from_env() -> Self {
+        Settings {
+            decode_mode: read_variable(DECODE_MODE_VARIABLE).unwrap_or_default(),
+            storage_backend: read_variable(STORAGE_BACKEND_VARIABLE).unwrap_or_default(),
+            storage_path: read_variable(STORAGE_PATH_VARIABLE)
+                .unwrap_or(PathBuf::from(DEFAULT_STORAGE_PATH)),
+            retry_policy: read_retry_policy(),
+            secondary_indexes: read_secondary_indexes(),
+            fx_rate_source: read_variable(FX_RATE_PROVIDER_VARIABLE).unwrap_or_default(),
+            fx_rate_path: read_variable(FX_RATE_PATH_VARIABLE)
+                .unwrap_or(PathBuf::from(DEFAULT_FX_RATE_PATH)),
+        }
+    }
+}
+
+fn read_retry_policy() -> RetryPolicy {
+    let default = RetryPolicy::default();
+    let read_millis = |name| read_variable(name).map(Duration::from_millis);
+
+    RetryPolicy {
+        max_attempts: read_variable(RETRY_MAX_ATTEMPTS_VARIABLE)
+            .filter(|attempts| *attempts > 0)
+            .unwrap_or(default.max_attempts),
+        base_delay: read_millis(RETRY_BASE_DELAY_VARIABLE).unwrap_or(default.base_delay),
+        max_delay: read_millis(RETRY_MAX_DELAY_VARIABLE).unwrap_or(default.max_delay),
+        deadline: read_millis(OPERATION_DEADLINE_VARIABLE).unwrap_or(default.deadline),
+    }
+}
+
+/// Each index name can be overridden through its variable, and an empty value disables it so
+/// listings by that attribute fall back to a Scan.
+fn read_secondary_indexes() -> Vec<SecondaryIndex> {
+    SECONDARY_INDEXES
+        .iter()
+        .map(
+            |(table_name, attribute_name, variable, default)| SecondaryIndex {
+                table_name: table_name.to_string(),
+                attribute_name: attribute_name.to_string(),
+                index_name: read_variable(variable).unwrap_or(default.to_string()),
+            },
+        )
+        .filter(|index| !index.index_name.is_empty())
+        .collect()
+}
+
+fn read_variable<T: FromStr>(name: &str) -> Option<T> {
+    let value = env::var(name).ok()?;
+    let parsed = T::from_str(value.as_str()).ok();
+    if parsed.is_none() {
+        warn!("Ignoring invalid value {value} for {name}");
+    }
+    parsed
+}
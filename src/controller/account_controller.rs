@@ -1,9 +1,14 @@
-use crate::error::PermanentError;
-use crate::model::account::{AccountStatus, AccountType};
+use crate::error::ApiError;
+use crate::model::account::{
+    AccountBalance, AccountRequest, AccountStatus, AccountType, BalanceFilter,
+};
+use crate::model::page::PageRequest;
+use crate::model::CurrencyCode;
 use crate::service::AccountService;
 use actix_web::http::header::ContentType;
-use actix_web::web::{Data, Query};
-use lambda_web::actix_web::{get, HttpResponse};
+use actix_web::web::{Data, Json, Path, Query};
+use chrono::{DateTime, Utc};
+use lambda_web::actix_web::{get, post, HttpResponse};
 use serde::Deserialize;
 use serde_json::to_string;
 use tracing::info;
@@ -13,6 +18,36 @@ use utoipa::IntoParams;
 pub struct ListAccountParams {
     pub account_type: Option<AccountType>,
     pub account_status: Option<AccountStatus>,
+    /// Maximum number of accounts in the page
+    pub limit: Option<i32>,
+    /// Opaque cursor returned as `next_cursor` by the previous page
+    pub cursor: Option<String>,
+    /// Converts every amount into this currency using the rate valid at its date
+    pub report_currency: Option<CurrencyCode>,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+pub struct ListBalanceParams {
+    /// Earliest balance date, inclusive
+    #[param(value_type = Option<String>, format = DateTime)]
+    pub from: Option<DateTime<Utc>>,
+    /// Latest balance date, inclusive
+    #[param(value_type = Option<String>, format = DateTime)]
+    pub to: Option<DateTime<Utc>>,
+    /// Keeps only the last balance of each month
+    pub monthly: Option<bool>,
+    /// Converts every balance into this currency using the rate valid at its date
+    pub report_currency: Option<CurrencyCode>,
+}
+
+impl From<ListBalanceParams> for BalanceFilter {
+    fn from(params: ListBalanceParams) -> Self {
+        BalanceFilter {
+            from: params.from,
+            to: params.to,
+            monthly: params.monthly.unwrap_or_default(),
+        }
+    }
 }
 
 #[utoipa::path(
@@ -20,7 +55,8 @@ pub struct ListAccountParams {
         ListAccountParams
     ),
     responses(
-        (status = 200, description = "List Current Accounts", body = [Account])
+        (status = 200, description = "List Current Accounts", body = AccountPage),
+        (status = 400, description = "Invalid Page Parameters Or No FX Rate Available", body = ProblemDetails, content_type = "application/problem+json")
     )
 )]
 #[tracing::instrument(skip(account_service))]
@@ -28,14 +64,166 @@ pub struct ListAccountParams {
 pub async fn list_accounts(
     account_service: Data<AccountService>,
     params: Query<ListAccountParams>,
-) -> Result<HttpResponse, PermanentError> {
+) -> Result<HttpResponse, actix_web::Error> {
     info!("Listing all accounts");
 
+    let page = PageRequest::new(params.limit, params.cursor.as_deref())?;
     let accounts = account_service
-        .list_accounts(params.account_type, params.account_status)
+        .list_accounts(
+            params.account_type,
+            params.account_status,
+            page,
+            params.report_currency,
+        )
         .await?;
 
     Ok(HttpResponse::Ok()
         .content_type(ContentType::json())
         .body(to_string(&accounts).unwrap()))
 }
+
+#[utoipa::path(
+    params(
+        ("id" = String, Path, description = "Account id")
+    ),
+    responses(
+        (status = 200, description = "Account Found", body = Account),
+        (status = 404, description = "Account Not Found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[tracing::instrument(skip(account_service))]
+#[get("/accounts/{id}")]
+pub async fn get_account(
+    account_service: Data<AccountService>,
+    id: Path<String>,
+) -> Result<HttpResponse, actix_web::Error> {
+    info!("Getting account");
+
+    let id = id.into_inner();
+    let account = account_service
+        .get_account(id.clone())
+        .await?
+        .ok_or(ApiError::NotFound {
+            message: format!("Account {id} not found"),
+        })?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(ContentType::json())
+        .body(to_string(&account).unwrap()))
+}
+
+#[utoipa::path(
+    request_body = AccountRequest,
+    responses(
+        (status = 201, description = "Account Created", body = ResourceId),
+        (status = 400, description = "Invalid Account", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[tracing::instrument(skip(account_service))]
+#[post("/accounts")]
+pub async fn create_account(
+    account_service: Data<AccountService>,
+    request: Json<AccountRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    info!("Creating account");
+
+    request.validate()?;
+    let account_id = account_service.create_account(request.into_inner()).await?;
+
+    Ok(HttpResponse::Created()
+        .content_type(ContentType::json())
+        .body(to_string(&account_id).unwrap()))
+}
+
+#[utoipa::path(
+    params(
+        ("id" = String, Path, description = "Account id")
+    ),
+    request_body = AccountRequest,
+    responses(
+        (status = 200, description = "Account Updated", body = ResourceId),
+        (status = 400, description = "Invalid Account", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Account Not Found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[tracing::instrument(skip(account_service))]
+#[post("/accounts/{id}")]
+pub async fn update_account(
+    account_service: Data<AccountService>,
+    id: Path<String>,
+    request: Json<AccountRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    info!("Updating account");
+
+    request.validate()?;
+    let account_id = account_service
+        .update_account(id.into_inner(), request.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(ContentType::json())
+        .body(to_string(&account_id).unwrap()))
+}
+
+#[utoipa::path(
+    params(
+        ("id" = String, Path, description = "Account id")
+    ),
+    request_body = AccountBalance,
+    responses(
+        (status = 201, description = "Balance Recorded", body = ResourceId),
+        (status = 400, description = "Invalid Balance", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Account Not Found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Another Balance Recorded For The Month", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[tracing::instrument(skip(account_service))]
+#[post("/accounts/{id}/balances")]
+pub async fn add_account_balance(
+    account_service: Data<AccountService>,
+    id: Path<String>,
+    request: Json<AccountBalance>,
+) -> Result<HttpResponse, actix_web::Error> {
+    info!("Adding account balance");
+
+    let account_id = account_service
+        .add_balance(id.into_inner(), request.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Created()
+        .content_type(ContentType::json())
+        .body(to_string(&account_id).unwrap()))
+}
+
+#[utoipa::path(
+    params(
+        ("id" = String, Path, description = "Account id"),
+        ListBalanceParams
+    ),
+    responses(
+        (status = 200, description = "Account Balance History", body = [AccountBalance]),
+        (status = 400, description = "Invalid Date Range Or No FX Rate Available", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Account Not Found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[tracing::instrument(skip(account_service))]
+#[get("/accounts/{id}/balances")]
+pub async fn list_account_balances(
+    account_service: Data<AccountService>,
+    id: Path<String>,
+    params: Query<ListBalanceParams>,
+) -> Result<HttpResponse, actix_web::Error> {
+    info!("Listing account balances");
+
+    let params = params.into_inner();
+    let report_currency = params.report_currency;
+    let filter = BalanceFilter::from(params);
+    filter.validate()?;
+    let balances = account_service
+        .balance_history(id.into_inner(), filter, report_currency)
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(ContentType::json())
+        .body(to_string(&balances).unwrap()))
+}
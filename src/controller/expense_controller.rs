@@ -0,0 +1,240 @@
+use crate::error::ApiError;
+use crate::model::expense::{ExpenseRequest, ExpenseStatus, ExpenseType, PaymentUpdate};
+use crate::model::CurrencyCode;
+use crate::service::{ExpensePaymentService, ExpenseService};
+use actix_web::http::header::ContentType;
+use actix_web::web::{Data, Json, Path, Query};
+use chrono::{DateTime, Utc};
+use lambda_web::actix_web::{get, post, HttpResponse};
+use serde::Deserialize;
+use serde_json::to_string;
+use tracing::info;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, Debug, IntoParams)]
+pub struct ListExpenseParams {
+    pub expense_type: Option<ExpenseType>,
+    pub expense_status: Option<ExpenseStatus>,
+    /// Converts every amount into this currency using the rate valid at its date
+    pub report_currency: Option<CurrencyCode>,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+pub struct DuePaymentParams {
+    /// Lists installments due before this date, now by default
+    #[param(value_type = Option<String>, format = DateTime)]
+    pub before: Option<DateTime<Utc>>,
+    /// Converts every amount into this currency using the rate valid at its due date
+    pub report_currency: Option<CurrencyCode>,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+pub struct ScheduleParams {
+    /// Number of months to plan payments for, starting now, 12 by default
+    pub months: Option<u32>,
+}
+
+#[utoipa::path(
+    params(
+        ListExpenseParams
+    ),
+    responses(
+        (status = 200, description = "List Expenses", body = [Expense]),
+        (status = 400, description = "No FX Rate Available", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[tracing::instrument(skip(expense_service))]
+#[get("/expenses")]
+pub async fn list_expenses(
+    expense_service: Data<ExpenseService>,
+    params: Query<ListExpenseParams>,
+) -> Result<HttpResponse, actix_web::Error> {
+    info!("Listing expenses");
+
+    let expenses = expense_service
+        .list_expenses(
+            params.expense_type,
+            params.expense_status,
+            params.report_currency,
+        )
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(ContentType::json())
+        .body(to_string(&expenses).unwrap()))
+}
+
+#[utoipa::path(
+    params(
+        ("id" = String, Path, description = "Expense id")
+    ),
+    responses(
+        (status = 200, description = "Expense Found", body = Expense),
+        (status = 404, description = "Expense Not Found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[tracing::instrument(skip(expense_service))]
+#[get("/expenses/{id}")]
+pub async fn get_expense(
+    expense_service: Data<ExpenseService>,
+    id: Path<String>,
+) -> Result<HttpResponse, actix_web::Error> {
+    info!("Getting expense");
+
+    let id = id.into_inner();
+    let expense = expense_service
+        .get_expense(id.clone())
+        .await?
+        .ok_or(ApiError::NotFound {
+            message: format!("Expense {id} not found"),
+        })?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(ContentType::json())
+        .body(to_string(&expense).unwrap()))
+}
+
+#[utoipa::path(
+    request_body = ExpenseRequest,
+    responses(
+        (status = 201, description = "Expense Created", body = ResourceId),
+        (status = 400, description = "Invalid Expense", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[tracing::instrument(skip(expense_service))]
+#[post("/expenses")]
+pub async fn create_expense(
+    expense_service: Data<ExpenseService>,
+    request: Json<ExpenseRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    info!("Creating expense");
+
+    request.validate()?;
+    let expense_id = expense_service.create_expense(request.into_inner()).await?;
+
+    Ok(HttpResponse::Created()
+        .content_type(ContentType::json())
+        .body(to_string(&expense_id).unwrap()))
+}
+
+#[utoipa::path(
+    params(
+        ("id" = String, Path, description = "Expense id")
+    ),
+    request_body = ExpenseRequest,
+    responses(
+        (status = 200, description = "Expense Updated", body = ResourceId),
+        (status = 400, description = "Invalid Expense", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Expense Changed Concurrently", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[tracing::instrument(skip(expense_service))]
+#[post("/expenses/{id}")]
+pub async fn update_expense(
+    expense_service: Data<ExpenseService>,
+    id: Path<String>,
+    request: Json<ExpenseRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    info!("Updating expense");
+
+    request.validate()?;
+    let expense_id = expense_service
+        .update_expense(id.into_inner(), request.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(ContentType::json())
+        .body(to_string(&expense_id).unwrap()))
+}
+
+#[utoipa::path(
+    params(
+        DuePaymentParams
+    ),
+    responses(
+        (status = 200, description = "List Due Payments", body = [DuePayment]),
+        (status = 400, description = "No FX Rate Available", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[tracing::instrument(skip(payment_service))]
+#[get("/expenses/due")]
+pub async fn list_due_payments(
+    payment_service: Data<ExpensePaymentService>,
+    params: Query<DuePaymentParams>,
+) -> Result<HttpResponse, actix_web::Error> {
+    info!("Listing due payments");
+
+    let payments = payment_service
+        .list_due(
+            params.before.unwrap_or_else(Utc::now),
+            params.report_currency,
+        )
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(ContentType::json())
+        .body(to_string(&payments).unwrap()))
+}
+
+#[utoipa::path(
+    params(
+        ("id" = String, Path, description = "Expense id"),
+        ("due_date" = String, Path, format = DateTime, description = "Due date of the installment")
+    ),
+    request_body = PaymentUpdate,
+    responses(
+        (status = 200, description = "Payment Updated", body = ResourceId),
+        (status = 400, description = "Invalid Payment", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Expense Or Payment Not Found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Expense Changed Concurrently", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[tracing::instrument(skip(payment_service))]
+#[post("/expenses/{id}/payments/{due_date}")]
+pub async fn update_expense_payment(
+    payment_service: Data<ExpensePaymentService>,
+    path: Path<(String, DateTime<Utc>)>,
+    request: Json<PaymentUpdate>,
+) -> Result<HttpResponse, actix_web::Error> {
+    info!("Updating expense payment");
+
+    request.validate()?;
+    let (id, due_date) = path.into_inner();
+    let expense_id = payment_service
+        .update_payment(id, due_date, request.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(ContentType::json())
+        .body(to_string(&expense_id).unwrap()))
+}
+
+#[utoipa::path(
+    params(
+        ("id" = String, Path, description = "Expense id"),
+        ScheduleParams
+    ),
+    responses(
+        (status = 200, description = "Payments Planned", body = [ExpensePayment]),
+        (status = 400, description = "Expense Without Recurrence Or Closed", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Expense Not Found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Expense Changed Concurrently", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[tracing::instrument(skip(payment_service))]
+#[post("/expenses/{id}/schedule")]
+pub async fn schedule_expense_payments(
+    payment_service: Data<ExpensePaymentService>,
+    id: Path<String>,
+    params: Query<ScheduleParams>,
+) -> Result<HttpResponse, actix_web::Error> {
+    info!("Scheduling expense payments");
+
+    let payments = payment_service
+        .schedule_payments(id.into_inner(), Utc::now(), params.months.unwrap_or(12))
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(ContentType::json())
+        .body(to_string(&payments).unwrap()))
+}
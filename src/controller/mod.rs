@@ -1,22 +1,28 @@
-use crate::error::PermanentError;
+use crate::controller::problem::ProblemDetails;
+use crate::error::ApiError;
 use actix_web::body::BoxBody;
-use actix_web::http::header::ContentType;
+use actix_web::http::StatusCode;
 use actix_web::{HttpResponse, ResponseError};
-use serde_json::Value;
 
 pub mod account_controller;
+pub mod expense_controller;
+pub mod problem;
+pub mod report_controller;
+pub mod transaction_controller;
 
-impl ResponseError for PermanentError {
-    fn error_response(&self) -> HttpResponse<BoxBody> {
-        let message = format!(r#"{{"cause": "{}"}}"#, self);
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::Validation { .. } => StatusCode::BAD_REQUEST,
+            ApiError::NotFound { .. } => StatusCode::NOT_FOUND,
+            ApiError::Conflict { .. } => StatusCode::CONFLICT,
+            ApiError::Transient { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Permanent { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
 
-        HttpResponse::InternalServerError()
-            .content_type(ContentType::json())
-            .body(
-                serde_json::from_str::<Value>(message.as_str())
-                    .unwrap()
-                    .to_string(),
-            )
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        ProblemDetails::from(self).to_response()
     }
 }
 
@@ -24,13 +30,14 @@ impl ResponseError for PermanentError {
 mod tests {
     use super::*;
     use actix_web::body::MessageBody;
-    use actix_web::http::StatusCode;
+    use actix_web::web::Bytes;
+    use rstest::rstest;
     use std::error::Error;
     use std::fmt::{Display, Formatter};
 
     #[test]
     fn should_return_internal_server_error() {
-        let error = PermanentError {
+        let error = ApiError::Permanent {
             source: Box::new(TestError {}),
             message: Some("test".to_string()),
         };
@@ -46,15 +53,36 @@ mod tests {
                 .unwrap()
                 .to_str()
                 .unwrap(),
-            "application/json"
+            "application/problem+json"
         );
 
         assert_eq!(
-            format!("{:?}", error_response.into_body().try_into_bytes().unwrap()),
-            "b\"{\\\"cause\\\":\\\"error: test\\\"}\""
+            error_response.into_body().try_into_bytes().unwrap(),
+            Bytes::from(
+                r#"{"type":"/problems/internal","title":"Internal error","status":500,"detail":"An unexpected error occurred"}"#
+            )
         );
     }
 
+    #[rstest]
+    #[case(ApiError::Validation { message: "name must not be \"blank\"".to_string() }, StatusCode::BAD_REQUEST, "name must not be \"blank\"")]
+    #[case(ApiError::NotFound { message: "Account id not found".to_string() }, StatusCode::NOT_FOUND, "Account id not found")]
+    #[case(ApiError::Conflict { message: "Account id changed".to_string() }, StatusCode::CONFLICT, "Account id changed")]
+    #[case(ApiError::Transient { source: Box::new(TestError {}), message: None }, StatusCode::SERVICE_UNAVAILABLE, "An unexpected error occurred")]
+    fn should_map_error_to_status(
+        #[case] error: ApiError,
+        #[case] expected_status: StatusCode,
+        #[case] detail: &str,
+    ) {
+        let error_response = error.error_response();
+
+        assert_eq!(error_response.status(), expected_status);
+        let problem: ProblemDetails =
+            serde_json::from_slice(&error_response.into_body().try_into_bytes().unwrap()).unwrap();
+        assert_eq!(expected_status.as_u16(), problem.status);
+        assert_eq!(detail, problem.detail);
+    }
+
     #[derive(Debug)]
     struct TestError {}
 
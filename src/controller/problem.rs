@@ -0,0 +1,223 @@
+use crate::error::ApiError;
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::StatusCode;
+use actix_web::middleware::Next;
+use actix_web::{HttpMessage, HttpResponse, ResponseError};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+use tracing_actix_web::RequestId;
+use utoipa::ToSchema;
+
+pub static PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+/// Sent instead of the description of transient and permanent failures, which may quote the
+/// internals of the database or the file system.
+static UNEXPECTED_ERROR_DETAIL: &str = "An unexpected error occurred";
+
+/// RFC 7807 error document returned by every failed request.
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl ProblemDetails {
+    pub fn new(status: StatusCode, detail: String) -> Self {
+        ProblemDetails {
+            problem_type: "about:blank".to_string(),
+            title: status.canonical_reason().unwrap_or("Error").to_string(),
+            status: status.as_u16(),
+            detail,
+            instance: None,
+            request_id: None,
+        }
+    }
+
+    pub fn to_response(&self) -> HttpResponse<BoxBody> {
+        HttpResponse::build(StatusCode::from_u16(self.status).unwrap())
+            .content_type(PROBLEM_CONTENT_TYPE)
+            .body(serde_json::to_string(self).unwrap())
+    }
+}
+
+impl From<&ApiError> for ProblemDetails {
+    fn from(error: &ApiError) -> Self {
+        let (problem_type, title) = match error {
+            ApiError::Validation { .. } => ("/problems/validation", "Invalid request"),
+            ApiError::NotFound { .. } => ("/problems/not-found", "Resource not found"),
+            ApiError::Conflict { .. } => ("/problems/conflict", "Conflicting request"),
+            ApiError::Transient { .. } => ("/problems/unavailable", "Service unavailable"),
+            ApiError::Permanent { .. } => ("/problems/internal", "Internal error"),
+        };
+        let detail = match error {
+            ApiError::Transient { .. } | ApiError::Permanent { .. } => {
+                UNEXPECTED_ERROR_DETAIL.to_string()
+            }
+            _ => error.to_string(),
+        };
+
+        ProblemDetails {
+            problem_type: problem_type.to_string(),
+            title: title.to_string(),
+            ..ProblemDetails::new(error.status_code(), detail)
+        }
+    }
+}
+
+/// Rewrites every error response as a problem document naming the request path and the
+/// request id assigned by the tracing middleware.
+pub async fn problem_details(
+    request: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, actix_web::Error> {
+    let response = next.call(request).await?;
+    let Some(error) = response.response().error() else {
+        return Ok(response.map_into_boxed_body());
+    };
+
+    let mut problem = match error.as_error::<ApiError>() {
+        Some(error) => {
+            if let ApiError::Transient { .. } | ApiError::Permanent { .. } = error {
+                error!(error = %error, "Request failed");
+            }
+            ProblemDetails::from(error)
+        }
+        None => ProblemDetails::new(response.status(), error.to_string()),
+    };
+    problem.instance = Some(response.request().path().to_string());
+    problem.request_id = response
+        .request()
+        .extensions()
+        .get::<RequestId>()
+        .map(|request_id| request_id.to_string());
+
+    let (request, _) = response.into_parts();
+    Ok(ServiceResponse::new(request, problem.to_response()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::middleware::from_fn;
+    use actix_web::test::{call_service, init_service, read_body, read_body_json, TestRequest};
+    use actix_web::{get, App};
+    use tracing_actix_web::TracingLogger;
+
+    #[get("/accounts/{id}")]
+    async fn missing_account() -> Result<HttpResponse, ApiError> {
+        Err(ApiError::NotFound {
+            message: "Account \"id\" not found".to_string(),
+        })
+    }
+
+    #[get("/accounts/{id}/balances")]
+    async fn failing_balances() -> Result<HttpResponse, ApiError> {
+        Err(ApiError::Permanent {
+            source: "disk /var/lib/playground is full".into(),
+            message: Some("failed writing balances.jsonl".to_string()),
+        })
+    }
+
+    #[get("/accounts")]
+    async fn list_accounts(_: actix_web::web::Query<Vec<(String, i32)>>) -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_web::test]
+    async fn should_describe_api_error_with_request_context() {
+        let app = init_service(
+            App::new()
+                .wrap(from_fn(problem_details))
+                .wrap(TracingLogger::default())
+                .service(missing_account),
+        )
+        .await;
+
+        let response =
+            call_service(&app, TestRequest::get().uri("/accounts/id").to_request()).await;
+
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+        assert_eq!(
+            PROBLEM_CONTENT_TYPE,
+            response.headers().get("Content-Type").unwrap()
+        );
+        let problem: ProblemDetails = read_body_json(response).await;
+        assert_eq!("/problems/not-found", problem.problem_type);
+        assert_eq!("Account \"id\" not found", problem.detail);
+        assert_eq!(404, problem.status);
+        assert_eq!(Some("/accounts/id".to_string()), problem.instance);
+        assert!(problem.request_id.is_some());
+    }
+
+    #[actix_web::test]
+    async fn should_not_expose_source_of_unexpected_errors() {
+        let app = init_service(
+            App::new()
+                .wrap(from_fn(problem_details))
+                .service(failing_balances),
+        )
+        .await;
+
+        let response = call_service(
+            &app,
+            TestRequest::get().uri("/accounts/id/balances").to_request(),
+        )
+        .await;
+
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+        let body = String::from_utf8(read_body(response).await.to_vec()).unwrap();
+        assert!(!body.contains("/var/lib/playground"));
+        assert!(!body.contains("balances.jsonl"));
+        let problem: ProblemDetails = serde_json::from_str(&body).unwrap();
+        assert_eq!("/problems/internal", problem.problem_type);
+        assert_eq!(UNEXPECTED_ERROR_DETAIL, problem.detail);
+    }
+
+    #[actix_web::test]
+    async fn should_describe_framework_errors() {
+        let app = init_service(
+            App::new()
+                .wrap(from_fn(problem_details))
+                .service(list_accounts),
+        )
+        .await;
+
+        let response = call_service(
+            &app,
+            TestRequest::get()
+                .uri("/accounts?limit=batata")
+                .to_request(),
+        )
+        .await;
+
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        let problem: ProblemDetails = read_body_json(response).await;
+        assert_eq!("about:blank", problem.problem_type);
+        assert_eq!("Bad Request", problem.title);
+        assert_eq!(None, problem.request_id);
+    }
+
+    #[test]
+    fn should_serialize_problem_type_as_type() {
+        let problem = ProblemDetails::new(StatusCode::CONFLICT, "conflict".to_string());
+
+        assert_eq!(
+            r#"{"type":"about:blank","title":"Conflict","status":409,"detail":"conflict"}"#,
+            serde_json::to_string(&problem).unwrap()
+        );
+    }
+
+    #[test]
+    fn problem_details_should_be_thread_safe() {
+        is_thread_safe::<ProblemDetails>();
+    }
+
+    fn is_thread_safe<T: Sized + Send + Sync + Unpin>() {}
+}
@@ -0,0 +1,85 @@
+use crate::model::report::NetWorthFilter;
+use crate::model::CurrencyCode;
+use crate::service::ReportService;
+use actix_web::http::header::ContentType;
+use actix_web::web::{Data, Query};
+use chrono::{DateTime, Utc};
+use lambda_web::actix_web::{get, HttpResponse};
+use serde::Deserialize;
+use serde_json::to_string;
+use tracing::info;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, Debug, IntoParams)]
+pub struct NetWorthParams {
+    /// Uses the latest balances recorded up to this date, now by default
+    #[param(value_type = Option<String>, format = DateTime)]
+    pub as_of: Option<DateTime<Utc>>,
+    /// Includes `CLOSED` and `NOT_IN_USE` accounts
+    pub include_inactive: Option<bool>,
+    /// Converts every balance into this currency using the rate valid at the `as_of` date
+    pub report_currency: Option<CurrencyCode>,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+pub struct CashFlowParams {
+    pub year: i32,
+    /// Converts every transaction into this currency using the rate valid at its date
+    pub report_currency: Option<CurrencyCode>,
+}
+
+#[utoipa::path(
+    params(
+        NetWorthParams
+    ),
+    responses(
+        (status = 200, description = "Net Worth Report", body = NetWorthReport),
+        (status = 400, description = "No FX Rate Available", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[tracing::instrument(skip(report_service))]
+#[get("/reports/net-worth")]
+pub async fn get_net_worth(
+    report_service: Data<ReportService>,
+    params: Query<NetWorthParams>,
+) -> Result<HttpResponse, actix_web::Error> {
+    info!("Getting net worth report");
+
+    let filter = NetWorthFilter {
+        as_of: params.as_of.unwrap_or_else(Utc::now),
+        include_inactive: params.include_inactive.unwrap_or_default(),
+    };
+    let report = report_service
+        .net_worth(filter, params.report_currency)
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(ContentType::json())
+        .body(to_string(&report).unwrap()))
+}
+
+#[utoipa::path(
+    params(
+        CashFlowParams
+    ),
+    responses(
+        (status = 200, description = "Cash Flow Report", body = CashFlowReport),
+        (status = 400, description = "No FX Rate Available", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[tracing::instrument(skip(report_service))]
+#[get("/reports/cash-flow")]
+pub async fn get_cash_flow(
+    report_service: Data<ReportService>,
+    params: Query<CashFlowParams>,
+) -> Result<HttpResponse, actix_web::Error> {
+    info!("Getting cash flow report of {}", params.year);
+
+    let report = report_service
+        .cash_flow(params.year, params.report_currency)
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(ContentType::json())
+        .body(to_string(&report).unwrap()))
+}
@@ -0,0 +1,200 @@
+use crate::error::ApiError;
+use crate::model::transaction::{
+    TransactionFilter, TransactionOperation, TransactionRequest, TransactionStatus,
+    TransactionType, TransferRequest,
+};
+use crate::model::CurrencyCode;
+use crate::service::TransactionService;
+use actix_web::http::header::ContentType;
+use actix_web::web::{Data, Json, Path, Query};
+use lambda_web::actix_web::{get, post, HttpRequest, HttpResponse};
+use serde::Deserialize;
+use serde_json::to_string;
+use tracing::info;
+use utoipa::IntoParams;
+
+static IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+#[derive(Deserialize, Debug, IntoParams)]
+pub struct ListTransactionParams {
+    pub source_id: Option<String>,
+    pub destination_id: Option<String>,
+    pub expense_id: Option<String>,
+    pub operation: Option<TransactionOperation>,
+    pub transaction_type: Option<TransactionType>,
+    pub transaction_status: Option<TransactionStatus>,
+    pub month: Option<u32>,
+    pub year: Option<i32>,
+    /// Converts every amount into this currency using the rate valid at its date
+    pub report_currency: Option<CurrencyCode>,
+}
+
+impl From<ListTransactionParams> for TransactionFilter {
+    fn from(params: ListTransactionParams) -> Self {
+        TransactionFilter {
+            source_id: params.source_id,
+            destination_id: params.destination_id,
+            expense_id: params.expense_id,
+            operation: params.operation,
+            transaction_type: params.transaction_type,
+            status: params.transaction_status,
+            month: params.month,
+            year: params.year,
+        }
+    }
+}
+
+#[utoipa::path(
+    params(
+        ListTransactionParams
+    ),
+    responses(
+        (status = 200, description = "List Transactions", body = [Transaction]),
+        (status = 400, description = "No FX Rate Available", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[tracing::instrument(skip(transaction_service))]
+#[get("/transactions")]
+pub async fn list_transactions(
+    transaction_service: Data<TransactionService>,
+    params: Query<ListTransactionParams>,
+) -> Result<HttpResponse, actix_web::Error> {
+    info!("Listing transactions");
+
+    let params = params.into_inner();
+    let report_currency = params.report_currency;
+    let transactions = transaction_service
+        .list_transactions(params.into(), report_currency)
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(ContentType::json())
+        .body(to_string(&transactions).unwrap()))
+}
+
+#[utoipa::path(
+    params(
+        ("id" = String, Path, description = "Transaction id")
+    ),
+    responses(
+        (status = 200, description = "Transaction Found", body = Transaction),
+        (status = 404, description = "Transaction Not Found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[tracing::instrument(skip(transaction_service))]
+#[get("/transactions/{id}")]
+pub async fn get_transaction(
+    transaction_service: Data<TransactionService>,
+    id: Path<String>,
+) -> Result<HttpResponse, actix_web::Error> {
+    info!("Getting transaction");
+
+    let id = id.into_inner();
+    let transaction = transaction_service
+        .get_transaction(id.clone())
+        .await?
+        .ok_or(ApiError::NotFound {
+            message: format!("Transaction {id} not found"),
+        })?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(ContentType::json())
+        .body(to_string(&transaction).unwrap()))
+}
+
+#[utoipa::path(
+    request_body = TransactionRequest,
+    responses(
+        (status = 201, description = "Transaction Created", body = ResourceId),
+        (status = 400, description = "Invalid Transaction", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Expense Changed Concurrently", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[tracing::instrument(skip(transaction_service))]
+#[post("/transactions")]
+pub async fn create_transaction(
+    transaction_service: Data<TransactionService>,
+    request: Json<TransactionRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    info!("Creating transaction");
+
+    request.validate()?;
+    let transaction_id = transaction_service
+        .create_transaction(request.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Created()
+        .content_type(ContentType::json())
+        .body(to_string(&transaction_id).unwrap()))
+}
+
+#[utoipa::path(
+    params(
+        ("id" = String, Path, description = "Transaction id")
+    ),
+    request_body = TransactionRequest,
+    responses(
+        (status = 200, description = "Transaction Updated", body = ResourceId),
+        (status = 400, description = "Invalid Transaction", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Expense Changed Concurrently", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[tracing::instrument(skip(transaction_service))]
+#[post("/transactions/{id}")]
+pub async fn update_transaction(
+    transaction_service: Data<TransactionService>,
+    id: Path<String>,
+    request: Json<TransactionRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    info!("Updating transaction");
+
+    request.validate()?;
+    let transaction_id = transaction_service
+        .update_transaction(id.into_inner(), request.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(ContentType::json())
+        .body(to_string(&transaction_id).unwrap()))
+}
+
+#[utoipa::path(
+    params(
+        ("Idempotency-Key" = String, Header, description = "Unique to the transfer, sent again on every retry of it")
+    ),
+    request_body = TransferRequest,
+    responses(
+        (status = 201, description = "Transfer Created", body = Transfer),
+        (status = 400, description = "Invalid Transfer Or Missing Idempotency Key", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Another Balance Recorded For The Month, Account Closed Or Idempotency Key Used For Another Transfer", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[tracing::instrument(skip(transaction_service, http_request))]
+#[post("/transfers")]
+pub async fn create_transfer(
+    transaction_service: Data<TransactionService>,
+    http_request: HttpRequest,
+    request: Json<TransferRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    info!("Creating transfer");
+
+    request.validate()?;
+    let idempotency_key = http_request
+        .headers()
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .ok_or(ApiError::Validation {
+            message: format!("{IDEMPOTENCY_KEY_HEADER} header is required"),
+        })?
+        .to_str()
+        .map(String::from)
+        .map_err(|_| ApiError::Validation {
+            message: format!("{IDEMPOTENCY_KEY_HEADER} must be visible ASCII"),
+        })?;
+    let transfer = transaction_service
+        .transfer(request.into_inner(), idempotency_key)
+        .await?;
+
+    Ok(HttpResponse::Created()
+        .content_type(ContentType::json())
+        .body(to_string(&transfer).unwrap()))
+}
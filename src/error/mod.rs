@@ -1,32 +1,110 @@
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
 
+/// Failures surfaced by the API, classified by how a client should react to them.
 #[derive(Debug)]
-pub struct PermanentError {
-    pub source: Box<dyn Error>,
-    pub message: Option<String>,
+pub enum ApiError {
+    Validation {
+        message: String,
+    },
+    NotFound {
+        message: String,
+    },
+    Conflict {
+        message: String,
+    },
+    /// Failures expected to succeed when retried, like throttling or timeouts
+    Transient {
+        source: Box<dyn Error + Send + Sync>,
+        message: Option<String>,
+    },
+    Permanent {
+        source: Box<dyn Error + Send + Sync>,
+        message: Option<String>,
+    },
 }
 
-impl Display for PermanentError {
+impl ApiError {
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, ApiError::Transient { .. })
+    }
+}
+
+impl Display for ApiError {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
-        let source_message = format!("{}", self.source.as_ref());
-        let exception_message = self
-            .message
-            .as_ref()
-            .map(|message| format!(": {message}"))
-            .unwrap_or("".to_string());
+        match self {
+            ApiError::Validation { message }
+            | ApiError::NotFound { message }
+            | ApiError::Conflict { message } => write!(formatter, "{message}"),
+            ApiError::Transient { source, message } | ApiError::Permanent { source, message } => {
+                write!(formatter, "{source}")?;
+                if let Some(message) = message {
+                    write!(formatter, ": {message}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for ApiError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ApiError::Transient { source, .. } | ApiError::Permanent { source, .. } => {
+                Some(source.as_ref())
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct DecodeError {
+    pub attribute: String,
+    pub item_id: Option<String>,
+    pub message: String,
+}
 
+impl DecodeError {
+    pub fn new(attribute: &str, message: &str) -> Self {
+        DecodeError {
+            attribute: attribute.to_string(),
+            item_id: None,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn within(self, parent: &str) -> Self {
+        DecodeError {
+            attribute: format!("{parent}.{}", self.attribute),
+            ..self
+        }
+    }
+
+    pub fn with_item_id(self, item_id: Option<String>) -> Self {
+        DecodeError { item_id, ..self }
+    }
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             formatter,
-            "{}{}",
-            source_message.as_str(),
-            exception_message.as_str()
+            "Failed to decode attribute {} of item {}: {}",
+            self.attribute,
+            self.item_id.as_deref().unwrap_or("<unknown>"),
+            self.message
         )
     }
 }
 
-impl Error for PermanentError {
-    fn source(&self) -> Option<&(dyn Error + 'static)> {
-        Some(self.source.as_ref())
+impl Error for DecodeError {}
+
+impl From<DecodeError> for ApiError {
+    fn from(value: DecodeError) -> Self {
+        ApiError::Permanent {
+            message: None,
+            source: Box::new(value),
+        }
     }
 }
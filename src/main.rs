@@ -1,11 +1,27 @@
+use actix_web::middleware::from_fn;
 use actix_web::web::Data;
 use lambda_web::actix_web::{self, App, HttpServer};
 use lambda_web::{is_running_on_lambda, run_actix_on_lambda, LambdaError};
 use playground_api::config::api_docs::ApiDoc;
+use playground_api::config::settings::Settings;
 use playground_api::config::telemetry::{get_subscriber, init_subscriber};
-use playground_api::controller::account_controller::list_accounts;
-use playground_api::repository::ConfigProvider;
-use playground_api::service::AccountService;
+use playground_api::controller::account_controller::{
+    add_account_balance, create_account, get_account, list_account_balances, list_accounts,
+    update_account,
+};
+use playground_api::controller::expense_controller::{
+    create_expense, get_expense, list_due_payments, list_expenses, schedule_expense_payments,
+    update_expense, update_expense_payment,
+};
+use playground_api::controller::problem::problem_details;
+use playground_api::controller::report_controller::{get_cash_flow, get_net_worth};
+use playground_api::controller::transaction_controller::{
+    create_transaction, create_transfer, get_transaction, list_transactions, update_transaction,
+};
+use playground_api::repository::{build_database_client, build_fx_rate_provider};
+use playground_api::service::{
+    AccountService, ExpensePaymentService, ExpenseService, ReportService, TransactionService,
+};
 use tracing_actix_web::TracingLogger;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
@@ -15,16 +31,62 @@ async fn main() -> Result<(), LambdaError> {
     let subscriber = get_subscriber("playground-api".into(), "info".into(), std::io::stdout);
     init_subscriber(subscriber);
 
-    let config_provider = ConfigProvider::default().provide().await;
+    let settings = Settings::from_env();
+    let client = build_database_client(&settings).await?;
+    let fx_rates = build_fx_rate_provider(client.clone(), &settings)?;
     let factory = move || {
         App::new()
+            .wrap(from_fn(problem_details))
             .wrap(TracingLogger::default())
             .service(list_accounts)
+            .service(get_account)
+            .service(create_account)
+            .service(update_account)
+            .service(add_account_balance)
+            .service(list_account_balances)
+            .service(list_transactions)
+            .service(get_transaction)
+            .service(create_transaction)
+            .service(update_transaction)
+            .service(create_transfer)
+            .service(list_expenses)
+            .service(list_due_payments)
+            .service(get_expense)
+            .service(create_expense)
+            .service(update_expense)
+            .service(update_expense_payment)
+            .service(schedule_expense_payments)
+            .service(get_net_worth)
+            .service(get_cash_flow)
             .service(
                 SwaggerUi::new("/swagger-ui/{_:.*}")
                     .url("/api-docs/openapi.json", ApiDoc::openapi()),
             )
-            .app_data(Data::new(AccountService::new(&config_provider)))
+            .app_data(Data::new(AccountService::new(
+                client.clone(),
+                fx_rates.clone(),
+                &settings,
+            )))
+            .app_data(Data::new(TransactionService::new(
+                client.clone(),
+                fx_rates.clone(),
+                &settings,
+            )))
+            .app_data(Data::new(ExpenseService::new(
+                client.clone(),
+                fx_rates.clone(),
+                &settings,
+            )))
+            .app_data(Data::new(ExpensePaymentService::new(
+                client.clone(),
+                fx_rates.clone(),
+                &settings,
+            )))
+            .app_data(Data::new(ReportService::new(
+                client.clone(),
+                fx_rates.clone(),
+                &settings,
+            )))
     };
 
     if is_running_on_lambda() {
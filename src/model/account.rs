@@ -1,5 +1,6 @@
-use crate::model::{Currency, ParseEnumError};
-use chrono::NaiveDateTime;
+use crate::error::ApiError;
+use crate::model::{Currency, CurrencyCode, ParseEnumError};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
@@ -22,27 +23,181 @@ pub enum AccountStatus {
     NotInUse,
 }
 
-#[derive(Serialize, Deserialize, ToSchema)]
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, ToSchema)]
 pub struct AccountBalance {
     #[schema(value_type = String, format = DateTime)]
-    pub date: NaiveDateTime,
+    pub date: DateTime<Utc>,
     pub balance: Currency,
 }
 
-#[derive(Serialize, Deserialize, ToSchema)]
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
 pub struct Account {
     pub id: String,
     pub name: String,
     pub bank_name: String,
     #[schema(value_type = String, format = DateTime)]
-    pub open_date: NaiveDateTime,
+    pub open_date: DateTime<Utc>,
     #[schema(value_type = String, format = DateTime)]
-    pub close_date: Option<NaiveDateTime>,
+    pub close_date: Option<DateTime<Utc>>,
     pub account_type: AccountType,
     pub balances: Vec<AccountBalance>,
     pub status: AccountStatus,
 }
 
+#[derive(Default, PartialEq, Debug)]
+pub struct BalanceFilter {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// Keeps only the last balance of each month
+    pub monthly: bool,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+pub struct AccountRequest {
+    pub name: String,
+    pub bank_name: String,
+    #[schema(value_type = String, format = DateTime)]
+    pub open_date: DateTime<Utc>,
+    #[schema(value_type = String, format = DateTime)]
+    pub close_date: Option<DateTime<Utc>>,
+    pub account_type: AccountType,
+    pub status: AccountStatus,
+}
+
+impl AccountRequest {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        if self.name.trim().is_empty() {
+            return Err(ApiError::Validation {
+                message: "name must not be blank".to_string(),
+            });
+        }
+
+        if self.bank_name.trim().is_empty() {
+            return Err(ApiError::Validation {
+                message: "bank_name must not be blank".to_string(),
+            });
+        }
+
+        if let Some(close_date) = self.close_date {
+            if close_date < self.open_date {
+                return Err(ApiError::Validation {
+                    message: "close_date must not be before open_date".to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn into_account(self, id: String) -> Account {
+        Account {
+            id,
+            name: self.name,
+            bank_name: self.bank_name,
+            open_date: self.open_date,
+            close_date: self.close_date,
+            account_type: self.account_type,
+            balances: vec![],
+            status: self.status,
+        }
+    }
+}
+
+impl AccountBalance {
+    /// Month of the balance as `yyyy-mm`. An account records at most one balance per month.
+    pub fn month(&self) -> String {
+        self.date.format("%Y-%m").to_string()
+    }
+}
+
+impl Account {
+    /// Checks whether `balance` can be added, returning `true` when the very same balance is
+    /// already recorded so repeated requests succeed without appending it again.
+    pub fn check_new_balance(&self, balance: &AccountBalance) -> Result<bool, ApiError> {
+        if balance.date < self.open_date {
+            return Err(ApiError::Validation {
+                message: "date must not be before the account open_date".to_string(),
+            });
+        }
+
+        let month = balance.month();
+        match self
+            .balances
+            .iter()
+            .find(|existing| existing.month() == month)
+        {
+            Some(existing) if existing == balance => Ok(true),
+            Some(_) => Err(ApiError::Conflict {
+                message: format!("Account {} already has a balance for {month}", self.id),
+            }),
+            None => Ok(false),
+        }
+    }
+
+    /// Currency of the latest recorded balance, unknown until a balance is recorded.
+    pub fn currency(&self) -> Option<CurrencyCode> {
+        self.balances
+            .iter()
+            .max_by_key(|balance| balance.date)
+            .map(|balance| balance.balance.code)
+    }
+
+    /// Checks the account can send or receive a transfer of `code`.
+    pub fn check_transfer(&self, code: CurrencyCode) -> Result<(), ApiError> {
+        if self.status == AccountStatus::Closed {
+            return Err(ApiError::Validation {
+                message: format!("Account {} is closed", self.id),
+            });
+        }
+
+        match self.currency() {
+            Some(currency) if currency != code => Err(ApiError::Validation {
+                message: format!("Account {} holds {currency}, not {code}", self.id),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Balances within the filter's date range, sorted by date.
+    pub fn balance_history(&self, filter: &BalanceFilter) -> Vec<AccountBalance> {
+        let mut balances: Vec<AccountBalance> = self
+            .balances
+            .iter()
+            .filter(|balance| filter.contains(balance))
+            .cloned()
+            .collect();
+        balances.sort_by_key(|balance| balance.date);
+
+        if filter.monthly {
+            // Sorted by date, so the last entry of each month is the one kept
+            balances.reverse();
+            balances.dedup_by_key(|balance| balance.month());
+            balances.reverse();
+        }
+
+        balances
+    }
+}
+
+impl BalanceFilter {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        if let (Some(from), Some(to)) = (self.from, self.to) {
+            if to < from {
+                return Err(ApiError::Validation {
+                    message: "to must not be before from".to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn contains(&self, balance: &AccountBalance) -> bool {
+        self.from.is_none_or(|from| balance.date >= from)
+            && self.to.is_none_or(|to| balance.date <= to)
+    }
+}
+
 impl Display for AccountType {
     fn fmt(&self, formatter: &mut Formatter) -> std::fmt::Result {
         match self {
@@ -102,7 +257,11 @@ impl FromStr for AccountStatus {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::CurrencyCode;
+    use chrono::NaiveDateTime;
     use rstest::rstest;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
 
     #[rstest]
     #[case(AccountType::Salary, "SALARY".to_string())]
@@ -172,6 +331,143 @@ mod tests {
         assert!(account_status.is_err());
     }
 
+    #[test]
+    fn should_accept_valid_account_request() {
+        assert!(build_account_request().validate().is_ok());
+    }
+
+    #[rstest]
+    #[case("", "bank", None)]
+    #[case("  ", "bank", None)]
+    #[case("name", "", None)]
+    #[case("name", "bank", Some("14/02/2023 13:51:12"))]
+    fn should_reject_invalid_account_request(
+        #[case] name: &str,
+        #[case] bank_name: &str,
+        #[case] close_date: Option<&str>,
+    ) {
+        let request = AccountRequest {
+            name: name.to_string(),
+            bank_name: bank_name.to_string(),
+            close_date: close_date.map(parse_date),
+            ..build_account_request()
+        };
+
+        assert!(request.validate().is_err());
+    }
+
+    #[test]
+    fn should_convert_account_request_into_account() {
+        let account = build_account_request().into_account("id".to_string());
+
+        assert_eq!("id", account.id);
+        assert_eq!("name", account.name);
+        assert_eq!("bank", account.bank_name);
+        assert_eq!(parse_date("15/02/2023 13:51:12"), account.open_date);
+        assert_eq!(None, account.close_date);
+        assert_eq!(AccountType::Checking, account.account_type);
+        assert!(account.balances.is_empty());
+        assert_eq!(AccountStatus::Open, account.status);
+    }
+
+    #[rstest]
+    #[case("28/02/2023 23:59:59", dec!(10), Ok(true))]
+    #[case("20/02/2023 00:00:00", dec!(10), Err("Account id already has a balance for 2023-02"))]
+    #[case("31/03/2023 23:59:59", dec!(10), Ok(false))]
+    #[case("28/02/2023 23:59:59", dec!(20), Err("Account id already has a balance for 2023-02"))]
+    #[case("31/01/2023 23:59:59", dec!(10), Err("date must not be before the account open_date"))]
+    fn should_check_new_balance_against_recorded_months(
+        #[case] date: &str,
+        #[case] value: Decimal,
+        #[case] expected: Result<bool, &str>,
+    ) {
+        let mut account = build_account_request().into_account("id".to_string());
+        account
+            .balances
+            .push(build_balance("28/02/2023 23:59:59", dec!(10)));
+
+        let result = account.check_new_balance(&build_balance(date, value));
+
+        assert_eq!(
+            expected.map_err(str::to_string),
+            result.map_err(|error| error.to_string())
+        );
+    }
+
+    #[rstest]
+    #[case(None, None, false, vec![dec!(1), dec!(2), dec!(3), dec!(4)])]
+    #[case(None, None, true, vec![dec!(2), dec!(3), dec!(4)])]
+    #[case(Some("01/03/2023 00:00:00"), None, false, vec![dec!(3), dec!(4)])]
+    #[case(None, Some("31/03/2023 23:59:59"), true, vec![dec!(2), dec!(3)])]
+    #[case(Some("01/04/2023 00:00:00"), Some("01/04/2023 00:00:00"), false, vec![])]
+    fn should_return_balance_history_sorted_by_date(
+        #[case] from: Option<&str>,
+        #[case] to: Option<&str>,
+        #[case] monthly: bool,
+        #[case] expected_values: Vec<Decimal>,
+    ) {
+        let mut account = build_account_request().into_account("id".to_string());
+        account.balances = vec![
+            build_balance("30/04/2023 23:59:59", dec!(4)),
+            build_balance("28/02/2023 23:59:59", dec!(2)),
+            build_balance("31/03/2023 23:59:59", dec!(3)),
+            build_balance("20/02/2023 00:00:00", dec!(1)),
+        ];
+        let filter = BalanceFilter {
+            from: from.map(parse_date),
+            to: to.map(parse_date),
+            monthly,
+        };
+
+        let history = account.balance_history(&filter);
+
+        assert_eq!(
+            expected_values,
+            history
+                .iter()
+                .map(|balance| balance.balance.value)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[rstest]
+    #[case(AccountStatus::Open, CurrencyCode::BRL, true)]
+    #[case(AccountStatus::NotInUse, CurrencyCode::BRL, true)]
+    #[case(AccountStatus::Closed, CurrencyCode::BRL, false)]
+    #[case(AccountStatus::Open, CurrencyCode::USD, false)]
+    fn should_accept_transfers_of_open_accounts_in_their_currency(
+        #[case] status: AccountStatus,
+        #[case] code: CurrencyCode,
+        #[case] expected: bool,
+    ) {
+        let mut account = build_account_request().into_account("id".to_string());
+        account.status = status;
+        account
+            .balances
+            .push(build_balance("28/02/2023 23:59:59", dec!(10)));
+
+        assert_eq!(expected, account.check_transfer(code).is_ok());
+    }
+
+    #[test]
+    fn should_accept_transfer_of_any_currency_without_balances() {
+        let account = build_account_request().into_account("id".to_string());
+
+        assert_eq!(None, account.currency());
+        assert!(account.check_transfer(CurrencyCode::USD).is_ok());
+    }
+
+    #[test]
+    fn should_reject_balance_filter_ending_before_start() {
+        let filter = BalanceFilter {
+            from: Some(parse_date("01/03/2023 00:00:00")),
+            to: Some(parse_date("28/02/2023 00:00:00")),
+            monthly: false,
+        };
+
+        assert!(filter.validate().is_err());
+    }
+
     #[test]
     fn account_type_should_be_thread_safe() {
         is_thread_safe::<AccountType>()
@@ -192,5 +488,34 @@ mod tests {
         is_thread_safe::<Account>();
     }
 
+    #[test]
+    fn account_request_should_be_thread_safe() {
+        is_thread_safe::<AccountRequest>();
+    }
+
     fn is_thread_safe<T: Sized + Send + Sync + Unpin>() {}
+
+    fn build_balance(date: &str, value: Decimal) -> AccountBalance {
+        AccountBalance {
+            date: parse_date(date),
+            balance: Currency::new(CurrencyCode::BRL, value),
+        }
+    }
+
+    fn build_account_request() -> AccountRequest {
+        AccountRequest {
+            name: "name".to_string(),
+            bank_name: "bank".to_string(),
+            open_date: parse_date("15/02/2023 13:51:12"),
+            close_date: None,
+            account_type: AccountType::Checking,
+            status: AccountStatus::Open,
+        }
+    }
+
+    fn parse_date(value: &str) -> DateTime<Utc> {
+        NaiveDateTime::parse_from_str(value, "%d/%m/%Y %H:%M:%S")
+            .unwrap()
+            .and_utc()
+    }
 }
@@ -0,0 +1,275 @@
+use crate::error::ApiError;
+use crate::model::ParseEnumError;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+macro_rules! currency_codes {
+    ($($code:ident => $minor_units:literal),* $(,)?) => {
+        /// ISO 4217 currencies in circulation, excluding precious metals and testing codes.
+        #[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Copy, Clone, ToSchema)]
+        pub enum CurrencyCode {
+            $($code),*
+        }
+
+        impl CurrencyCode {
+            pub const VALUES: &'static [CurrencyCode] = &[$(CurrencyCode::$code),*];
+
+            /// Number of decimal places of the currency's minor unit, e.g. cents.
+            pub fn minor_units(&self) -> u32 {
+                match self {
+                    $(CurrencyCode::$code => $minor_units),*
+                }
+            }
+        }
+
+        impl Display for CurrencyCode {
+            fn fmt(&self, formatter: &mut Formatter) -> std::fmt::Result {
+                match self {
+                    $(CurrencyCode::$code => write!(formatter, stringify!($code))),*
+                }
+            }
+        }
+
+        impl FromStr for CurrencyCode {
+            type Err = ParseEnumError;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                match value {
+                    $(stringify!($code) => Ok(CurrencyCode::$code),)*
+                    _ => Err(ParseEnumError {
+                        message: format!("Invalid CurrencyCode {value}"),
+                    }),
+                }
+            }
+        }
+    };
+}
+
+currency_codes! {
+    AED => 2, AFN => 2, ALL => 2, AMD => 2, ANG => 2, AOA => 2, ARS => 2, AUD => 2,
+    AWG => 2, AZN => 2, BAM => 2, BBD => 2, BDT => 2, BGN => 2, BHD => 3, BIF => 0,
+    BMD => 2, BND => 2, BOB => 2, BOV => 2, BRL => 2, BSD => 2, BTN => 2, BWP => 2,
+    BYN => 2, BZD => 2, CAD => 2, CDF => 2, CHE => 2, CHF => 2, CHW => 2, CLF => 4,
+    CLP => 0, CNY => 2, COP => 2, COU => 2, CRC => 2, CUP => 2, CVE => 2, CZK => 2,
+    DJF => 0, DKK => 2, DOP => 2, DZD => 2, EGP => 2, ERN => 2, ETB => 2, EUR => 2,
+    FJD => 2, FKP => 2, GBP => 2, GEL => 2, GHS => 2, GIP => 2, GMD => 2, GNF => 0,
+    GTQ => 2, GYD => 2, HKD => 2, HNL => 2, HTG => 2, HUF => 2, IDR => 2, ILS => 2,
+    INR => 2, IQD => 3, IRR => 2, ISK => 0, JMD => 2, JOD => 3, JPY => 0, KES => 2,
+    KGS => 2, KHR => 2, KMF => 0, KPW => 2, KRW => 0, KWD => 3, KYD => 2, KZT => 2,
+    LAK => 2, LBP => 2, LKR => 2, LRD => 2, LSL => 2, LYD => 3, MAD => 2, MDL => 2,
+    MGA => 2, MKD => 2, MMK => 2, MNT => 2, MOP => 2, MRU => 2, MUR => 2, MVR => 2,
+    MWK => 2, MXN => 2, MXV => 2, MYR => 2, MZN => 2, NAD => 2, NGN => 2, NIO => 2,
+    NOK => 2, NPR => 2, NZD => 2, OMR => 3, PAB => 2, PEN => 2, PGK => 2, PHP => 2,
+    PKR => 2, PLN => 2, PYG => 0, QAR => 2, RON => 2, RSD => 2, RUB => 2, RWF => 0,
+    SAR => 2, SBD => 2, SCR => 2, SDG => 2, SEK => 2, SGD => 2, SHP => 2, SLE => 2,
+    SOS => 2, SRD => 2, SSP => 2, STN => 2, SVC => 2, SYP => 2, SZL => 2, THB => 2,
+    TJS => 2, TMT => 2, TND => 3, TOP => 2, TRY => 2, TTD => 2, TWD => 2, TZS => 2,
+    UAH => 2, UGX => 0, USD => 2, USN => 2, UYI => 0, UYU => 2, UYW => 4, UZS => 2,
+    VED => 2, VES => 2, VND => 0, VUV => 0, WST => 2, XAF => 0, XCD => 2, XCG => 2,
+    XOF => 0, XPF => 0, YER => 2, ZAR => 2, ZMW => 2, ZWG => 2,
+}
+
+/// Exact monetary amount, serialized as a decimal string so no precision is lost on the wire.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, ToSchema)]
+pub struct Currency {
+    pub code: CurrencyCode,
+    #[schema(value_type = String, example = "10.50")]
+    pub value: Decimal,
+}
+
+impl Currency {
+    pub fn new(code: CurrencyCode, value: Decimal) -> Self {
+        Currency { code, value }
+    }
+
+    pub fn zero(code: CurrencyCode) -> Self {
+        Currency::new(code, Decimal::ZERO)
+    }
+
+    pub fn try_add(&self, other: &Currency) -> Result<Currency, ApiError> {
+        self.combine(other, Decimal::checked_add)
+    }
+
+    pub fn try_sub(&self, other: &Currency) -> Result<Currency, ApiError> {
+        self.combine(other, Decimal::checked_sub)
+    }
+
+    pub fn negate(&self) -> Currency {
+        Currency::new(self.code, -self.value)
+    }
+
+    /// Rounds the amount to the minor unit of its currency, half away from zero.
+    pub fn rounded(&self) -> Currency {
+        Currency::new(
+            self.code,
+            self.value.round_dp_with_strategy(
+                self.code.minor_units(),
+                RoundingStrategy::MidpointAwayFromZero,
+            ),
+        )
+    }
+
+    pub fn is_positive(&self) -> bool {
+        self.value > Decimal::ZERO
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.value < Decimal::ZERO
+    }
+
+    fn combine(
+        &self,
+        other: &Currency,
+        operation: fn(Decimal, Decimal) -> Option<Decimal>,
+    ) -> Result<Currency, ApiError> {
+        if self.code != other.code {
+            return Err(ApiError::Validation {
+                message: format!("Cannot combine {} with {} amounts", self.code, other.code),
+            });
+        }
+
+        operation(self.value, other.value)
+            .map(|value| Currency::new(self.code, value))
+            .ok_or(ApiError::Validation {
+                message: format!("{} amount out of range", self.code),
+            })
+    }
+}
+
+impl Display for Currency {
+    fn fmt(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        let mut value = self.rounded().value;
+        value.rescale(self.code.minor_units());
+        write!(formatter, "{} {}", self.code, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use rust_decimal_macros::dec;
+
+    #[rstest]
+    #[case(CurrencyCode::BRL, "BRL".to_string())]
+    #[case(CurrencyCode::USD, "USD".to_string())]
+    #[case(CurrencyCode::JPY, "JPY".to_string())]
+    fn should_return_string_representation_of_currency_code(
+        #[case] currency_code: CurrencyCode,
+        #[case] expected_string: String,
+    ) {
+        assert_eq!(expected_string, currency_code.to_string())
+    }
+
+    #[rstest]
+    #[case(CurrencyCode::BRL, "BRL")]
+    #[case(CurrencyCode::EUR, "EUR")]
+    #[case(CurrencyCode::KWD, "KWD")]
+    fn should_return_enum_from_string_of_currency_code(
+        #[case] expected_currency_code: CurrencyCode,
+        #[case] enum_string: &str,
+    ) {
+        let currency_enum = CurrencyCode::from_str(enum_string);
+        assert!(currency_enum.is_ok());
+        assert_eq!(expected_currency_code, currency_enum.unwrap());
+    }
+
+    #[test]
+    fn should_return_err_when_currency_code_does_not_exist() {
+        let currency_code = CurrencyCode::from_str("batata");
+
+        assert!(currency_code.is_err());
+    }
+
+    #[test]
+    fn should_add_and_subtract_amounts_exactly() {
+        let first = Currency::new(CurrencyCode::BRL, dec!(0.1));
+        let second = Currency::new(CurrencyCode::BRL, dec!(0.2));
+
+        assert_eq!(dec!(0.3), first.try_add(&second).unwrap().value);
+        assert_eq!(dec!(-0.1), first.try_sub(&second).unwrap().value);
+        assert!(first.try_sub(&second).unwrap().is_negative());
+        assert!(first.negate().try_add(&second).unwrap().is_positive());
+    }
+
+    #[test]
+    fn should_reject_arithmetic_overflow() {
+        let max = Currency::new(CurrencyCode::BRL, Decimal::MAX);
+
+        assert!(max.try_add(&max).is_err());
+    }
+
+    #[test]
+    fn should_serialize_amount_as_string() {
+        let currency = Currency::new(CurrencyCode::BRL, dec!(10.50));
+
+        let json = serde_json::to_string(&currency).unwrap();
+
+        assert_eq!(r#"{"code":"BRL","value":"10.50"}"#, json);
+        assert_eq!(currency, serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn should_round_trip_every_currency_code() {
+        for code in CurrencyCode::VALUES {
+            assert_eq!(
+                *code,
+                CurrencyCode::from_str(code.to_string().as_str()).unwrap()
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(CurrencyCode::BRL, 2)]
+    #[case(CurrencyCode::JPY, 0)]
+    #[case(CurrencyCode::BHD, 3)]
+    #[case(CurrencyCode::CLF, 4)]
+    fn should_return_minor_units_of_currency_code(
+        #[case] code: CurrencyCode,
+        #[case] expected_minor_units: u32,
+    ) {
+        assert_eq!(expected_minor_units, code.minor_units());
+    }
+
+    #[rstest]
+    #[case(CurrencyCode::BRL, dec!(10.125), dec!(10.13), "BRL 10.13")]
+    #[case(CurrencyCode::BRL, dec!(-10.125), dec!(-10.13), "BRL -10.13")]
+    #[case(CurrencyCode::BRL, dec!(10), dec!(10), "BRL 10.00")]
+    #[case(CurrencyCode::JPY, dec!(1500.5), dec!(1501), "JPY 1501")]
+    #[case(CurrencyCode::KWD, dec!(1.23456), dec!(1.235), "KWD 1.235")]
+    fn should_round_and_format_to_minor_units(
+        #[case] code: CurrencyCode,
+        #[case] value: Decimal,
+        #[case] expected_value: Decimal,
+        #[case] expected_string: &str,
+    ) {
+        let currency = Currency::new(code, value);
+
+        assert_eq!(expected_value, currency.rounded().value);
+        assert_eq!(expected_string, currency.to_string());
+    }
+
+    #[test]
+    fn should_reject_mixed_currencies() {
+        let real = Currency::new(CurrencyCode::BRL, dec!(1));
+        let dollar = Currency::new(CurrencyCode::USD, dec!(1));
+
+        assert!(real.try_add(&dollar).is_err());
+        assert!(real.try_sub(&dollar).is_err());
+    }
+
+    #[test]
+    fn currency_code_should_be_thread_safe() {
+        is_thread_safe::<CurrencyCode>();
+    }
+
+    #[test]
+    fn currency_should_be_thread_safe() {
+        is_thread_safe::<Currency>();
+    }
+
+    fn is_thread_safe<T: Sized + Send + Sync + Unpin>() {}
+}
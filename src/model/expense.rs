@@ -0,0 +1,1020 @@
+use crate::error::ApiError;
+use crate::model::{Currency, ParseEnumError};
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Copy, Clone, ToSchema)]
+pub enum ExpenseType {
+    Fixed,
+    Variable,
+    OneTime,
+    Lend,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Copy, Clone, ToSchema)]
+pub enum ExpenseStatus {
+    Open,
+    Closed,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Copy, Clone, ToSchema)]
+pub enum PaymentStatus {
+    Paid,
+    Unpaid,
+    Planned,
+    Cancelled,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+pub struct ExpenseValue {
+    #[schema(value_type = String, format = DateTime)]
+    pub due_date: DateTime<Utc>,
+    #[schema(value_type = String, format = DateTime)]
+    pub added_date: DateTime<Utc>,
+    pub value: Currency,
+}
+
+impl ExpenseValue {
+    fn rounded(self) -> Self {
+        ExpenseValue {
+            value: self.value.rounded(),
+            ..self
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, ToSchema)]
+pub struct ExpensePayment {
+    #[schema(value_type = String, format = DateTime)]
+    pub due_date: DateTime<Utc>,
+    #[schema(value_type = String, format = DateTime)]
+    pub payment_date: Option<DateTime<Utc>>,
+    #[schema(value_type = String, format = DateTime)]
+    pub receive_date: Option<DateTime<Utc>>,
+    pub status: PaymentStatus,
+    /// The payment transaction that settled this installment
+    pub transaction_id: Option<String>,
+}
+
+/// Repeats an expense every `interval_months` months on `day_of_month`, counted from the month it
+/// was added. Days past the end of a shorter month fall on its last day.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Copy, Clone, ToSchema)]
+pub struct Recurrence {
+    pub day_of_month: u32,
+    pub interval_months: u32,
+    #[schema(value_type = String, format = DateTime)]
+    pub end_date: Option<DateTime<Utc>>,
+}
+
+/// Changes the status of the installment due at a given date.
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+pub struct PaymentUpdate {
+    pub status: PaymentStatus,
+    #[schema(value_type = String, format = DateTime)]
+    pub payment_date: Option<DateTime<Utc>>,
+    #[schema(value_type = String, format = DateTime)]
+    pub receive_date: Option<DateTime<Utc>>,
+}
+
+/// An installment not paid by its due date, with the expense value in effect at that date.
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+pub struct DuePayment {
+    pub expense_id: String,
+    pub description: String,
+    pub expense_type: ExpenseType,
+    #[schema(value_type = String, format = DateTime)]
+    pub due_date: DateTime<Utc>,
+    pub value: Option<Currency>,
+    pub status: PaymentStatus,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+pub struct Expense {
+    pub id: String,
+    pub description: String,
+    #[schema(value_type = String, format = DateTime)]
+    pub date_added: DateTime<Utc>,
+    pub value_history: Vec<ExpenseValue>,
+    pub payments: Vec<ExpensePayment>,
+    pub expense_type: ExpenseType,
+    pub status: ExpenseStatus,
+    pub recurrence: Option<Recurrence>,
+    /// Number of times the expense was saved, so a save based on a stale read can be rejected
+    #[serde(skip)]
+    pub version: u64,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+pub struct ExpenseRequest {
+    pub description: String,
+    #[schema(value_type = String, format = DateTime)]
+    pub date_added: DateTime<Utc>,
+    pub value: ExpenseValue,
+    pub payment: Option<ExpensePayment>,
+    pub expense_type: ExpenseType,
+    pub status: ExpenseStatus,
+    pub recurrence: Option<Recurrence>,
+}
+
+impl ExpenseRequest {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        if self.description.trim().is_empty() {
+            return Err(ApiError::Validation {
+                message: "description must not be blank".to_string(),
+            });
+        }
+
+        if self.value.value.is_negative() {
+            return Err(ApiError::Validation {
+                message: "value must not be negative".to_string(),
+            });
+        }
+
+        if let Some(recurrence) = &self.recurrence {
+            if !matches!(
+                self.expense_type,
+                ExpenseType::Fixed | ExpenseType::Variable
+            ) {
+                return Err(ApiError::Validation {
+                    message: "recurrence is only supported for FIXED and VARIABLE expenses"
+                        .to_string(),
+                });
+            }
+            recurrence.validate()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn into_expense(self, id: String) -> Expense {
+        Expense {
+            id,
+            description: self.description,
+            date_added: self.date_added,
+            value_history: vec![self.value.rounded()],
+            payments: self.payment.into_iter().collect(),
+            expense_type: self.expense_type,
+            status: self.status,
+            recurrence: self.recurrence,
+            version: 0,
+        }
+    }
+}
+
+/// Longest interval accepted between two occurrences of a recurring expense: ten years.
+pub static MAX_INTERVAL_MONTHS: u32 = 120;
+
+impl Recurrence {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        if !(1..=31).contains(&self.day_of_month) {
+            return Err(ApiError::Validation {
+                message: "day_of_month must be between 1 and 31".to_string(),
+            });
+        }
+
+        if !(1..=MAX_INTERVAL_MONTHS).contains(&self.interval_months) {
+            return Err(ApiError::Validation {
+                message: format!("interval_months must be between 1 and {MAX_INTERVAL_MONTHS}"),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Due dates of the occurrences starting at `start` that fall within `[from, until)`.
+    pub fn due_dates(
+        &self,
+        start: DateTime<Utc>,
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Vec<DateTime<Utc>> {
+        let first_month = start.year() * 12 + start.month0() as i32;
+        (0..)
+            .map(|occurrence| first_month + occurrence * self.interval_months.max(1) as i32)
+            .map_while(|month| {
+                let due_date = self.due_date_in(month)?;
+                (due_date < until).then_some(due_date)
+            })
+            .filter(|due_date| *due_date >= start && *due_date >= from)
+            .filter(|due_date| self.end_date.is_none_or(|end_date| *due_date <= end_date))
+            .collect()
+    }
+
+    fn due_date_in(&self, month: i32) -> Option<DateTime<Utc>> {
+        let (year, month) = (month.div_euclid(12), month.rem_euclid(12) as u32 + 1);
+        (1..=self.day_of_month)
+            .rev()
+            .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))?
+            .and_hms_opt(0, 0, 0)
+            .map(|date| date.and_utc())
+    }
+}
+
+impl PaymentUpdate {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        if self.status == PaymentStatus::Paid && self.payment_date.is_none() {
+            return Err(ApiError::Validation {
+                message: "payment_date must be informed when status is PAID".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl PaymentStatus {
+    /// Whether an installment with this status is still owed.
+    pub fn is_pending(&self) -> bool {
+        matches!(self, PaymentStatus::Unpaid | PaymentStatus::Planned)
+    }
+}
+
+impl Expense {
+    pub fn update_payment(
+        &mut self,
+        due_date: DateTime<Utc>,
+        update: PaymentUpdate,
+    ) -> Result<(), ApiError> {
+        let payment = self
+            .payments
+            .iter_mut()
+            .find(|payment| payment.due_date == due_date)
+            .ok_or(ApiError::NotFound {
+                message: format!(
+                    "Expense {} has no payment due at {}",
+                    self.id,
+                    due_date.to_rfc3339()
+                ),
+            })?;
+
+        // Only a PAID installment keeps its payment date and the transaction that settled it
+        if update.status == PaymentStatus::Paid {
+            payment.payment_date = update.payment_date;
+        } else {
+            payment.payment_date = None;
+            payment.transaction_id = None;
+        }
+        payment.status = update.status;
+        payment.receive_date = update.receive_date;
+
+        Ok(())
+    }
+
+    /// Adds a `PLANNED` payment for every occurrence due within `months` months of `from` that
+    /// has no payment yet, so running it again adds nothing. Returns the added payments.
+    pub fn schedule(
+        &mut self,
+        from: DateTime<Utc>,
+        months: u32,
+    ) -> Result<Vec<ExpensePayment>, ApiError> {
+        let recurrence = self.recurrence.ok_or(ApiError::Validation {
+            message: format!("Expense {} has no recurrence", self.id),
+        })?;
+        if self.status == ExpenseStatus::Closed {
+            return Err(ApiError::Validation {
+                message: format!("Expense {} is closed", self.id),
+            });
+        }
+        let until = from
+            .checked_add_months(Months::new(months))
+            .ok_or(ApiError::Validation {
+                message: format!("months {months} is out of range"),
+            })?;
+
+        let planned: Vec<ExpensePayment> = recurrence
+            .due_dates(self.date_added, from, until)
+            .into_iter()
+            .filter(|due_date| {
+                !self
+                    .payments
+                    .iter()
+                    .any(|payment| payment.due_date.date_naive() == due_date.date_naive())
+            })
+            .map(|due_date| ExpensePayment {
+                due_date,
+                payment_date: None,
+                receive_date: None,
+                status: PaymentStatus::Planned,
+                transaction_id: None,
+            })
+            .collect();
+        self.payments.extend(planned.iter().cloned());
+        self.payments.sort_by_key(|payment| payment.due_date);
+
+        Ok(planned)
+    }
+
+    /// Marks an installment as `PAID` by `transaction_id` at `date`: the one already settled by
+    /// that transaction, else the oldest pending one due in the month of `date`, else the oldest
+    /// pending one.
+    pub fn settle(&mut self, transaction_id: &str, date: DateTime<Utc>) -> Result<(), ApiError> {
+        let month = (date.year(), date.month());
+        let oldest_pending = |in_month: bool| {
+            self.payments
+                .iter()
+                .enumerate()
+                .filter(|(_, payment)| payment.status.is_pending())
+                .filter(|(_, payment)| {
+                    !in_month || (payment.due_date.year(), payment.due_date.month()) == month
+                })
+                .min_by_key(|(_, payment)| payment.due_date)
+                .map(|(position, _)| position)
+        };
+
+        let position = self
+            .payments
+            .iter()
+            .position(|payment| payment.transaction_id.as_deref() == Some(transaction_id))
+            .or_else(|| oldest_pending(true))
+            .or_else(|| oldest_pending(false))
+            .ok_or(ApiError::Validation {
+                message: format!("Expense {} has no pending payment", self.id),
+            })?;
+        let payment = &mut self.payments[position];
+        payment.status = PaymentStatus::Paid;
+        payment.payment_date = Some(date);
+        payment.transaction_id = Some(transaction_id.to_string());
+
+        Ok(())
+    }
+
+    /// Reverts the installment settled by `transaction_id` to `UNPAID`. Returns whether there was
+    /// one.
+    pub fn unsettle(&mut self, transaction_id: &str) -> bool {
+        let Some(payment) = self
+            .payments
+            .iter_mut()
+            .find(|payment| payment.transaction_id.as_deref() == Some(transaction_id))
+        else {
+            return false;
+        };
+        payment.status = PaymentStatus::Unpaid;
+        payment.payment_date = None;
+        payment.transaction_id = None;
+
+        true
+    }
+
+    /// The most recent value whose due date is not after `due_date`.
+    pub fn value_at(&self, due_date: DateTime<Utc>) -> Option<&Currency> {
+        self.value_history
+            .iter()
+            .filter(|entry| entry.due_date <= due_date)
+            .max_by_key(|entry| (entry.due_date, entry.added_date))
+            .map(|entry| &entry.value)
+    }
+
+    /// Pending installments due strictly before `before`.
+    pub fn due_payments(&self, before: DateTime<Utc>) -> Vec<DuePayment> {
+        self.payments
+            .iter()
+            .filter(|payment| payment.status.is_pending() && payment.due_date < before)
+            .map(|payment| DuePayment {
+                expense_id: self.id.clone(),
+                description: self.description.clone(),
+                expense_type: self.expense_type,
+                due_date: payment.due_date,
+                value: self.value_at(payment.due_date).cloned(),
+                status: payment.status,
+            })
+            .collect()
+    }
+
+    /// Applies an update request, replacing the value and payment entries that share the same
+    /// dates so repeating a request does not duplicate history.
+    pub fn apply(&mut self, request: ExpenseRequest) {
+        self.description = request.description;
+        self.date_added = request.date_added;
+        self.expense_type = request.expense_type;
+        self.status = request.status;
+        self.recurrence = request.recurrence;
+
+        let value = request.value.rounded();
+        match self
+            .value_history
+            .iter_mut()
+            .find(|entry| entry.due_date == value.due_date && entry.added_date == value.added_date)
+        {
+            Some(entry) => *entry = value,
+            None => self.value_history.push(value),
+        }
+
+        if let Some(payment) = request.payment {
+            match self
+                .payments
+                .iter_mut()
+                .find(|entry| entry.due_date == payment.due_date)
+            {
+                Some(entry) => *entry = payment,
+                None => self.payments.push(payment),
+            }
+            self.payments.sort_by_key(|payment| payment.due_date);
+        }
+    }
+}
+
+impl Display for ExpenseType {
+    fn fmt(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        match self {
+            ExpenseType::Fixed => write!(formatter, "FIXED"),
+            ExpenseType::Variable => write!(formatter, "VARIABLE"),
+            ExpenseType::OneTime => write!(formatter, "ONE_TIME"),
+            ExpenseType::Lend => write!(formatter, "LEND"),
+        }
+    }
+}
+
+impl FromStr for ExpenseType {
+    type Err = ParseEnumError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "FIXED" => Ok(ExpenseType::Fixed),
+            "VARIABLE" => Ok(ExpenseType::Variable),
+            "ONE_TIME" => Ok(ExpenseType::OneTime),
+            "LEND" => Ok(ExpenseType::Lend),
+            _ => Err(ParseEnumError {
+                message: format!("Invalid ExpenseType {value}"),
+            }),
+        }
+    }
+}
+
+impl Display for ExpenseStatus {
+    fn fmt(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        match self {
+            ExpenseStatus::Open => write!(formatter, "OPEN"),
+            ExpenseStatus::Closed => write!(formatter, "CLOSED"),
+        }
+    }
+}
+
+impl FromStr for ExpenseStatus {
+    type Err = ParseEnumError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "OPEN" => Ok(ExpenseStatus::Open),
+            "CLOSED" => Ok(ExpenseStatus::Closed),
+            _ => Err(ParseEnumError {
+                message: format!("Invalid ExpenseStatus {value}"),
+            }),
+        }
+    }
+}
+
+impl Display for PaymentStatus {
+    fn fmt(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        match self {
+            PaymentStatus::Paid => write!(formatter, "PAID"),
+            PaymentStatus::Unpaid => write!(formatter, "UNPAID"),
+            PaymentStatus::Planned => write!(formatter, "PLANNED"),
+            PaymentStatus::Cancelled => write!(formatter, "CANCELLED"),
+        }
+    }
+}
+
+impl FromStr for PaymentStatus {
+    type Err = ParseEnumError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "PAID" => Ok(PaymentStatus::Paid),
+            "UNPAID" => Ok(PaymentStatus::Unpaid),
+            "PLANNED" => Ok(PaymentStatus::Planned),
+            "CANCELLED" => Ok(PaymentStatus::Cancelled),
+            _ => Err(ParseEnumError {
+                message: format!("Invalid PaymentStatus {value}"),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::CurrencyCode;
+    use chrono::{Duration, NaiveDate};
+    use rstest::rstest;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    #[rstest]
+    #[case(ExpenseType::Fixed, "FIXED".to_string())]
+    #[case(ExpenseType::Variable, "VARIABLE".to_string())]
+    #[case(ExpenseType::OneTime, "ONE_TIME".to_string())]
+    #[case(ExpenseType::Lend, "LEND".to_string())]
+    fn should_return_string_representation_of_expense_type(
+        #[case] expense_type: ExpenseType,
+        #[case] expected_string: String,
+    ) {
+        assert_eq!(expected_string, expense_type.to_string())
+    }
+
+    #[rstest]
+    #[case(ExpenseType::Fixed, "FIXED")]
+    #[case(ExpenseType::Variable, "VARIABLE")]
+    #[case(ExpenseType::OneTime, "ONE_TIME")]
+    #[case(ExpenseType::Lend, "LEND")]
+    fn should_return_enum_from_string_of_expense_type(
+        #[case] expected_expense_type: ExpenseType,
+        #[case] enum_string: &str,
+    ) {
+        let expense_type = ExpenseType::from_str(enum_string);
+        assert!(expense_type.is_ok());
+        assert_eq!(expected_expense_type, expense_type.unwrap());
+    }
+
+    #[test]
+    fn should_return_err_when_expense_type_does_not_exist() {
+        let expense_type = ExpenseType::from_str("batata");
+
+        assert!(expense_type.is_err());
+    }
+
+    #[rstest]
+    #[case(ExpenseStatus::Open, "OPEN".to_string())]
+    #[case(ExpenseStatus::Closed, "CLOSED".to_string())]
+    fn should_return_string_representation_of_expense_status(
+        #[case] status: ExpenseStatus,
+        #[case] expected_string: String,
+    ) {
+        assert_eq!(expected_string, status.to_string())
+    }
+
+    #[rstest]
+    #[case(ExpenseStatus::Open, "OPEN")]
+    #[case(ExpenseStatus::Closed, "CLOSED")]
+    fn should_return_enum_from_string_of_expense_status(
+        #[case] expected_status: ExpenseStatus,
+        #[case] enum_string: &str,
+    ) {
+        let status = ExpenseStatus::from_str(enum_string);
+        assert!(status.is_ok());
+        assert_eq!(expected_status, status.unwrap());
+    }
+
+    #[test]
+    fn should_return_err_when_expense_status_does_not_exist() {
+        let status = ExpenseStatus::from_str("batata");
+
+        assert!(status.is_err());
+    }
+
+    #[rstest]
+    #[case(PaymentStatus::Paid, "PAID".to_string())]
+    #[case(PaymentStatus::Unpaid, "UNPAID".to_string())]
+    #[case(PaymentStatus::Planned, "PLANNED".to_string())]
+    #[case(PaymentStatus::Cancelled, "CANCELLED".to_string())]
+    fn should_return_string_representation_of_payment_status(
+        #[case] status: PaymentStatus,
+        #[case] expected_string: String,
+    ) {
+        assert_eq!(expected_string, status.to_string())
+    }
+
+    #[rstest]
+    #[case(PaymentStatus::Paid, "PAID")]
+    #[case(PaymentStatus::Unpaid, "UNPAID")]
+    #[case(PaymentStatus::Planned, "PLANNED")]
+    #[case(PaymentStatus::Cancelled, "CANCELLED")]
+    fn should_return_enum_from_string_of_payment_status(
+        #[case] expected_status: PaymentStatus,
+        #[case] enum_string: &str,
+    ) {
+        let status = PaymentStatus::from_str(enum_string);
+        assert!(status.is_ok());
+        assert_eq!(expected_status, status.unwrap());
+    }
+
+    #[test]
+    fn should_return_err_when_payment_status_does_not_exist() {
+        let status = PaymentStatus::from_str("batata");
+
+        assert!(status.is_err());
+    }
+
+    #[test]
+    fn should_accept_valid_expense_request() {
+        assert!(build_expense_request("15/02/2023", dec!(10.0))
+            .validate()
+            .is_ok());
+    }
+
+    #[rstest]
+    #[case(" ", dec!(10))]
+    #[case("description", dec!(-1))]
+    fn should_reject_invalid_expense_request(#[case] description: &str, #[case] value: Decimal) {
+        let request = ExpenseRequest {
+            description: description.to_string(),
+            ..build_expense_request("15/02/2023", value)
+        };
+
+        assert!(request.validate().is_err());
+    }
+
+    #[test]
+    fn should_create_expense_with_initial_value_and_payment() {
+        let expense =
+            build_expense_request("15/02/2023", dec!(10.0)).into_expense("id".to_string());
+
+        assert_eq!("id", expense.id);
+        assert_eq!(1, expense.value_history.len());
+        assert_eq!(1, expense.payments.len());
+    }
+
+    #[test]
+    fn should_not_duplicate_history_when_applying_same_request() {
+        let mut expense =
+            build_expense_request("15/02/2023", dec!(10.0)).into_expense("id".to_string());
+
+        expense.apply(build_expense_request("15/02/2023", dec!(12.0)));
+
+        assert_eq!(1, expense.value_history.len());
+        assert_eq!(dec!(12), expense.value_history[0].value.value);
+        assert_eq!(1, expense.payments.len());
+    }
+
+    #[test]
+    fn should_append_history_when_applying_new_due_date() {
+        let mut expense =
+            build_expense_request("15/03/2023", dec!(10.0)).into_expense("id".to_string());
+
+        expense.apply(build_expense_request("15/02/2023", dec!(12.0)));
+
+        assert_eq!(2, expense.value_history.len());
+        assert_eq!(2, expense.payments.len());
+        assert_eq!(parse_date("15/02/2023"), expense.payments[0].due_date);
+    }
+
+    #[rstest]
+    #[case(PaymentStatus::Paid, Some("20/02/2023"), true)]
+    #[case(PaymentStatus::Paid, None, false)]
+    #[case(PaymentStatus::Cancelled, None, true)]
+    fn should_require_payment_date_when_paid(
+        #[case] status: PaymentStatus,
+        #[case] payment_date: Option<&str>,
+        #[case] expected: bool,
+    ) {
+        let update = PaymentUpdate {
+            status,
+            payment_date: payment_date.map(parse_date),
+            receive_date: None,
+        };
+
+        assert_eq!(expected, update.validate().is_ok());
+    }
+
+    #[test]
+    fn should_update_payment_due_at_date() {
+        let mut expense =
+            build_expense_request("15/02/2023", dec!(10.0)).into_expense("id".to_string());
+
+        expense
+            .update_payment(
+                parse_date("15/02/2023"),
+                PaymentUpdate {
+                    status: PaymentStatus::Paid,
+                    payment_date: Some(parse_date("14/02/2023")),
+                    receive_date: None,
+                },
+            )
+            .unwrap();
+
+        assert_eq!(PaymentStatus::Paid, expense.payments[0].status);
+        assert_eq!(
+            Some(parse_date("14/02/2023")),
+            expense.payments[0].payment_date
+        );
+    }
+
+    #[rstest]
+    #[case(PaymentStatus::Unpaid)]
+    #[case(PaymentStatus::Planned)]
+    #[case(PaymentStatus::Cancelled)]
+    fn should_unlink_payment_no_longer_paid(#[case] status: PaymentStatus) {
+        let mut expense =
+            build_expense_request("15/02/2023", dec!(10.0)).into_expense("id".to_string());
+        expense
+            .settle("transaction-id", parse_date("14/02/2023"))
+            .unwrap();
+
+        expense
+            .update_payment(
+                parse_date("15/02/2023"),
+                PaymentUpdate {
+                    status,
+                    payment_date: Some(parse_date("14/02/2023")),
+                    receive_date: None,
+                },
+            )
+            .unwrap();
+
+        assert_eq!(status, expense.payments[0].status);
+        assert_eq!(None, expense.payments[0].payment_date);
+        assert_eq!(None, expense.payments[0].transaction_id);
+    }
+
+    #[test]
+    fn should_not_find_payment_due_at_other_date() {
+        let mut expense =
+            build_expense_request("15/02/2023", dec!(10.0)).into_expense("id".to_string());
+
+        let result = expense.update_payment(
+            parse_date("15/03/2023"),
+            PaymentUpdate {
+                status: PaymentStatus::Cancelled,
+                payment_date: None,
+                receive_date: None,
+            },
+        );
+
+        assert!(matches!(result, Err(ApiError::NotFound { .. })));
+    }
+
+    #[rstest]
+    #[case("16/02/2023", vec!["15/02/2023"])]
+    #[case("15/02/2023", vec![])]
+    #[case("01/05/2023", vec!["15/02/2023", "15/04/2023"])]
+    fn should_list_pending_payments_due_before_date(
+        #[case] before: &str,
+        #[case] expected: Vec<&str>,
+    ) {
+        let mut expense =
+            build_expense_request("15/02/2023", dec!(10.0)).into_expense("id".to_string());
+        expense.apply(build_expense_request("15/03/2023", dec!(12.0)));
+        expense.apply(build_expense_request("15/04/2023", dec!(12.0)));
+        expense.payments[1].status = PaymentStatus::Paid;
+        expense.payments[2].status = PaymentStatus::Planned;
+
+        let due = expense.due_payments(parse_date(before));
+
+        assert_eq!(
+            expected.into_iter().map(parse_date).collect::<Vec<_>>(),
+            due.iter()
+                .map(|payment| payment.due_date)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[rstest]
+    #[case("14/02/2023", None)]
+    #[case("15/02/2023", Some(dec!(10)))]
+    #[case("20/03/2023", Some(dec!(12)))]
+    fn should_pick_value_in_effect_at_due_date(
+        #[case] due_date: &str,
+        #[case] expected: Option<Decimal>,
+    ) {
+        let mut expense =
+            build_expense_request("15/02/2023", dec!(10.0)).into_expense("id".to_string());
+        expense.apply(build_expense_request("15/03/2023", dec!(12.0)));
+
+        assert_eq!(
+            expected,
+            expense
+                .value_at(parse_date(due_date))
+                .map(|value| value.value)
+        );
+    }
+
+    #[rstest]
+    #[case(ExpenseType::Fixed, build_recurrence(31, 1), true)]
+    #[case(ExpenseType::Variable, build_recurrence(1, 3), true)]
+    #[case(ExpenseType::OneTime, build_recurrence(1, 1), false)]
+    #[case(ExpenseType::Fixed, build_recurrence(0, 1), false)]
+    #[case(ExpenseType::Fixed, build_recurrence(32, 1), false)]
+    #[case(ExpenseType::Fixed, build_recurrence(10, 0), false)]
+    #[case(ExpenseType::Fixed, build_recurrence(10, 120), true)]
+    #[case(ExpenseType::Fixed, build_recurrence(10, 121), false)]
+    fn should_validate_recurrence(
+        #[case] expense_type: ExpenseType,
+        #[case] recurrence: Recurrence,
+        #[case] expected: bool,
+    ) {
+        let request = ExpenseRequest {
+            expense_type,
+            recurrence: Some(recurrence),
+            ..build_expense_request("15/02/2023", dec!(10))
+        };
+
+        assert_eq!(expected, request.validate().is_ok());
+    }
+
+    #[rstest]
+    #[case(build_recurrence(31, 1), "01/01/2023", "01/05/2023", vec!["31/01/2023", "28/02/2023", "31/03/2023", "30/04/2023"])]
+    #[case(build_recurrence(10, 2), "01/01/2023", "01/07/2023", vec!["10/01/2023", "10/03/2023", "10/05/2023"])]
+    #[case(build_recurrence(10, 2), "01/04/2023", "01/07/2023", vec!["10/05/2023"])]
+    #[case(build_recurrence(5, 1), "06/01/2023", "01/03/2023", vec!["05/02/2023"])]
+    #[case(Recurrence { end_date: Some(parse_date("10/02/2023")), ..build_recurrence(10, 1) }, "01/01/2023", "01/06/2023", vec!["10/01/2023", "10/02/2023"])]
+    fn should_generate_recurrence_due_dates(
+        #[case] recurrence: Recurrence,
+        #[case] from: &str,
+        #[case] until: &str,
+        #[case] expected: Vec<&str>,
+    ) {
+        let due_dates = recurrence.due_dates(
+            parse_date("01/01/2023"),
+            parse_date(from),
+            parse_date(until),
+        );
+
+        assert_eq!(
+            expected.into_iter().map(parse_date).collect::<Vec<_>>(),
+            due_dates
+        );
+    }
+
+    #[test]
+    fn should_schedule_planned_payments_once() {
+        let mut expense = ExpenseRequest {
+            recurrence: Some(build_recurrence(15, 1)),
+            ..build_expense_request("15/02/2023", dec!(10))
+        }
+        .into_expense("id".to_string());
+
+        let planned = expense.schedule(parse_date("01/02/2023"), 3).unwrap();
+        let replanned = expense.schedule(parse_date("01/02/2023"), 3).unwrap();
+
+        assert_eq!(
+            vec![parse_date("15/03/2023"), parse_date("15/04/2023")],
+            planned
+                .iter()
+                .map(|payment| payment.due_date)
+                .collect::<Vec<_>>()
+        );
+        assert!(replanned.is_empty());
+        assert_eq!(3, expense.payments.len());
+        assert_eq!(PaymentStatus::Unpaid, expense.payments[0].status);
+        assert_eq!(PaymentStatus::Planned, expense.payments[2].status);
+    }
+
+    #[test]
+    fn should_not_schedule_day_with_installment_at_other_time() {
+        let mut expense = ExpenseRequest {
+            recurrence: Some(build_recurrence(15, 1)),
+            ..build_expense_request("15/02/2023", dec!(10))
+        }
+        .into_expense("id".to_string());
+        expense.payments[0].due_date = parse_date("15/02/2023") + Duration::hours(12);
+
+        let planned = expense.schedule(parse_date("01/02/2023"), 2).unwrap();
+
+        assert_eq!(
+            vec![parse_date("15/03/2023")],
+            planned
+                .iter()
+                .map(|payment| payment.due_date)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(2, expense.payments.len());
+    }
+
+    #[test]
+    fn should_not_schedule_expense_without_recurrence() {
+        let mut expense =
+            build_expense_request("15/02/2023", dec!(10.0)).into_expense("id".to_string());
+
+        assert!(expense.schedule(parse_date("01/02/2023"), 3).is_err());
+    }
+
+    #[rstest]
+    #[case("20/03/2023", "15/03/2023")]
+    #[case("20/05/2023", "15/02/2023")]
+    fn should_settle_pending_payment_of_transaction_month_first(
+        #[case] date: &str,
+        #[case] expected_due_date: &str,
+    ) {
+        let mut expense =
+            build_expense_request("15/02/2023", dec!(10.0)).into_expense("id".to_string());
+        expense.apply(build_expense_request("15/03/2023", dec!(10.0)));
+
+        expense.settle("transaction", parse_date(date)).unwrap();
+
+        let paid = expense
+            .payments
+            .iter()
+            .find(|payment| payment.status == PaymentStatus::Paid)
+            .unwrap();
+        assert_eq!(parse_date(expected_due_date), paid.due_date);
+        assert_eq!(Some(parse_date(date)), paid.payment_date);
+        assert_eq!(Some("transaction".to_string()), paid.transaction_id);
+    }
+
+    #[test]
+    fn should_settle_same_payment_when_transaction_is_repeated() {
+        let mut expense =
+            build_expense_request("15/02/2023", dec!(10.0)).into_expense("id".to_string());
+        expense.apply(build_expense_request("15/03/2023", dec!(10.0)));
+
+        expense
+            .settle("transaction", parse_date("20/02/2023"))
+            .unwrap();
+        expense
+            .settle("transaction", parse_date("21/02/2023"))
+            .unwrap();
+
+        assert_eq!(PaymentStatus::Paid, expense.payments[0].status);
+        assert_eq!(
+            Some(parse_date("21/02/2023")),
+            expense.payments[0].payment_date
+        );
+        assert_eq!(PaymentStatus::Unpaid, expense.payments[1].status);
+        assert!(expense.settle("other", parse_date("20/02/2023")).is_ok());
+        assert!(expense.settle("third", parse_date("20/02/2023")).is_err());
+    }
+
+    #[test]
+    fn should_unsettle_only_payment_of_transaction() {
+        let mut expense =
+            build_expense_request("15/02/2023", dec!(10.0)).into_expense("id".to_string());
+        expense.apply(build_expense_request("15/03/2023", dec!(10.0)));
+        expense
+            .settle("transaction", parse_date("20/02/2023"))
+            .unwrap();
+        expense.settle("other", parse_date("20/03/2023")).unwrap();
+
+        assert!(expense.unsettle("transaction"));
+        assert!(!expense.unsettle("transaction"));
+
+        assert_eq!(PaymentStatus::Unpaid, expense.payments[0].status);
+        assert_eq!(None, expense.payments[0].payment_date);
+        assert_eq!(None, expense.payments[0].transaction_id);
+        assert_eq!(PaymentStatus::Paid, expense.payments[1].status);
+    }
+
+    #[test]
+    fn recurrence_should_be_thread_safe() {
+        is_thread_safe::<Recurrence>();
+    }
+
+    #[test]
+    fn expense_type_should_be_thread_safe() {
+        is_thread_safe::<ExpenseType>();
+    }
+
+    #[test]
+    fn expense_status_should_be_thread_safe() {
+        is_thread_safe::<ExpenseStatus>();
+    }
+
+    #[test]
+    fn payment_status_should_be_thread_safe() {
+        is_thread_safe::<PaymentStatus>();
+    }
+
+    #[test]
+    fn expense_should_be_thread_safe() {
+        is_thread_safe::<Expense>();
+    }
+
+    #[test]
+    fn expense_request_should_be_thread_safe() {
+        is_thread_safe::<ExpenseRequest>();
+    }
+
+    fn is_thread_safe<T: Sized + Send + Sync + Unpin>() {}
+
+    fn build_expense_request(due_date: &str, value: Decimal) -> ExpenseRequest {
+        ExpenseRequest {
+            description: "description".to_string(),
+            date_added: parse_date("01/02/2023"),
+            value: ExpenseValue {
+                due_date: parse_date(due_date),
+                added_date: parse_date("01/02/2023"),
+                value: Currency {
+                    code: CurrencyCode::BRL,
+                    value,
+                },
+            },
+            payment: Some(ExpensePayment {
+                due_date: parse_date(due_date),
+                payment_date: None,
+                receive_date: None,
+                status: PaymentStatus::Unpaid,
+                transaction_id: None,
+            }),
+            expense_type: ExpenseType::Fixed,
+            status: ExpenseStatus::Open,
+            recurrence: None,
+        }
+    }
+
+    fn build_recurrence(day_of_month: u32, interval_months: u32) -> Recurrence {
+        Recurrence {
+            day_of_month,
+            interval_months,
+            end_date: None,
+        }
+    }
+
+    fn parse_date(value: &str) -> DateTime<Utc> {
+        NaiveDate::parse_from_str(value, "%d/%m/%Y")
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc()
+    }
+}
@@ -0,0 +1,56 @@
+use crate::model::CurrencyCode;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Amount of `target` bought by one unit of `source`, effective from `valid_from` until a newer
+/// rate for the same pair takes over.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, ToSchema)]
+pub struct FxRate {
+    pub source: CurrencyCode,
+    pub target: CurrencyCode,
+    #[schema(value_type = String, example = "0.2")]
+    pub rate: Decimal,
+    #[schema(value_type = String, format = DateTime)]
+    pub valid_from: DateTime<Utc>,
+}
+
+impl FxRate {
+    pub fn id(&self) -> String {
+        format!(
+            "{}-{}-{}",
+            self.source,
+            self.target,
+            self.valid_from.format("%Y%m%d%H%M%S")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn should_identify_rate_by_pair_and_date() {
+        let rate = FxRate {
+            source: CurrencyCode::USD,
+            target: CurrencyCode::BRL,
+            rate: dec!(5),
+            valid_from: NaiveDateTime::parse_from_str("15/02/2023 13:51:12", "%d/%m/%Y %H:%M:%S")
+                .unwrap()
+                .and_utc(),
+        };
+
+        assert_eq!("USD-BRL-20230215135112", rate.id());
+    }
+
+    #[test]
+    fn fx_rate_should_be_thread_safe() {
+        is_thread_safe::<FxRate>();
+    }
+
+    fn is_thread_safe<T: Sized + Send + Sync + Unpin>() {}
+}
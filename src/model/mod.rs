@@ -1,19 +1,20 @@
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
-use std::str::FromStr;
 use utoipa::ToSchema;
 
 pub mod account;
+pub mod currency;
+pub mod expense;
+pub mod fx_rate;
+pub mod page;
+pub mod report;
+pub mod transaction;
 
-#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
-pub enum CurrencyCode {
-    BRL,
-}
+pub use currency::{Currency, CurrencyCode};
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
-pub struct Currency {
-    pub code: CurrencyCode,
-    pub value: f64,
+pub struct ResourceId {
+    pub id: String,
 }
 
 #[derive(Debug, PartialEq, Eq, ToSchema)]
@@ -21,67 +22,19 @@ pub struct ParseEnumError {
     pub message: String,
 }
 
-impl Display for CurrencyCode {
+impl Display for ParseEnumError {
     fn fmt(&self, formatter: &mut Formatter) -> std::fmt::Result {
-        match self {
-            CurrencyCode::BRL => write!(formatter, "BRL"),
-        }
-    }
-}
-
-impl FromStr for CurrencyCode {
-    type Err = ParseEnumError;
-
-    fn from_str(value: &str) -> Result<Self, Self::Err> {
-        match value {
-            "BRL" => Ok(CurrencyCode::BRL),
-            _ => Err(ParseEnumError {
-                message: format!("Invalid CurrencyCode {value}"),
-            }),
-        }
+        write!(formatter, "{}", self.message)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rstest::rstest;
-
-    #[rstest]
-    #[case(CurrencyCode::BRL, "BRL".to_string())]
-    fn should_return_string_representation_of_currency_code(
-        #[case] currency_code: CurrencyCode,
-        #[case] expected_string: String,
-    ) {
-        assert_eq!(expected_string, currency_code.to_string())
-    }
-
-    #[rstest]
-    #[case(CurrencyCode::BRL, "BRL")]
-    fn should_return_enum_from_string_of_currency_code(
-        #[case] expected_currency_code: CurrencyCode,
-        #[case] enum_string: &str,
-    ) {
-        let currency_enum = CurrencyCode::from_str(enum_string);
-        assert!(currency_enum.is_ok());
-        assert_eq!(expected_currency_code, currency_enum.unwrap());
-    }
-
-    #[test]
-    fn should_return_err_when_currency_code_does_not_exist() {
-        let currency_code = CurrencyCode::from_str("batata");
-
-        assert!(currency_code.is_err());
-    }
-
-    #[test]
-    fn currency_code_should_be_thread_safe() {
-        is_thread_safe::<CurrencyCode>();
-    }
 
     #[test]
-    fn currency_should_be_thread_safe() {
-        is_thread_safe::<Currency>();
+    fn resource_id_should_be_thread_safe() {
+        is_thread_safe::<ResourceId>();
     }
 
     #[test]
@@ -0,0 +1,140 @@
+use crate::error::ApiError;
+use crate::model::account::Account;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+pub static MAX_PAGE_LIMIT: i32 = 100;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+#[aliases(AccountPage = Page<Account>)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Default, PartialEq, Debug, Clone)]
+pub struct PageRequest {
+    pub limit: Option<i32>,
+    pub cursor: Option<Cursor>,
+}
+
+/// Opaque position in a listing, sent to clients as url-safe base64 encoded JSON of the last
+/// evaluated key.
+#[derive(Serialize, Deserialize, Default, PartialEq, Debug, Clone)]
+pub struct Cursor {
+    pub key: BTreeMap<String, CursorValue>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub enum CursorValue {
+    S(String),
+    N(String),
+}
+
+impl PageRequest {
+    pub fn new(limit: Option<i32>, cursor: Option<&str>) -> Result<Self, ApiError> {
+        if let Some(limit) = limit {
+            if !(1..=MAX_PAGE_LIMIT).contains(&limit) {
+                return Err(ApiError::Validation {
+                    message: format!("limit must be between 1 and {MAX_PAGE_LIMIT}"),
+                });
+            }
+        }
+
+        Ok(PageRequest {
+            limit,
+            cursor: cursor.map(Cursor::from_str).transpose()?,
+        })
+    }
+}
+
+impl Display for Cursor {
+    fn fmt(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        let json = serde_json::to_vec(self).map_err(|_| std::fmt::Error)?;
+        write!(formatter, "{}", URL_SAFE_NO_PAD.encode(json))
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = ApiError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        URL_SAFE_NO_PAD
+            .decode(value)
+            .ok()
+            .and_then(|json| serde_json::from_slice::<Cursor>(&json).ok())
+            .filter(|cursor| !cursor.key.is_empty())
+            .ok_or(ApiError::Validation {
+                message: "cursor is invalid".to_string(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn should_round_trip_cursor() {
+        let cursor = build_cursor();
+
+        let decoded = Cursor::from_str(cursor.to_string().as_str());
+
+        assert_eq!(cursor, decoded.unwrap());
+    }
+
+    #[rstest]
+    #[case("batata")]
+    #[case("e30")]
+    #[case("")]
+    fn should_reject_invalid_cursor(#[case] cursor: &str) {
+        assert!(Cursor::from_str(cursor).is_err());
+    }
+
+    #[rstest]
+    #[case(Some(1), true)]
+    #[case(Some(MAX_PAGE_LIMIT), true)]
+    #[case(None, true)]
+    #[case(Some(0), false)]
+    #[case(Some(MAX_PAGE_LIMIT + 1), false)]
+    fn should_validate_page_limit(#[case] limit: Option<i32>, #[case] valid: bool) {
+        assert_eq!(valid, PageRequest::new(limit, None).is_ok());
+    }
+
+    #[test]
+    fn should_decode_cursor_of_page_request() {
+        let cursor = build_cursor();
+
+        let request = PageRequest::new(Some(10), Some(cursor.to_string().as_str())).unwrap();
+
+        assert_eq!(Some(10), request.limit);
+        assert_eq!(Some(cursor), request.cursor);
+    }
+
+    #[test]
+    fn page_should_be_thread_safe() {
+        is_thread_safe::<Page<Account>>();
+    }
+
+    #[test]
+    fn page_request_should_be_thread_safe() {
+        is_thread_safe::<PageRequest>();
+    }
+
+    fn is_thread_safe<T: Sized + Send + Sync + Unpin>() {}
+
+    fn build_cursor() -> Cursor {
+        Cursor {
+            key: BTreeMap::from([
+                ("id".to_string(), CursorValue::S("account".to_string())),
+                ("year".to_string(), CursorValue::N("2023".to_string())),
+            ]),
+        }
+    }
+}
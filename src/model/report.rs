@@ -0,0 +1,179 @@
+use crate::model::account::{Account, AccountBalance, AccountStatus, AccountType};
+use crate::model::transaction::TransactionType;
+use crate::model::Currency;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(PartialEq, Debug)]
+pub struct NetWorthFilter {
+    pub as_of: DateTime<Utc>,
+    /// Includes `Closed` and `NotInUse` accounts
+    pub include_inactive: bool,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+pub struct NetWorthReport {
+    #[schema(value_type = String, format = DateTime)]
+    pub as_of: DateTime<Utc>,
+    /// One total per currency across every group
+    pub totals: Vec<Currency>,
+    pub groups: Vec<NetWorthGroup>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+pub struct NetWorthGroup {
+    pub account_type: AccountType,
+    pub bank_name: String,
+    pub accounts: usize,
+    pub totals: Vec<Currency>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+pub struct CashFlowReport {
+    pub year: i32,
+    /// Only months with at least one transaction, in calendar order
+    pub months: Vec<CashFlowMonth>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+pub struct CashFlowMonth {
+    pub month: u32,
+    pub totals: CashFlowTotals,
+    /// Totals per account and transaction type
+    pub entries: Vec<CashFlowEntry>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+pub struct CashFlowEntry {
+    pub account_id: Option<String>,
+    pub transaction_type: TransactionType,
+    pub totals: CashFlowTotals,
+}
+
+/// Money in, money out and their difference, one amount per currency.
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+pub struct CashFlowTotals {
+    pub inflow: Vec<Currency>,
+    pub outflow: Vec<Currency>,
+    pub net: Vec<Currency>,
+}
+
+impl NetWorthFilter {
+    /// Whether the account was active at `as_of`. Accounts with a `close_date` count until that
+    /// date, while the others are only active when `Open`, since `NotInUse` carries no date.
+    pub fn includes(&self, account: &Account) -> bool {
+        if self.include_inactive {
+            return true;
+        }
+
+        match account.close_date {
+            Some(close_date) => close_date > self.as_of,
+            None => account.status == AccountStatus::Open,
+        }
+    }
+
+    /// Latest balance of the account recorded up to `as_of`.
+    pub fn balance_of<'a>(&self, account: &'a Account) -> Option<&'a AccountBalance> {
+        account
+            .balances
+            .iter()
+            .filter(|balance| balance.date <= self.as_of)
+            .max_by_key(|balance| balance.date)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::CurrencyCode;
+    use chrono::NaiveDateTime;
+    use rstest::rstest;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    #[rstest]
+    #[case(AccountStatus::Open, None, false, true)]
+    #[case(AccountStatus::Closed, None, false, false)]
+    #[case(AccountStatus::NotInUse, None, false, false)]
+    #[case(AccountStatus::Closed, None, true, true)]
+    #[case(AccountStatus::Closed, Some("01/06/2024 00:00:00"), false, true)]
+    #[case(AccountStatus::Closed, Some("01/06/2023 00:00:00"), false, false)]
+    #[case(AccountStatus::Open, Some("01/06/2023 00:00:00"), false, false)]
+    fn should_exclude_accounts_inactive_at_as_of_date_by_default(
+        #[case] status: AccountStatus,
+        #[case] close_date: Option<&str>,
+        #[case] include_inactive: bool,
+        #[case] expected: bool,
+    ) {
+        let filter = NetWorthFilter {
+            as_of: parse_date("31/12/2023 00:00:00"),
+            include_inactive,
+        };
+        let account = Account {
+            close_date: close_date.map(parse_date),
+            ..build_account(status, vec![])
+        };
+
+        assert_eq!(expected, filter.includes(&account));
+    }
+
+    #[rstest]
+    #[case("31/12/2023 00:00:00", Some(dec!(3)))]
+    #[case("15/03/2023 00:00:00", Some(dec!(2)))]
+    #[case("01/01/2023 00:00:00", None)]
+    fn should_pick_latest_balance_up_to_as_of_date(
+        #[case] as_of: &str,
+        #[case] expected: Option<Decimal>,
+    ) {
+        let filter = NetWorthFilter {
+            as_of: parse_date(as_of),
+            include_inactive: false,
+        };
+        let account = build_account(
+            AccountStatus::Open,
+            vec![
+                build_balance("31/03/2023 23:59:59", dec!(3)),
+                build_balance("31/01/2023 23:59:59", dec!(1)),
+                build_balance("28/02/2023 23:59:59", dec!(2)),
+            ],
+        );
+
+        let balance = filter.balance_of(&account);
+
+        assert_eq!(expected, balance.map(|balance| balance.balance.value));
+    }
+
+    #[test]
+    fn net_worth_report_should_be_thread_safe() {
+        is_thread_safe::<NetWorthReport>();
+    }
+
+    fn is_thread_safe<T: Sized + Send + Sync + Unpin>() {}
+
+    fn build_account(status: AccountStatus, balances: Vec<AccountBalance>) -> Account {
+        Account {
+            id: "id".to_string(),
+            name: "name".to_string(),
+            bank_name: "bank".to_string(),
+            open_date: parse_date("01/01/2023 00:00:00"),
+            close_date: None,
+            account_type: AccountType::Checking,
+            balances,
+            status,
+        }
+    }
+
+    fn build_balance(date: &str, value: Decimal) -> AccountBalance {
+        AccountBalance {
+            date: parse_date(date),
+            balance: Currency::new(CurrencyCode::BRL, value),
+        }
+    }
+
+    fn parse_date(value: &str) -> DateTime<Utc> {
+        NaiveDateTime::parse_from_str(value, "%d/%m/%Y %H:%M:%S")
+            .unwrap()
+            .and_utc()
+    }
+}
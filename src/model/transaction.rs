@@ -0,0 +1,628 @@
+use crate::error::ApiError;
+use crate::model::{Currency, ParseEnumError};
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Namespace of the ids derived for transfers, fixed so repeats derive the same ids anywhere.
+static TRANSFER_NAMESPACE: Uuid = Uuid::from_u128(0x6f0c_4b5e_8a1d_4c3f_9e27_d5b8_a0f4_1c62);
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Copy, Clone, ToSchema)]
+pub enum TransactionOperation {
+    Credit,
+    Debit,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Copy, Clone, ToSchema)]
+pub enum TransactionType {
+    Salary,
+    Payment,
+    Transfer,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Copy, Clone, ToSchema)]
+pub enum TransactionStatus {
+    Open,
+    Closed,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+pub struct Transaction {
+    pub id: String,
+    pub source_id: Option<String>,
+    pub destination_id: Option<String>,
+    pub expense_id: Option<String>,
+    pub description: String,
+    #[schema(value_type = String, format = DateTime)]
+    pub date: DateTime<Utc>,
+    pub month: u32,
+    pub year: i32,
+    pub value: Currency,
+    pub operation: TransactionOperation,
+    pub transaction_type: TransactionType,
+    pub status: TransactionStatus,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+pub struct TransactionRequest {
+    pub source_id: Option<String>,
+    pub destination_id: Option<String>,
+    pub expense_id: Option<String>,
+    pub description: String,
+    #[schema(value_type = String, format = DateTime)]
+    pub date: DateTime<Utc>,
+    pub value: Currency,
+    pub operation: TransactionOperation,
+    pub transaction_type: TransactionType,
+    pub status: TransactionStatus,
+}
+
+/// Moves `value` between two accounts, optionally recording their balances after the transfer.
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+pub struct TransferRequest {
+    pub source_id: String,
+    pub destination_id: String,
+    pub description: String,
+    #[schema(value_type = String, format = DateTime)]
+    pub date: DateTime<Utc>,
+    pub value: Currency,
+    pub source_balance: Option<Currency>,
+    pub destination_balance: Option<Currency>,
+}
+
+/// Ids of the two transactions recorded by a transfer.
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+pub struct Transfer {
+    pub debit_id: String,
+    pub credit_id: String,
+}
+
+#[derive(Default, PartialEq, Debug)]
+pub struct TransactionFilter {
+    pub source_id: Option<String>,
+    pub destination_id: Option<String>,
+    pub expense_id: Option<String>,
+    pub operation: Option<TransactionOperation>,
+    pub transaction_type: Option<TransactionType>,
+    pub status: Option<TransactionStatus>,
+    pub month: Option<u32>,
+    pub year: Option<i32>,
+}
+
+impl TransactionRequest {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        if self.description.trim().is_empty() {
+            return Err(ApiError::Validation {
+                message: "description must not be blank".to_string(),
+            });
+        }
+
+        if self.source_id.is_none() && self.destination_id.is_none() {
+            return Err(ApiError::Validation {
+                message: "source_id or destination_id must be informed".to_string(),
+            });
+        }
+
+        if !self.value.is_positive() {
+            return Err(ApiError::Validation {
+                message: "value must be positive".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn into_transaction(self, id: String) -> Transaction {
+        Transaction {
+            id,
+            source_id: self.source_id,
+            destination_id: self.destination_id,
+            expense_id: self.expense_id,
+            description: self.description,
+            date: self.date,
+            month: self.date.month(),
+            year: self.date.year(),
+            value: self.value.rounded(),
+            operation: self.operation,
+            transaction_type: self.transaction_type,
+            status: self.status,
+        }
+    }
+}
+
+impl TransferRequest {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        if self.description.trim().is_empty() {
+            return Err(ApiError::Validation {
+                message: "description must not be blank".to_string(),
+            });
+        }
+
+        if self.source_id == self.destination_id {
+            return Err(ApiError::Validation {
+                message: "source_id and destination_id must be different".to_string(),
+            });
+        }
+
+        if !self.value.is_positive() {
+            return Err(ApiError::Validation {
+                message: "value must be positive".to_string(),
+            });
+        }
+
+        let code = self.value.code;
+        if [&self.source_balance, &self.destination_balance]
+            .into_iter()
+            .flatten()
+            .any(|balance| balance.code != code)
+        {
+            return Err(ApiError::Validation {
+                message: format!("balances must be in the transfer currency {code}"),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Whether `debit`, recorded under the same idempotency key, is the debit leg of this very
+    /// transfer rather than of another one sent with a reused key.
+    pub fn is_recorded_as(&self, debit: &Transaction) -> bool {
+        debit.source_id.as_ref() == Some(&self.source_id)
+            && debit.destination_id.as_ref() == Some(&self.destination_id)
+            && debit.value == self.value
+            && debit.date == self.date
+    }
+
+    /// The `DEBIT` leg on the source account and the `CREDIT` leg on the destination.
+    pub fn into_transactions(self, transfer: &Transfer) -> (Transaction, Transaction) {
+        let leg = |id: &String, operation| {
+            TransactionRequest {
+                source_id: Some(self.source_id.clone()),
+                destination_id: Some(self.destination_id.clone()),
+                expense_id: None,
+                description: self.description.clone(),
+                date: self.date,
+                value: self.value.clone(),
+                operation,
+                transaction_type: TransactionType::Transfer,
+                status: TransactionStatus::Closed,
+            }
+            .into_transaction(id.clone())
+        };
+
+        (
+            leg(&transfer.debit_id, TransactionOperation::Debit),
+            leg(&transfer.credit_id, TransactionOperation::Credit),
+        )
+    }
+}
+
+impl Transfer {
+    /// The legs of the transfer identified by `idempotency_key`, the same on every repeat.
+    pub fn from_idempotency_key(idempotency_key: &str) -> Self {
+        Transfer {
+            debit_id: derive_id(idempotency_key, "debit"),
+            credit_id: derive_id(idempotency_key, "credit"),
+        }
+    }
+}
+
+/// A name-based UUID of `key` for `purpose`, stable across builds and deployments.
+fn derive_id(key: &str, purpose: &str) -> String {
+    Uuid::new_v5(&TRANSFER_NAMESPACE, format!("{purpose}:{key}").as_bytes()).to_string()
+}
+
+impl Transaction {
+    /// The account money moves into on a credit or out of on a debit.
+    pub fn account_id(&self) -> Option<&String> {
+        match self.operation {
+            TransactionOperation::Credit => {
+                self.destination_id.as_ref().or(self.source_id.as_ref())
+            }
+            TransactionOperation::Debit => self.source_id.as_ref().or(self.destination_id.as_ref()),
+        }
+    }
+
+    /// The expense whose installment this transaction settles, only set for payments.
+    pub fn settled_expense_id(&self) -> Option<&String> {
+        match self.transaction_type {
+            TransactionType::Payment => self.expense_id.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl Display for TransactionOperation {
+    fn fmt(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        match self {
+            TransactionOperation::Credit => write!(formatter, "CREDIT"),
+            TransactionOperation::Debit => write!(formatter, "DEBIT"),
+        }
+    }
+}
+
+impl FromStr for TransactionOperation {
+    type Err = ParseEnumError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "CREDIT" => Ok(TransactionOperation::Credit),
+            "DEBIT" => Ok(TransactionOperation::Debit),
+            _ => Err(ParseEnumError {
+                message: format!("Invalid TransactionOperation {value}"),
+            }),
+        }
+    }
+}
+
+impl Display for TransactionType {
+    fn fmt(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        match self {
+            TransactionType::Salary => write!(formatter, "SALARY"),
+            TransactionType::Payment => write!(formatter, "PAYMENT"),
+            TransactionType::Transfer => write!(formatter, "TRANSFER"),
+        }
+    }
+}
+
+impl FromStr for TransactionType {
+    type Err = ParseEnumError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "SALARY" => Ok(TransactionType::Salary),
+            "PAYMENT" => Ok(TransactionType::Payment),
+            "TRANSFER" => Ok(TransactionType::Transfer),
+            _ => Err(ParseEnumError {
+                message: format!("Invalid TransactionType {value}"),
+            }),
+        }
+    }
+}
+
+impl Display for TransactionStatus {
+    fn fmt(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        match self {
+            TransactionStatus::Open => write!(formatter, "OPEN"),
+            TransactionStatus::Closed => write!(formatter, "CLOSED"),
+        }
+    }
+}
+
+impl FromStr for TransactionStatus {
+    type Err = ParseEnumError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "OPEN" => Ok(TransactionStatus::Open),
+            "CLOSED" => Ok(TransactionStatus::Closed),
+            _ => Err(ParseEnumError {
+                message: format!("Invalid TransactionStatus {value}"),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::CurrencyCode;
+    use chrono::NaiveDateTime;
+    use rstest::rstest;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    #[rstest]
+    #[case(TransactionOperation::Credit, "CREDIT".to_string())]
+    #[case(TransactionOperation::Debit, "DEBIT".to_string())]
+    fn should_return_string_representation_of_transaction_operation(
+        #[case] operation: TransactionOperation,
+        #[case] expected_string: String,
+    ) {
+        assert_eq!(expected_string, operation.to_string())
+    }
+
+    #[rstest]
+    #[case(TransactionOperation::Credit, "CREDIT")]
+    #[case(TransactionOperation::Debit, "DEBIT")]
+    fn should_return_enum_from_string_of_transaction_operation(
+        #[case] expected_operation: TransactionOperation,
+        #[case] enum_string: &str,
+    ) {
+        let operation = TransactionOperation::from_str(enum_string);
+        assert!(operation.is_ok());
+        assert_eq!(expected_operation, operation.unwrap());
+    }
+
+    #[test]
+    fn should_return_err_when_transaction_operation_does_not_exist() {
+        let operation = TransactionOperation::from_str("batata");
+
+        assert!(operation.is_err());
+    }
+
+    #[rstest]
+    #[case(TransactionType::Salary, "SALARY".to_string())]
+    #[case(TransactionType::Payment, "PAYMENT".to_string())]
+    #[case(TransactionType::Transfer, "TRANSFER".to_string())]
+    fn should_return_string_representation_of_transaction_type(
+        #[case] transaction_type: TransactionType,
+        #[case] expected_string: String,
+    ) {
+        assert_eq!(expected_string, transaction_type.to_string())
+    }
+
+    #[rstest]
+    #[case(TransactionType::Salary, "SALARY")]
+    #[case(TransactionType::Payment, "PAYMENT")]
+    #[case(TransactionType::Transfer, "TRANSFER")]
+    fn should_return_enum_from_string_of_transaction_type(
+        #[case] expected_transaction_type: TransactionType,
+        #[case] enum_string: &str,
+    ) {
+        let transaction_type = TransactionType::from_str(enum_string);
+        assert!(transaction_type.is_ok());
+        assert_eq!(expected_transaction_type, transaction_type.unwrap());
+    }
+
+    #[test]
+    fn should_return_err_when_transaction_type_does_not_exist() {
+        let transaction_type = TransactionType::from_str("batata");
+
+        assert!(transaction_type.is_err());
+    }
+
+    #[rstest]
+    #[case(TransactionStatus::Open, "OPEN".to_string())]
+    #[case(TransactionStatus::Closed, "CLOSED".to_string())]
+    fn should_return_string_representation_of_transaction_status(
+        #[case] status: TransactionStatus,
+        #[case] expected_string: String,
+    ) {
+        assert_eq!(expected_string, status.to_string())
+    }
+
+    #[rstest]
+    #[case(TransactionStatus::Open, "OPEN")]
+    #[case(TransactionStatus::Closed, "CLOSED")]
+    fn should_return_enum_from_string_of_transaction_status(
+        #[case] expected_status: TransactionStatus,
+        #[case] enum_string: &str,
+    ) {
+        let status = TransactionStatus::from_str(enum_string);
+        assert!(status.is_ok());
+        assert_eq!(expected_status, status.unwrap());
+    }
+
+    #[test]
+    fn should_return_err_when_transaction_status_does_not_exist() {
+        let status = TransactionStatus::from_str("batata");
+
+        assert!(status.is_err());
+    }
+
+    #[test]
+    fn should_accept_valid_transaction_request() {
+        assert!(build_transaction_request().validate().is_ok());
+    }
+
+    #[rstest]
+    #[case(" ", Some("source"), None, dec!(10))]
+    #[case("description", None, None, dec!(10))]
+    #[case("description", Some("source"), None, dec!(0))]
+    #[case("description", None, Some("destination"), dec!(-1))]
+    fn should_reject_invalid_transaction_request(
+        #[case] description: &str,
+        #[case] source_id: Option<&str>,
+        #[case] destination_id: Option<&str>,
+        #[case] value: Decimal,
+    ) {
+        let request = TransactionRequest {
+            description: description.to_string(),
+            source_id: source_id.map(String::from),
+            destination_id: destination_id.map(String::from),
+            value: Currency {
+                code: CurrencyCode::BRL,
+                value,
+            },
+            ..build_transaction_request()
+        };
+
+        assert!(request.validate().is_err());
+    }
+
+    #[test]
+    fn should_derive_month_and_year_from_transaction_date() {
+        let transaction = build_transaction_request().into_transaction("id".to_string());
+
+        assert_eq!("id", transaction.id);
+        assert_eq!(2, transaction.month);
+        assert_eq!(2023, transaction.year);
+    }
+
+    #[test]
+    fn should_round_value_to_currency_minor_units() {
+        let mut request = build_transaction_request();
+        request.value.value = dec!(10.005);
+
+        let transaction = request.into_transaction("id".to_string());
+
+        assert_eq!(dec!(10.01), transaction.value.value);
+    }
+
+    #[rstest]
+    #[case(
+        TransactionOperation::Credit,
+        Some("source"),
+        Some("destination"),
+        Some("destination")
+    )]
+    #[case(
+        TransactionOperation::Debit,
+        Some("source"),
+        Some("destination"),
+        Some("source")
+    )]
+    #[case(TransactionOperation::Credit, Some("source"), None, Some("source"))]
+    #[case(
+        TransactionOperation::Debit,
+        None,
+        Some("destination"),
+        Some("destination")
+    )]
+    fn should_pick_account_money_moves_through(
+        #[case] operation: TransactionOperation,
+        #[case] source_id: Option<&str>,
+        #[case] destination_id: Option<&str>,
+        #[case] expected: Option<&str>,
+    ) {
+        let transaction = TransactionRequest {
+            source_id: source_id.map(String::from),
+            destination_id: destination_id.map(String::from),
+            operation,
+            ..build_transaction_request()
+        }
+        .into_transaction("id".to_string());
+
+        assert_eq!(expected, transaction.account_id().map(String::as_str));
+    }
+
+    #[rstest]
+    #[case(" ", "destination", dec!(10), None)]
+    #[case("description", "source", dec!(10), None)]
+    #[case("description", "destination", dec!(0), None)]
+    #[case("description", "destination", dec!(10), Some(CurrencyCode::USD))]
+    fn should_reject_invalid_transfer_request(
+        #[case] description: &str,
+        #[case] destination_id: &str,
+        #[case] value: Decimal,
+        #[case] balance_code: Option<CurrencyCode>,
+    ) {
+        let request = TransferRequest {
+            description: description.to_string(),
+            destination_id: destination_id.to_string(),
+            value: Currency::new(CurrencyCode::BRL, value),
+            destination_balance: balance_code.map(|code| Currency::new(code, dec!(1))),
+            ..build_transfer_request()
+        };
+
+        assert!(request.validate().is_err());
+    }
+
+    #[test]
+    fn should_split_transfer_into_debit_and_credit() {
+        let request = build_transfer_request();
+        assert!(request.validate().is_ok());
+
+        let (debit, credit) = request.into_transactions(&Transfer {
+            debit_id: "debit".to_string(),
+            credit_id: "credit".to_string(),
+        });
+
+        assert_eq!("debit", debit.id);
+        assert_eq!(TransactionOperation::Debit, debit.operation);
+        assert_eq!(Some("source"), debit.account_id().map(String::as_str));
+        assert_eq!("credit", credit.id);
+        assert_eq!(TransactionOperation::Credit, credit.operation);
+        assert_eq!(Some("destination"), credit.account_id().map(String::as_str));
+        assert_eq!(debit.value, credit.value);
+        assert_eq!(TransactionType::Transfer, credit.transaction_type);
+    }
+
+    #[test]
+    fn should_derive_transfer_from_idempotency_key() {
+        let transfer = Transfer::from_idempotency_key("key");
+
+        assert_eq!(transfer, Transfer::from_idempotency_key("key"));
+        assert_ne!(transfer.debit_id, transfer.credit_id);
+        assert_ne!(transfer, Transfer::from_idempotency_key("other"));
+    }
+
+    #[test]
+    fn should_derive_same_transfer_ids_in_every_build() {
+        let transfer = Transfer::from_idempotency_key("key");
+
+        assert_eq!("48290d07-68dd-587b-87b9-13ee2b1d492e", transfer.debit_id);
+        assert_eq!("5e462635-4b3b-567c-ba4a-103d09a98985", transfer.credit_id);
+    }
+
+    #[rstest]
+    #[case(build_transfer_request(), true)]
+    #[case(TransferRequest { description: "other".to_string(), ..build_transfer_request() }, true)]
+    #[case(TransferRequest { source_id: "other".to_string(), ..build_transfer_request() }, false)]
+    #[case(TransferRequest { destination_id: "other".to_string(), ..build_transfer_request() }, false)]
+    #[case(TransferRequest { value: Currency::new(CurrencyCode::BRL, dec!(11)), ..build_transfer_request() }, false)]
+    #[case(TransferRequest { date: Utc::now(), ..build_transfer_request() }, false)]
+    fn should_match_transfer_recorded_under_key(
+        #[case] request: TransferRequest,
+        #[case] expected: bool,
+    ) {
+        let transfer = Transfer::from_idempotency_key("key");
+        let (debit, _) = build_transfer_request().into_transactions(&transfer);
+
+        assert_eq!(expected, request.is_recorded_as(&debit));
+    }
+
+    #[test]
+    fn transaction_operation_should_be_thread_safe() {
+        is_thread_safe::<TransactionOperation>();
+    }
+
+    #[test]
+    fn transaction_type_should_be_thread_safe() {
+        is_thread_safe::<TransactionType>();
+    }
+
+    #[test]
+    fn transaction_status_should_be_thread_safe() {
+        is_thread_safe::<TransactionStatus>();
+    }
+
+    #[test]
+    fn transaction_should_be_thread_safe() {
+        is_thread_safe::<Transaction>();
+    }
+
+    #[test]
+    fn transaction_request_should_be_thread_safe() {
+        is_thread_safe::<TransactionRequest>();
+    }
+
+    fn is_thread_safe<T: Sized + Send + Sync + Unpin>() {}
+
+    fn build_transfer_request() -> TransferRequest {
+        TransferRequest {
+            source_id: "source".to_string(),
+            destination_id: "destination".to_string(),
+            description: "description".to_string(),
+            date: NaiveDateTime::parse_from_str("15/02/2023 13:51:12", "%d/%m/%Y %H:%M:%S")
+                .unwrap()
+                .and_utc(),
+            value: Currency::new(CurrencyCode::BRL, dec!(10)),
+            source_balance: Some(Currency::new(CurrencyCode::BRL, dec!(90))),
+            destination_balance: None,
+        }
+    }
+
+    fn build_transaction_request() -> TransactionRequest {
+        TransactionRequest {
+            source_id: Some("source".to_string()),
+            destination_id: None,
+            expense_id: None,
+            description: "description".to_string(),
+            date: NaiveDateTime::parse_from_str("15/02/2023 13:51:12", "%d/%m/%Y %H:%M:%S")
+                .unwrap()
+                .and_utc(),
+            value: Currency {
+                code: CurrencyCode::BRL,
+                value: dec!(10),
+            },
+            operation: TransactionOperation::Debit,
+            transaction_type: TransactionType::Payment,
+            status: TransactionStatus::Open,
+        }
+    }
+}
@@ -1,21 +1,30 @@
-use crate::error::PermanentError;
-use crate::model::account::{Account, AccountStatus, AccountType};
-use crate::repository::dynamo_client::DynamoDbClient;
-use crate::repository::DatabaseClient;
-use aws_config::SdkConfig;
+use crate::config::settings::Settings;
+use crate::error::ApiError;
+use crate::model::account::{Account, AccountBalance, AccountStatus, AccountType};
+use crate::model::page::{Page, PageRequest};
+use crate::repository::dynamo_item::{
+    BALANCES_ATTRIBUTE, BALANCE_MONTHS_ATTRIBUTE, CLOSE_DATE_ATTRIBUTE, ID_ATTRIBUTE,
+};
+use crate::repository::{
+    decode_page, Condition, DatabaseClient, DecodeMode, Item, ItemUpdate, ListAppend, TransactWrite,
+};
+use aws_sdk_dynamodb::types::AttributeValue;
+use std::sync::Arc;
 
 static TABLE_NAME: &str = "Account";
 static ACCOUNT_TYPE_PARAMETER: &str = "type";
 static ACCOUNT_STATUS_PARAMETER: &str = "status";
 
 pub struct AccountRepository {
-    client: Box<dyn DatabaseClient>,
+    client: Arc<dyn DatabaseClient>,
+    decode_mode: DecodeMode,
 }
 
 impl AccountRepository {
-    pub fn new(config: &SdkConfig) -> Self {
+    pub fn new(client: Arc<dyn DatabaseClient>, settings: &Settings) -> Self {
         AccountRepository {
-            client: Box::new(DynamoDbClient::new(config)),
+            client,
+            decode_mode: settings.decode_mode,
         }
     }
 
@@ -24,7 +33,8 @@ impl AccountRepository {
         &self,
         account_type: Option<AccountType>,
         account_status: Option<AccountStatus>,
-    ) -> Result<Vec<Account>, PermanentError> {
+        page: PageRequest,
+    ) -> Result<Page<Account>, ApiError> {
         let query_attributes = vec![
             (
                 ACCOUNT_TYPE_PARAMETER.to_string(),
@@ -37,11 +47,203 @@ impl AccountRepository {
         ]
         .into_iter()
         .filter(|(_, value)| value.is_some())
-        .map(|(parameter, value)| (parameter, value.unwrap()))
+        .map(|(parameter, value)| (parameter, AttributeValue::S(value.unwrap())))
         .collect();
 
+        decode_page(
+            self.client
+                .list(
+                    TABLE_NAME.to_string(),
+                    query_attributes,
+                    page.limit,
+                    page.cursor.map(Item::from),
+                )
+                .await?,
+            self.decode_mode,
+        )
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_account(&self, id: String) -> Result<Option<Account>, ApiError> {
+        self.client
+            .get(TABLE_NAME.to_string(), id)
+            .await?
+            .map(|item| Account::try_from(item).map_err(ApiError::from))
+            .transpose()
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn save_account(&self, account: Account) -> Result<(), ApiError> {
         self.client
-            .list(TABLE_NAME.to_string(), query_attributes)
+            .put(TABLE_NAME.to_string(), account.into(), None)
+            .await
+    }
+
+    /// Overwrites every attribute of an existing account but its balances, so balances appended
+    /// meanwhile are kept. Fails with `NotFound` when the account does not exist.
+    #[tracing::instrument(skip(self))]
+    pub async fn update_account(&self, account: Account) -> Result<(), ApiError> {
+        let id = account.id.clone();
+        let mut values = Item::from(account);
+        for attribute_name in [ID_ATTRIBUTE, BALANCES_ATTRIBUTE, BALANCE_MONTHS_ATTRIBUTE] {
+            values.remove(attribute_name);
+        }
+        let removed = [CLOSE_DATE_ATTRIBUTE]
+            .into_iter()
+            .filter(|attribute_name| !values.contains_key(*attribute_name))
+            .map(String::from)
+            .collect();
+
+        self.client
+            .update(TABLE_NAME.to_string(), id, ItemUpdate { values, removed })
+            .await
+    }
+
+    /// Fails with `Conflict` when the account already has a balance for the same month.
+    #[tracing::instrument(skip(self))]
+    pub async fn append_balance(
+        &self,
+        id: String,
+        balance: AccountBalance,
+    ) -> Result<(), ApiError> {
+        self.client
+            .append(TABLE_NAME.to_string(), id, balance_append(balance))
+            .await
+    }
+
+    /// The write appending `balance` as part of a transaction, with the same conflict rules as
+    /// `append_balance` and only while the account is not closed.
+    pub fn append_balance_write(&self, id: String, balance: AccountBalance) -> TransactWrite {
+        TransactWrite::Append {
+            table_name: TABLE_NAME.to_string(),
+            id,
+            append: balance_append(balance),
+            condition: Some(not_closed()),
+        }
+    }
+
+    /// The check, as part of a transaction, that the account exists and is not closed.
+    pub fn check_not_closed_write(&self, id: String) -> TransactWrite {
+        TransactWrite::Check {
+            table_name: TABLE_NAME.to_string(),
+            id,
+            condition: not_closed(),
+        }
+    }
+}
+
+fn not_closed() -> Condition {
+    Condition::NotEqual {
+        attribute: ACCOUNT_STATUS_PARAMETER.to_string(),
+        value: AttributeValue::S(AccountStatus::Closed.to_string()),
+    }
+}
+
+fn balance_append(balance: AccountBalance) -> ListAppend {
+    ListAppend {
+        attribute: BALANCES_ATTRIBUTE.to_string(),
+        marker_attribute: BALANCE_MONTHS_ATTRIBUTE.to_string(),
+        marker: balance.month(),
+        value: AttributeValue::M(balance.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Currency, CurrencyCode};
+    use crate::repository::InMemoryClient;
+    use chrono::NaiveDateTime;
+    use rust_decimal::Decimal;
+
+    #[tokio::test]
+    async fn should_page_through_saved_accounts() {
+        let repository =
+            AccountRepository::new(Arc::new(InMemoryClient::default()), &Settings::default());
+        for (id, status) in [
+            ("a", AccountStatus::Open),
+            ("b", AccountStatus::Closed),
+            ("c", AccountStatus::Open),
+            ("d", AccountStatus::Open),
+        ] {
+            repository
+                .save_account(build_account(id, status))
+                .await
+                .unwrap();
+        }
+
+        let first = repository
+            .list_accounts(
+                None,
+                Some(AccountStatus::Open),
+                PageRequest {
+                    limit: Some(2),
+                    cursor: None,
+                },
+            )
+            .await
+            .unwrap();
+        let cursor = first.next_cursor.unwrap();
+        let second = repository
+            .list_accounts(
+                None,
+                Some(AccountStatus::Open),
+                PageRequest::new(Some(2), Some(cursor.as_str())).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                build_account("a", AccountStatus::Open),
+                build_account("c", AccountStatus::Open)
+            ],
+            first.items
+        );
+        assert_eq!(vec![build_account("d", AccountStatus::Open)], second.items);
+        assert_eq!(None, second.next_cursor);
+    }
+
+    #[tokio::test]
+    async fn should_append_one_balance_per_month() {
+        let repository =
+            AccountRepository::new(Arc::new(InMemoryClient::default()), &Settings::default());
+        repository
+            .save_account(build_account("a", AccountStatus::Open))
             .await
+            .unwrap();
+        let balance = AccountBalance {
+            date: NaiveDateTime::parse_from_str("28/02/2023 23:59:59", "%d/%m/%Y %H:%M:%S")
+                .unwrap()
+                .and_utc(),
+            balance: Currency::new(CurrencyCode::BRL, Decimal::TEN),
+        };
+
+        repository
+            .append_balance("a".to_string(), balance.clone())
+            .await
+            .unwrap();
+        let repeated = repository
+            .append_balance("a".to_string(), balance.clone())
+            .await;
+
+        let account = repository.get_account("a".to_string()).await.unwrap();
+        assert_eq!(vec![balance], account.unwrap().balances);
+        assert!(matches!(repeated, Err(ApiError::Conflict { .. })));
+    }
+
+    fn build_account(id: &str, status: AccountStatus) -> Account {
+        Account {
+            id: id.to_string(),
+            name: "name".to_string(),
+            bank_name: "bank".to_string(),
+            open_date: NaiveDateTime::parse_from_str("15/02/2023 13:51:12", "%d/%m/%Y %H:%M:%S")
+                .unwrap()
+                .and_utc(),
+            close_date: None,
+            account_type: AccountType::Checking,
+            balances: vec![],
+            status,
+        }
     }
 }
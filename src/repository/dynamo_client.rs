@@ -1,26 +1,87 @@
-use crate::error::PermanentError;
-use crate::model::account::{Account, AccountBalance, AccountStatus, AccountType};
-use crate::model::{Currency, CurrencyCode};
-use crate::repository::DatabaseClient;
+use crate::config::settings::{SecondaryIndex, Settings};
+use crate::error::ApiError;
+use crate::repository::dynamo_item::{Item, ID_ATTRIBUTE};
+use crate::repository::{
+    Condition, DatabaseClient, ItemPage, ItemUpdate, ListAppend, TransactWrite,
+};
 use async_trait::async_trait;
 use aws_config::SdkConfig;
 use aws_sdk_dynamodb::error::SdkError;
+use aws_sdk_dynamodb::operation::get_item::GetItemError;
+use aws_sdk_dynamodb::operation::put_item::PutItemError;
+use aws_sdk_dynamodb::operation::query::builders::QueryFluentBuilder;
+use aws_sdk_dynamodb::operation::query::QueryError;
+use aws_sdk_dynamodb::operation::scan::builders::ScanFluentBuilder;
 use aws_sdk_dynamodb::operation::scan::ScanError;
-use aws_sdk_dynamodb::types::AttributeValue;
+use aws_sdk_dynamodb::operation::transact_write_items::TransactWriteItemsError;
+use aws_sdk_dynamodb::operation::update_item::UpdateItemError;
+use aws_sdk_dynamodb::types::error::TransactionCanceledException;
+use aws_sdk_dynamodb::types::{AttributeValue, ConditionCheck, Put, TransactWriteItem, Update};
 use aws_sdk_dynamodb::Client;
 use aws_smithy_types::error::metadata::ProvideErrorMetadata;
-use chrono::NaiveDateTime;
 use std::collections::HashMap;
-use std::str::FromStr;
+use std::error::Error;
+use tracing::info;
+
+static APPEND_EXPRESSION: &str =
+    "SET #list = list_append(if_not_exists(#list, :empty), :values) ADD #markers :markers";
+static APPEND_CONDITION: &str = "attribute_exists(#id) AND NOT contains(#markers, :marker)";
+static UPDATE_CONDITION: &str = "attribute_exists(#id)";
 
 pub struct DynamoDbClient {
     client: Client,
+    secondary_indexes: Vec<SecondaryIndex>,
 }
 
 impl DynamoDbClient {
-    pub fn new(config: &SdkConfig) -> Self {
+    pub fn new(config: &SdkConfig, settings: &Settings) -> Self {
         DynamoDbClient {
             client: Client::new(config),
+            secondary_indexes: settings.secondary_indexes.clone(),
+        }
+    }
+}
+
+enum ListRequest {
+    Scan(ScanFluentBuilder),
+    Query(QueryFluentBuilder),
+}
+
+impl ListRequest {
+    async fn send(
+        &self,
+        limit: Option<i32>,
+        start_key: Option<Item>,
+    ) -> Result<ItemPage, ApiError> {
+        match self {
+            ListRequest::Scan(request) => {
+                let output = request
+                    .clone()
+                    .set_limit(limit)
+                    .set_exclusive_start_key(start_key)
+                    .send()
+                    .await
+                    .map_err(ApiError::from)?;
+
+                Ok(ItemPage {
+                    items: output.items.unwrap_or_default(),
+                    last_evaluated_key: output.last_evaluated_key,
+                })
+            }
+            ListRequest::Query(request) => {
+                let output = request
+                    .clone()
+                    .set_limit(limit)
+                    .set_exclusive_start_key(start_key)
+                    .send()
+                    .await
+                    .map_err(ApiError::from)?;
+
+                Ok(ItemPage {
+                    items: output.items.unwrap_or_default(),
+                    last_evaluated_key: output.last_evaluated_key,
+                })
+            }
         }
     }
 }
@@ -31,302 +92,736 @@ impl DatabaseClient for DynamoDbClient {
     async fn list(
         &self,
         table_name: String,
-        conditions: Vec<(String, String)>,
-    ) -> Result<Vec<Account>, PermanentError> {
-        let mut request = self.client.scan().table_name(table_name);
-
-        let mut filter_expression: Option<String> = None;
-        for (attribute_name, attribute_value) in conditions {
-            let name_parameter = format!("#{attribute_name}");
-            let value_parameter = format!(":{attribute_name}");
-
-            filter_expression = Some(match filter_expression {
-                Some(expression) => {
-                    format!("{expression} AND {name_parameter} = {value_parameter}")
-                }
-                None => format!("{name_parameter} = {value_parameter}"),
-            });
-
-            request = request
-                .filter_expression(filter_expression.as_ref().unwrap())
-                .expression_attribute_names(name_parameter, attribute_name)
-                .expression_attribute_values(value_parameter, AttributeValue::S(attribute_value));
+        mut conditions: Vec<(String, AttributeValue)>,
+        limit: Option<i32>,
+        start_key: Option<Item>,
+    ) -> Result<ItemPage, ApiError> {
+        let request = match select_index(&self.secondary_indexes, &table_name, &conditions) {
+            Some((position, index)) => {
+                info!("Querying index {}", index.index_name);
+                let (attribute_name, attribute_value) = conditions.remove(position);
+                let key_condition = format!("#{attribute_name} = :{attribute_name}");
+                let filter_expression = build_expression(&conditions);
+                conditions.push((attribute_name, attribute_value));
+
+                ListRequest::Query(
+                    self.client
+                        .query()
+                        .table_name(table_name)
+                        .index_name(&index.index_name)
+                        .key_condition_expression(key_condition)
+                        .set_filter_expression(filter_expression)
+                        .set_expression_attribute_names(Some(build_names(&conditions)))
+                        .set_expression_attribute_values(Some(build_values(conditions))),
+                )
+            }
+            None if conditions.is_empty() => {
+                ListRequest::Scan(self.client.scan().table_name(table_name))
+            }
+            None => ListRequest::Scan(
+                self.client
+                    .scan()
+                    .table_name(table_name)
+                    .set_filter_expression(build_expression(&conditions))
+                    .set_expression_attribute_names(Some(build_names(&conditions)))
+                    .set_expression_attribute_values(Some(build_values(conditions))),
+            ),
+        };
+
+        // Limits apply before filtering, so keep reading until the page is full or the table is
+        // exhausted.
+        let mut page = ItemPage {
+            items: vec![],
+            last_evaluated_key: start_key,
+        };
+        loop {
+            let remaining = limit.map(|limit| limit - page.items.len() as i32);
+            let next = request
+                .send(remaining, page.last_evaluated_key.take())
+                .await?;
+
+            page.items.extend(next.items);
+            page.last_evaluated_key = next.last_evaluated_key;
+
+            let is_full = limit.is_some_and(|limit| page.items.len() as i32 >= limit);
+            if page.last_evaluated_key.is_none() || is_full {
+                return Ok(page);
+            }
         }
+    }
 
-        let result = request
+    #[tracing::instrument(skip(self))]
+    async fn get(&self, table_name: String, id: String) -> Result<Option<Item>, ApiError> {
+        Ok(self
+            .client
+            .get_item()
+            .table_name(table_name)
+            .key(ID_ATTRIBUTE, AttributeValue::S(id))
             .send()
             .await
-            .map_err(PermanentError::from)?
-            .items
-            .unwrap();
-
-        Ok(result
-            .into_iter()
-            .map(Account::from)
-            .collect::<Vec<Account>>())
+            .map_err(ApiError::from)?
+            .item)
     }
-}
 
-impl DynamoDbClient {
-    pub fn extract_string(key: &str, values: &HashMap<String, AttributeValue>) -> Option<String> {
-        Some(values.get(key)?.as_s().unwrap().clone())
+    #[tracing::instrument(skip(self))]
+    async fn put(
+        &self,
+        table_name: String,
+        item: Item,
+        condition: Option<Condition>,
+    ) -> Result<(), ApiError> {
+        self.client
+            .put_item()
+            .table_name(table_name)
+            .set_item(Some(item))
+            .set_condition_expression(condition.as_ref().map(build_condition_expression))
+            .set_expression_attribute_names(condition.as_ref().map(build_condition_names))
+            .set_expression_attribute_values(condition.map(build_condition_values))
+            .send()
+            .await
+            .map_err(ApiError::from)?;
+
+        Ok(())
     }
 
-    pub fn extract_number(key: &str, values: &HashMap<String, AttributeValue>) -> Option<f64> {
-        Some(values.get(key)?.as_n().unwrap().parse::<f64>().unwrap())
+    #[tracing::instrument(skip(self))]
+    async fn update(
+        &self,
+        table_name: String,
+        id: String,
+        update: ItemUpdate,
+    ) -> Result<(), ApiError> {
+        let values = build_update_values(&update);
+        let result = self
+            .client
+            .update_item()
+            .table_name(&table_name)
+            .key(ID_ATTRIBUTE, AttributeValue::S(id.clone()))
+            .update_expression(build_update_expression(&update))
+            .condition_expression(UPDATE_CONDITION)
+            .set_expression_attribute_names(Some(build_update_names(&update)))
+            .set_expression_attribute_values((!values.is_empty()).then_some(values))
+            .send()
+            .await
+            .map_err(ApiError::from);
+
+        match result {
+            // Existence is the only condition, so a failed one means the item is missing
+            Err(ApiError::Conflict { .. }) => Err(ApiError::NotFound {
+                message: format!("Item {id} of {table_name} does not exist"),
+            }),
+            result => result.map(|_| ()),
+        }
     }
 
-    pub fn extract_list(
-        key: &str,
-        values: &HashMap<String, AttributeValue>,
-    ) -> Option<Vec<AttributeValue>> {
-        Some(values.get(key)?.as_l().unwrap().clone())
+    #[tracing::instrument(skip(self))]
+    async fn append(
+        &self,
+        table_name: String,
+        id: String,
+        append: ListAppend,
+    ) -> Result<(), ApiError> {
+        let result = self
+            .client
+            .update_item()
+            .table_name(&table_name)
+            .key(ID_ATTRIBUTE, AttributeValue::S(id.clone()))
+            .update_expression(APPEND_EXPRESSION)
+            .condition_expression(APPEND_CONDITION)
+            .set_expression_attribute_names(Some(build_append_names(&append)))
+            .set_expression_attribute_values(Some(build_append_values(append)))
+            .send()
+            .await
+            .map_err(ApiError::from);
+
+        match result {
+            Ok(_) => Ok(()),
+            // The failed condition does not tell a missing item from a recorded marker
+            Err(error @ ApiError::Conflict { .. }) => {
+                match self.get(table_name.clone(), id.clone()).await? {
+                    Some(_) => Err(error),
+                    None => Err(ApiError::NotFound {
+                        message: format!("Item {id} of {table_name} does not exist"),
+                    }),
+                }
+            }
+            Err(error) => Err(error),
+        }
     }
 
-    pub fn extract_map(
-        key: &str,
-        values: &HashMap<String, AttributeValue>,
-    ) -> Option<HashMap<String, AttributeValue>> {
-        Some(values.get(key)?.as_m().unwrap().clone())
+    #[tracing::instrument(skip(self))]
+    async fn transact_write(
+        &self,
+        writes: Vec<TransactWrite>,
+        client_request_token: Option<String>,
+    ) -> Result<(), ApiError> {
+        let items = writes
+            .into_iter()
+            .map(|write| match write {
+                TransactWrite::Put {
+                    table_name,
+                    item,
+                    condition,
+                } => TransactWriteItem::builder()
+                    .put(
+                        Put::builder()
+                            .table_name(table_name)
+                            .set_item(Some(item))
+                            .set_condition_expression(
+                                condition.as_ref().map(build_condition_expression),
+                            )
+                            .set_expression_attribute_names(
+                                condition.as_ref().map(build_condition_names),
+                            )
+                            .set_expression_attribute_values(condition.map(build_condition_values))
+                            .build(),
+                    )
+                    .build(),
+                TransactWrite::Append {
+                    table_name,
+                    id,
+                    append,
+                    condition,
+                } => {
+                    let mut names = build_append_names(&append);
+                    let mut values = build_append_values(append);
+                    let expression = match condition {
+                        Some(condition) => {
+                            names.extend(build_condition_names(&condition));
+                            let expression = format!(
+                                "{APPEND_CONDITION} AND ({})",
+                                build_condition_expression(&condition)
+                            );
+                            values.extend(build_condition_values(condition));
+                            expression
+                        }
+                        None => APPEND_CONDITION.to_string(),
+                    };
+                    TransactWriteItem::builder()
+                        .update(
+                            Update::builder()
+                                .table_name(table_name)
+                                .key(ID_ATTRIBUTE, AttributeValue::S(id))
+                                .update_expression(APPEND_EXPRESSION)
+                                .condition_expression(expression)
+                                .set_expression_attribute_names(Some(names))
+                                .set_expression_attribute_values(Some(values))
+                                .build(),
+                        )
+                        .build()
+                }
+                TransactWrite::Check {
+                    table_name,
+                    id,
+                    condition,
+                } => TransactWriteItem::builder()
+                    .condition_check(
+                        ConditionCheck::builder()
+                            .table_name(table_name)
+                            .key(ID_ATTRIBUTE, AttributeValue::S(id))
+                            .condition_expression(build_condition_expression(&condition))
+                            .set_expression_attribute_names(Some(build_condition_names(&condition)))
+                            .set_expression_attribute_values(Some(build_condition_values(
+                                condition,
+                            )))
+                            .build(),
+                    )
+                    .build(),
+            })
+            .collect();
+
+        self.client
+            .transact_write_items()
+            .set_transact_items(Some(items))
+            .set_client_request_token(client_request_token)
+            .send()
+            .await
+            .map_err(ApiError::from)?;
+
+        Ok(())
     }
 }
 
-impl From<HashMap<String, AttributeValue>> for Account {
-    fn from(values: HashMap<String, AttributeValue>) -> Self {
-        Account {
-            id: DynamoDbClient::extract_string("id", &values).unwrap(),
-            name: DynamoDbClient::extract_string("name", &values).unwrap(),
-            bank_name: DynamoDbClient::extract_string("bank_name", &values).unwrap(),
-            open_date: DynamoDbClient::extract_string("open_date", &values)
-                .map(convert_date)
-                .unwrap(),
-            close_date: DynamoDbClient::extract_string("close_date", &values).map(convert_date),
-            account_type: AccountType::from_str(
-                DynamoDbClient::extract_string("type", &values)
-                    .unwrap()
-                    .as_str(),
-            )
-            .unwrap(),
-            balances: DynamoDbClient::extract_list("balances", &values)
-                .unwrap()
-                .into_iter()
-                .map(|attribute| attribute.as_m().unwrap().clone())
-                .map(AccountBalance::from)
-                .collect(),
-            status: AccountStatus::from_str(
-                DynamoDbClient::extract_string("status", &values)
-                    .unwrap()
-                    .as_str(),
-            )
-            .unwrap(),
-        }
+impl From<SdkError<ScanError>> for ApiError {
+    fn from(value: SdkError<ScanError>) -> Self {
+        classify(value)
     }
 }
 
-impl From<HashMap<String, AttributeValue>> for AccountBalance {
-    fn from(values: HashMap<String, AttributeValue>) -> Self {
-        AccountBalance {
-            date: DynamoDbClient::extract_string("date", &values)
-                .map(convert_date)
-                .unwrap(),
-            balance: Currency::from(DynamoDbClient::extract_map("balance", &values).unwrap()),
-        }
+impl From<SdkError<GetItemError>> for ApiError {
+    fn from(value: SdkError<GetItemError>) -> Self {
+        classify(value)
     }
 }
 
-impl From<HashMap<String, AttributeValue>> for Currency {
-    fn from(values: HashMap<String, AttributeValue>) -> Self {
-        Currency {
-            code: CurrencyCode::from_str(
-                DynamoDbClient::extract_string("currency", &values)
-                    .unwrap()
-                    .as_str(),
-            )
-            .unwrap(),
-            value: DynamoDbClient::extract_number("value", &values).unwrap(),
-        }
+impl From<SdkError<PutItemError>> for ApiError {
+    fn from(value: SdkError<PutItemError>) -> Self {
+        classify(value)
     }
 }
 
-fn convert_date(value: String) -> NaiveDateTime {
-    NaiveDateTime::parse_from_str(value.as_str(), "%d/%m/%Y %H:%M:%S%z").unwrap()
+impl From<SdkError<UpdateItemError>> for ApiError {
+    fn from(value: SdkError<UpdateItemError>) -> Self {
+        classify(value)
+    }
 }
 
-impl From<SdkError<ScanError>> for PermanentError {
-    fn from(value: SdkError<ScanError>) -> Self {
-        let service_error = value.into_service_error();
-        Self {
-            message: service_error.message().map(String::from),
-            source: Box::new(service_error),
+impl From<SdkError<TransactWriteItemsError>> for ApiError {
+    fn from(value: SdkError<TransactWriteItemsError>) -> Self {
+        match value {
+            SdkError::ServiceError(context) => classify_transact_write_error(context.into_err()),
+            value => classify(value),
         }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use aws_sdk_dynamodb::types::AttributeValue;
+impl From<SdkError<QueryError>> for ApiError {
+    fn from(value: SdkError<QueryError>) -> Self {
+        classify(value)
+    }
+}
 
-    #[test]
-    fn should_extract_string_when_available() {
-        let key = "batata";
-        let mut values = HashMap::new();
-        values.insert(key.to_string(), AttributeValue::S("frita".to_string()));
+/// Picks the first condition backed by a secondary index of the table, if any.
+fn select_index<'a>(
+    secondary_indexes: &'a [SecondaryIndex],
+    table_name: &str,
+    conditions: &[(String, AttributeValue)],
+) -> Option<(usize, &'a SecondaryIndex)> {
+    conditions
+        .iter()
+        .enumerate()
+        .find_map(|(position, (attribute_name, _))| {
+            secondary_indexes
+                .iter()
+                .find(|index| {
+                    index.table_name == table_name && &index.attribute_name == attribute_name
+                })
+                .map(|index| (position, index))
+        })
+}
 
-        let result = DynamoDbClient::extract_string(key, &values);
+fn build_expression(conditions: &[(String, AttributeValue)]) -> Option<String> {
+    conditions
+        .iter()
+        .map(|(attribute_name, _)| format!("#{attribute_name} = :{attribute_name}"))
+        .reduce(|expression, condition| format!("{expression} AND {condition}"))
+}
 
-        assert!(result.is_some());
-        assert_eq!("frita".to_string(), result.unwrap());
-    }
+fn build_names(conditions: &[(String, AttributeValue)]) -> HashMap<String, String> {
+    conditions
+        .iter()
+        .map(|(attribute_name, _)| (format!("#{attribute_name}"), attribute_name.clone()))
+        .collect()
+}
 
-    #[test]
-    fn should_return_none_when_string_is_not_available() {
-        let key = "batata";
-        let values = HashMap::new();
+fn build_values(conditions: Vec<(String, AttributeValue)>) -> Item {
+    conditions
+        .into_iter()
+        .map(|(attribute_name, attribute_value)| (format!(":{attribute_name}"), attribute_value))
+        .collect()
+}
 
-        let result = DynamoDbClient::extract_string(key, &values);
+/// `SET` of every value followed by `REMOVE` of every removed attribute, sorted by name.
+fn build_update_expression(update: &ItemUpdate) -> String {
+    let mut values: Vec<&String> = update.values.keys().collect();
+    values.sort();
+    let mut removed: Vec<&String> = update.removed.iter().collect();
+    removed.sort();
 
-        assert!(result.is_none());
+    let mut clauses = vec![];
+    if !values.is_empty() {
+        let assignments: Vec<String> = values
+            .into_iter()
+            .map(|attribute_name| format!("#{attribute_name} = :{attribute_name}"))
+            .collect();
+        clauses.push(format!("SET {}", assignments.join(", ")));
     }
-
-    #[test]
-    #[should_panic]
-    fn should_fail_when_attribute_is_not_string() {
-        let key = "batata";
-        let mut values = HashMap::new();
-        values.insert(key.to_string(), AttributeValue::N("1".to_string()));
-
-        DynamoDbClient::extract_string(key, &values);
+    if !removed.is_empty() {
+        let attributes: Vec<String> = removed
+            .into_iter()
+            .map(|attribute_name| format!("#{attribute_name}"))
+            .collect();
+        clauses.push(format!("REMOVE {}", attributes.join(", ")));
     }
+    clauses.join(" ")
+}
 
-    #[test]
-    fn should_extract_number_when_available() {
-        let key = "batata";
-        let mut values = HashMap::new();
-        values.insert(key.to_string(), AttributeValue::N("1.1".to_string()));
+fn build_update_names(update: &ItemUpdate) -> HashMap<String, String> {
+    let mut names: HashMap<String, String> = update
+        .values
+        .keys()
+        .chain(update.removed.iter())
+        .map(|attribute_name| (format!("#{attribute_name}"), attribute_name.clone()))
+        .collect();
+    names.insert("#id".to_string(), ID_ATTRIBUTE.to_string());
+    names
+}
 
-        let result = DynamoDbClient::extract_number(key, &values);
+fn build_update_values(update: &ItemUpdate) -> Item {
+    update
+        .values
+        .iter()
+        .map(|(attribute_name, value)| (format!(":{attribute_name}"), value.clone()))
+        .collect()
+}
 
-        assert!(result.is_some());
-        assert_eq!(1.1, result.unwrap());
-    }
+fn build_append_names(append: &ListAppend) -> HashMap<String, String> {
+    HashMap::from([
+        ("#id".to_string(), ID_ATTRIBUTE.to_string()),
+        ("#list".to_string(), append.attribute.clone()),
+        ("#markers".to_string(), append.marker_attribute.clone()),
+    ])
+}
 
-    #[test]
-    fn should_return_none_when_number_is_not_available() {
-        let key = "batata";
-        let values = HashMap::new();
+fn build_append_values(append: ListAppend) -> Item {
+    HashMap::from([
+        (":empty".to_string(), AttributeValue::L(vec![])),
+        (":values".to_string(), AttributeValue::L(vec![append.value])),
+        (
+            ":markers".to_string(),
+            AttributeValue::Ss(vec![append.marker.clone()]),
+        ),
+        (":marker".to_string(), AttributeValue::S(append.marker)),
+    ])
+}
 
-        let result = DynamoDbClient::extract_number(key, &values);
+/// A version 0 also matches items written before they were versioned.
+fn build_condition_expression(condition: &Condition) -> String {
+    match condition {
+        Condition::Version { expected: 0, .. } => {
+            "attribute_not_exists(#version) OR #version = :version".to_string()
+        }
+        Condition::Version { .. } => "#version = :version".to_string(),
+        Condition::NotEqual { .. } => {
+            "attribute_exists(#id) AND #condition <> :condition".to_string()
+        }
+    }
+}
 
-        assert!(result.is_none());
+fn build_condition_names(condition: &Condition) -> HashMap<String, String> {
+    match condition {
+        Condition::Version { attribute, .. } => {
+            HashMap::from([("#version".to_string(), attribute.clone())])
+        }
+        Condition::NotEqual { attribute, .. } => HashMap::from([
+            ("#id".to_string(), ID_ATTRIBUTE.to_string()),
+            ("#condition".to_string(), attribute.clone()),
+        ]),
     }
+}
 
-    #[test]
-    #[should_panic]
-    fn should_fail_when_attribute_is_not_number() {
-        let key = "batata";
-        let mut values = HashMap::new();
-        values.insert(key.to_string(), AttributeValue::S("batata".to_string()));
+fn build_condition_values(condition: Condition) -> Item {
+    match condition {
+        Condition::Version { expected, .. } => HashMap::from([(
+            ":version".to_string(),
+            AttributeValue::N(expected.to_string()),
+        )]),
+        Condition::NotEqual { value, .. } => HashMap::from([(":condition".to_string(), value)]),
+    }
+}
 
-        DynamoDbClient::extract_number(key, &values);
+/// Network failures and throttling are worth retrying, rejected conditions are conflicts and
+/// anything else, including failures this client does not know of, is permanent.
+fn classify<E>(error: SdkError<E>) -> ApiError
+where
+    E: Error + ProvideErrorMetadata + Send + Sync + 'static,
+{
+    let message = Some(error.to_string());
+    match error {
+        SdkError::ServiceError(context) => classify_service_error(context.into_err()),
+        SdkError::TimeoutError(_) | SdkError::DispatchFailure(_) | SdkError::ResponseError(_) => {
+            ApiError::Transient {
+                message,
+                source: into_source(error),
+            }
+        }
+        _ => ApiError::Permanent {
+            message,
+            source: into_source(error),
+        },
     }
+}
 
-    #[test]
-    #[should_panic]
-    fn should_fail_when_attribute_is_not_parseable_number() {
-        let key = "batata";
-        let mut values = HashMap::new();
-        values.insert(key.to_string(), AttributeValue::N("batata".to_string()));
+/// The error behind `error`, or its description when it has none.
+fn into_source<E>(error: SdkError<E>) -> Box<dyn Error + Send + Sync>
+where
+    E: Error + Send + Sync + 'static,
+{
+    error
+        .into_source()
+        .unwrap_or_else(|error| error.to_string().into())
+}
 
-        DynamoDbClient::extract_number(key, &values);
+fn classify_service_error<E>(service_error: E) -> ApiError
+where
+    E: Error + ProvideErrorMetadata + Send + Sync + 'static,
+{
+    let message = service_error.message().map(String::from);
+    match service_error.code() {
+        Some(
+            "ProvisionedThroughputExceededException"
+            | "RequestLimitExceeded"
+            | "ThrottlingException"
+            | "InternalServerError"
+            | "ServiceUnavailable"
+            | "TransactionInProgressException"
+            | "TransactionConflictException",
+        ) => ApiError::Transient {
+            message,
+            source: Box::new(service_error),
+        },
+        Some("ConditionalCheckFailedException" | "IdempotentParameterMismatchException") => {
+            ApiError::Conflict {
+                message: message.unwrap_or(service_error.to_string()),
+            }
+        }
+        _ => ApiError::Permanent {
+            message,
+            source: Box::new(service_error),
+        },
     }
+}
 
-    #[test]
-    fn should_extract_list_when_available() {
-        let key = "batata";
-        let mut values = HashMap::new();
-        values.insert(
-            key.to_string(),
-            AttributeValue::L(vec![AttributeValue::S("test".to_string())]),
-        );
-
-        let result = DynamoDbClient::extract_list(key, &values);
+fn classify_transact_write_error(service_error: TransactWriteItemsError) -> ApiError {
+    match service_error {
+        TransactWriteItemsError::TransactionCanceledException(canceled) => {
+            classify_cancellation(canceled)
+        }
+        service_error => classify_service_error(service_error),
+    }
+}
 
-        assert!(result.is_some());
-        assert_eq!(vec![AttributeValue::S("test".to_string())], result.unwrap());
+/// A canceled transaction is classified by the reasons its writes were canceled for: a failed
+/// condition is a conflict, contention is worth retrying and anything else is permanent.
+fn classify_cancellation(canceled: TransactionCanceledException) -> ApiError {
+    let codes: Vec<&str> = canceled
+        .cancellation_reasons()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|reason| reason.code())
+        .collect();
+    let message = canceled.message().map(String::from);
+    if codes.contains(&"ConditionalCheckFailed") {
+        ApiError::Conflict {
+            message: message.unwrap_or(canceled.to_string()),
+        }
+    } else if codes.iter().any(|code| {
+        matches!(
+            *code,
+            "ThrottlingError" | "ProvisionedThroughputExceeded" | "TransactionConflict"
+        )
+    }) {
+        ApiError::Transient {
+            message,
+            source: Box::new(canceled),
+        }
+    } else {
+        ApiError::Permanent {
+            message,
+            source: Box::new(canceled),
+        }
     }
+}
 
-    #[test]
-    fn should_return_none_when_list_is_not_available() {
-        let key = "batata";
-        let values = HashMap::new();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_dynamodb::types::CancellationReason;
+    use aws_smithy_types::error::ErrorMetadata;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("Account", vec!["name", "status"], Some((1, "AccountStatusIndex")))]
+    #[case("Account", vec!["type", "status"], Some((0, "AccountTypeIndex")))]
+    #[case("Account", vec!["name"], None)]
+    #[case("Expense", vec!["status"], None)]
+    fn should_select_index_of_first_indexed_condition(
+        #[case] table_name: &str,
+        #[case] attribute_names: Vec<&str>,
+        #[case] expected: Option<(usize, &str)>,
+    ) {
+        let indexes = vec![
+            build_index("Account", "type", "AccountTypeIndex"),
+            build_index("Account", "status", "AccountStatusIndex"),
+        ];
+        let conditions: Vec<(String, AttributeValue)> = attribute_names
+            .into_iter()
+            .map(|name| (name.to_string(), AttributeValue::S("value".to_string())))
+            .collect();
 
-        let result = DynamoDbClient::extract_list(key, &values);
+        let result = select_index(&indexes, table_name, &conditions)
+            .map(|(position, index)| (position, index.index_name.as_str()));
 
-        assert!(result.is_none());
+        assert_eq!(expected, result);
     }
 
     #[test]
-    #[should_panic]
-    fn should_fail_when_attribute_is_not_list() {
-        let key = "batata";
-        let mut values = HashMap::new();
-        values.insert(key.to_string(), AttributeValue::N("1".to_string()));
+    fn should_join_conditions_into_filter_expression() {
+        let conditions = vec![
+            (
+                "type".to_string(),
+                AttributeValue::S("CHECKING".to_string()),
+            ),
+            ("year".to_string(), AttributeValue::N("2023".to_string())),
+        ];
 
-        DynamoDbClient::extract_list(key, &values);
+        assert_eq!(
+            Some("#type = :type AND #year = :year".to_string()),
+            build_expression(&conditions)
+        );
+        assert_eq!(
+            HashMap::from([
+                ("#type".to_string(), "type".to_string()),
+                ("#year".to_string(), "year".to_string())
+            ]),
+            build_names(&conditions)
+        );
+        assert_eq!(None, build_expression(&[]));
     }
 
     #[test]
-    fn should_extract_map_when_available() {
-        let key = "batata";
-
-        let mut map = HashMap::new();
-        map.insert("batata".to_string(), AttributeValue::Bool(true));
+    fn should_build_update_expression_of_values_and_removals() {
+        let update = ItemUpdate {
+            values: Item::from([
+                ("status".to_string(), AttributeValue::S("OPEN".to_string())),
+                ("name".to_string(), AttributeValue::S("name".to_string())),
+            ]),
+            removed: vec!["close_date".to_string()],
+        };
 
-        let mut values = HashMap::new();
-        values.insert(key.to_string(), AttributeValue::M(map.clone()));
+        assert_eq!(
+            "SET #name = :name, #status = :status REMOVE #close_date",
+            build_update_expression(&update)
+        );
+        assert_eq!(
+            HashMap::from([
+                ("#id".to_string(), "id".to_string()),
+                ("#name".to_string(), "name".to_string()),
+                ("#status".to_string(), "status".to_string()),
+                ("#close_date".to_string(), "close_date".to_string())
+            ]),
+            build_update_names(&update)
+        );
+        assert_eq!(2, build_update_values(&update).len());
+    }
 
-        let result = DynamoDbClient::extract_map(key, &values);
+    #[rstest]
+    #[case(0, "attribute_not_exists(#version) OR #version = :version")]
+    #[case(4, "#version = :version")]
+    fn should_build_version_condition(#[case] expected: u64, #[case] expression: &str) {
+        let condition = Condition::Version {
+            attribute: "version".to_string(),
+            expected,
+        };
 
-        assert!(result.is_some());
-        assert_eq!(map.clone(), result.unwrap());
+        assert_eq!(expression, build_condition_expression(&condition));
+        assert_eq!(
+            HashMap::from([("#version".to_string(), "version".to_string())]),
+            build_condition_names(&condition)
+        );
+        assert_eq!(
+            HashMap::from([(
+                ":version".to_string(),
+                AttributeValue::N(expected.to_string())
+            )]),
+            build_condition_values(condition)
+        );
     }
 
     #[test]
-    fn should_return_none_when_map_is_not_available() {
-        let key = "batata";
-        let values = HashMap::new();
+    fn should_build_not_equal_condition() {
+        let condition = Condition::NotEqual {
+            attribute: "status".to_string(),
+            value: AttributeValue::S("CLOSED".to_string()),
+        };
 
-        let result = DynamoDbClient::extract_map(key, &values);
+        assert_eq!(
+            "attribute_exists(#id) AND #condition <> :condition",
+            build_condition_expression(&condition)
+        );
+        assert_eq!(
+            HashMap::from([
+                ("#id".to_string(), "id".to_string()),
+                ("#condition".to_string(), "status".to_string())
+            ]),
+            build_condition_names(&condition)
+        );
+        assert_eq!(
+            HashMap::from([(
+                ":condition".to_string(),
+                AttributeValue::S("CLOSED".to_string())
+            )]),
+            build_condition_values(condition)
+        );
+    }
 
-        assert!(result.is_none());
+    #[rstest]
+    #[case("ProvisionedThroughputExceededException", vec![], "Transient")]
+    #[case("ThrottlingException", vec![], "Transient")]
+    #[case("ConditionalCheckFailedException", vec![], "Conflict")]
+    #[case("TransactionConflictException", vec![], "Transient")]
+    #[case("IdempotentParameterMismatchException", vec![], "Conflict")]
+    #[case("TransactionInProgressException", vec![], "Transient")]
+    #[case("ResourceNotFoundException", vec![], "Permanent")]
+    #[case("ValidationException", vec![], "Permanent")]
+    #[case("TransactionCanceledException", vec!["None", "ConditionalCheckFailed"], "Conflict")]
+    #[case("TransactionCanceledException", vec!["ThrottlingError", "ConditionalCheckFailed"], "Conflict")]
+    #[case("TransactionCanceledException", vec!["None", "ThrottlingError"], "Transient")]
+    #[case("TransactionCanceledException", vec!["ProvisionedThroughputExceeded"], "Transient")]
+    #[case("TransactionCanceledException", vec!["TransactionConflict", "None"], "Transient")]
+    #[case("TransactionCanceledException", vec!["None", "ValidationError"], "Permanent")]
+    #[case("TransactionCanceledException", vec![], "Permanent")]
+    fn should_classify_service_errors_by_code(
+        #[case] code: &str,
+        #[case] reasons: Vec<&str>,
+        #[case] expected: &str,
+    ) {
+        let metadata = ErrorMetadata::builder()
+            .code(code)
+            .message("failure")
+            .build();
+        let error = match code {
+            "TransactionCanceledException" => {
+                TransactWriteItemsError::TransactionCanceledException(
+                    TransactionCanceledException::builder()
+                        .message("failure")
+                        .set_cancellation_reasons(Some(
+                            reasons
+                                .into_iter()
+                                .map(|reason| CancellationReason::builder().code(reason).build())
+                                .collect(),
+                        ))
+                        .meta(metadata)
+                        .build(),
+                )
+            }
+            _ => TransactWriteItemsError::generic(metadata),
+        };
+
+        let result = classify_transact_write_error(error);
+
+        assert!(format!("{result:?}").starts_with(expected));
     }
 
     #[test]
-    #[should_panic]
-    fn should_fail_when_attribute_is_not_map() {
-        let key = "batata";
-        let mut values = HashMap::new();
-        values.insert(key.to_string(), AttributeValue::N("1".to_string()));
+    fn should_classify_timeouts_as_transient() {
+        let error: SdkError<ScanError> = SdkError::timeout_error("timed out");
 
-        DynamoDbClient::extract_map(key, &values);
+        assert!(matches!(ApiError::from(error), ApiError::Transient { .. }));
     }
 
     #[test]
-    fn should_convert_valid_date() {
-        let expected_date =
-            NaiveDateTime::parse_from_str("15/02/2023 13:51:12+03:00", "%d/%m/%Y %H:%M:%S%z")
-                .unwrap();
+    fn should_classify_construction_failures_as_permanent() {
+        let error: SdkError<ScanError> = SdkError::construction_failure("invalid request");
 
-        assert_eq!(
-            expected_date,
-            convert_date("15/02/2023 13:51:12+03:00".to_string())
-        )
+        assert!(matches!(ApiError::from(error), ApiError::Permanent { .. }));
     }
 
-    #[test]
-    #[should_panic]
-    fn should_panic_when_converting_invalid_date() {
-        convert_date("15/02/2023 13:51".to_string());
+    fn build_index(table_name: &str, attribute_name: &str, index_name: &str) -> SecondaryIndex {
+        SecondaryIndex {
+            table_name: table_name.to_string(),
+            attribute_name: attribute_name.to_string(),
+            index_name: index_name.to_string(),
+        }
     }
 }
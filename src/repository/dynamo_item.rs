@@ -0,0 +1,1075 @@
+use crate::error::DecodeError;
+use crate::model::account::{Account, AccountBalance};
+use crate::model::expense::{Expense, ExpensePayment, ExpenseValue, Recurrence};
+use crate::model::fx_rate::FxRate;
+use crate::model::page::{Cursor, CursorValue};
+use crate::model::transaction::Transaction;
+use crate::model::Currency;
+use aws_sdk_dynamodb::types::AttributeValue;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, SecondsFormat, Utc};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+pub type Item = HashMap<String, AttributeValue>;
+
+pub static ID_ATTRIBUTE: &str = "id";
+pub static BALANCES_ATTRIBUTE: &str = "balances";
+pub static CLOSE_DATE_ATTRIBUTE: &str = "close_date";
+pub static VERSION_ATTRIBUTE: &str = "version";
+/// String set of the months in `balances`, guarding the conditional append of a new balance.
+pub static BALANCE_MONTHS_ATTRIBUTE: &str = "balance_months";
+
+/// Codec between an entity and its attribute map, shared by every `DatabaseClient` backend.
+pub trait DynamoItem: TryFrom<Item, Error = DecodeError> + Into<Item> {}
+
+impl<T: TryFrom<Item, Error = DecodeError> + Into<Item>> DynamoItem for T {}
+
+impl TryFrom<Item> for Account {
+    type Error = DecodeError;
+
+    fn try_from(values: Item) -> Result<Self, Self::Error> {
+        decode_account(&values).map_err(|error| error.with_item_id(extract_id(&values)))
+    }
+}
+
+impl TryFrom<Item> for Transaction {
+    type Error = DecodeError;
+
+    fn try_from(values: Item) -> Result<Self, Self::Error> {
+        decode_transaction(&values).map_err(|error| error.with_item_id(extract_id(&values)))
+    }
+}
+
+impl TryFrom<Item> for Expense {
+    type Error = DecodeError;
+
+    fn try_from(values: Item) -> Result<Self, Self::Error> {
+        decode_expense(&values).map_err(|error| error.with_item_id(extract_id(&values)))
+    }
+}
+
+impl TryFrom<Item> for FxRate {
+    type Error = DecodeError;
+
+    fn try_from(values: Item) -> Result<Self, Self::Error> {
+        decode_fx_rate(&values).map_err(|error| error.with_item_id(extract_id(&values)))
+    }
+}
+
+fn decode_account(values: &Item) -> Result<Account, DecodeError> {
+    Ok(Account {
+        id: required(ID_ATTRIBUTE, extract_string(ID_ATTRIBUTE, values))?,
+        name: required("name", extract_string("name", values))?,
+        bank_name: required("bank_name", extract_string("bank_name", values))?,
+        open_date: required("open_date", extract_date("open_date", values))?,
+        close_date: extract_date(CLOSE_DATE_ATTRIBUTE, values)?,
+        account_type: required("type", extract_enum("type", values))?,
+        balances: extract_items(BALANCES_ATTRIBUTE, values, decode_account_balance)?,
+        status: required("status", extract_enum("status", values))?,
+    })
+}
+
+fn decode_account_balance(values: &Item) -> Result<AccountBalance, DecodeError> {
+    Ok(AccountBalance {
+        date: required("date", extract_date("date", values))?,
+        balance: decode_nested("balance", values, decode_currency)?,
+    })
+}
+
+fn decode_currency(values: &Item) -> Result<Currency, DecodeError> {
+    Ok(Currency {
+        code: required("currency", extract_enum("currency", values))?,
+        value: required("value", extract_number("value", values))?,
+    })
+}
+
+fn decode_transaction(values: &Item) -> Result<Transaction, DecodeError> {
+    Ok(Transaction {
+        id: required(ID_ATTRIBUTE, extract_string(ID_ATTRIBUTE, values))?,
+        source_id: extract_string("source_id", values)?,
+        destination_id: extract_string("destination_id", values)?,
+        expense_id: extract_string("expense_id", values)?,
+        description: required("description", extract_string("description", values))?,
+        date: required("date", extract_date("date", values))?,
+        month: required("month", extract_integer("month", values))?,
+        year: required("year", extract_integer("year", values))?,
+        value: decode_nested("value", values, decode_currency)?,
+        operation: required("operation", extract_enum("operation", values))?,
+        transaction_type: required("type", extract_enum("type", values))?,
+        status: required("status", extract_enum("status", values))?,
+    })
+}
+
+fn decode_expense(values: &Item) -> Result<Expense, DecodeError> {
+    Ok(Expense {
+        id: required(ID_ATTRIBUTE, extract_string(ID_ATTRIBUTE, values))?,
+        description: required("description", extract_string("description", values))?,
+        date_added: required("date_added", extract_date("date_added", values))?,
+        value_history: extract_items("value_history", values, decode_expense_value)?,
+        payments: extract_items("payments", values, decode_expense_payment)?,
+        expense_type: required("type", extract_enum("type", values))?,
+        status: required("status", extract_enum("status", values))?,
+        recurrence: extract_map("recurrence", values)?
+            .map(|item| decode_recurrence(&item).map_err(|error| error.within("recurrence")))
+            .transpose()?,
+        version: extract_integer(VERSION_ATTRIBUTE, values)?.unwrap_or_default(),
+    })
+}
+
+fn decode_expense_value(values: &Item) -> Result<ExpenseValue, DecodeError> {
+    Ok(ExpenseValue {
+        due_date: required("due_date", extract_date("due_date", values))?,
+        added_date: required("added_date", extract_date("added_date", values))?,
+        value: decode_nested("value", values, decode_currency)?,
+    })
+}
+
+fn decode_expense_payment(values: &Item) -> Result<ExpensePayment, DecodeError> {
+    Ok(ExpensePayment {
+        due_date: required("due_date", extract_date("due_date", values))?,
+        payment_date: extract_date("payment_date", values)?,
+        receive_date: extract_date("receive_date", values)?,
+        status: required("status", extract_enum("status", values))?,
+        transaction_id: extract_string("transaction_id", values)?,
+    })
+}
+
+fn decode_recurrence(values: &Item) -> Result<Recurrence, DecodeError> {
+    Ok(Recurrence {
+        day_of_month: required("day_of_month", extract_integer("day_of_month", values))?,
+        interval_months: required(
+            "interval_months",
+            extract_integer("interval_months", values),
+        )?,
+        end_date: extract_date("end_date", values)?,
+    })
+}
+
+fn decode_fx_rate(values: &Item) -> Result<FxRate, DecodeError> {
+    Ok(FxRate {
+        source: required("source", extract_enum("source", values))?,
+        target: required("target", extract_enum("target", values))?,
+        rate: required("rate", extract_number("rate", values))?,
+        valid_from: required("valid_from", extract_date("valid_from", values))?,
+    })
+}
+
+impl From<Account> for Item {
+    fn from(account: Account) -> Self {
+        let mut values = HashMap::new();
+        values.insert(ID_ATTRIBUTE.to_string(), AttributeValue::S(account.id));
+        values.insert("name".to_string(), AttributeValue::S(account.name));
+        values.insert(
+            "bank_name".to_string(),
+            AttributeValue::S(account.bank_name),
+        );
+        values.insert(
+            "open_date".to_string(),
+            AttributeValue::S(format_date(&account.open_date)),
+        );
+        if let Some(close_date) = account.close_date {
+            values.insert(
+                CLOSE_DATE_ATTRIBUTE.to_string(),
+                AttributeValue::S(format_date(&close_date)),
+            );
+        }
+        values.insert(
+            "type".to_string(),
+            AttributeValue::S(account.account_type.to_string()),
+        );
+        let mut months: Vec<String> = account
+            .balances
+            .iter()
+            .map(|balance| balance.month())
+            .collect();
+        months.sort();
+        months.dedup();
+        // DynamoDB rejects empty sets
+        if !months.is_empty() {
+            values.insert(
+                BALANCE_MONTHS_ATTRIBUTE.to_string(),
+                AttributeValue::Ss(months),
+            );
+        }
+        values.insert(
+            BALANCES_ATTRIBUTE.to_string(),
+            AttributeValue::L(
+                account
+                    .balances
+                    .into_iter()
+                    .map(|balance| AttributeValue::M(balance.into()))
+                    .collect(),
+            ),
+        );
+        values.insert(
+            "status".to_string(),
+            AttributeValue::S(account.status.to_string()),
+        );
+        values
+    }
+}
+
+impl From<AccountBalance> for Item {
+    fn from(balance: AccountBalance) -> Self {
+        let mut values = HashMap::new();
+        values.insert(
+            "date".to_string(),
+            AttributeValue::S(format_date(&balance.date)),
+        );
+        values.insert(
+            "balance".to_string(),
+            AttributeValue::M(balance.balance.into()),
+        );
+        values
+    }
+}
+
+impl From<Currency> for Item {
+    fn from(currency: Currency) -> Self {
+        let mut values = HashMap::new();
+        values.insert(
+            "currency".to_string(),
+            AttributeValue::S(currency.code.to_string()),
+        );
+        values.insert(
+            "value".to_string(),
+            AttributeValue::N(currency.value.to_string()),
+        );
+        values
+    }
+}
+
+impl From<Transaction> for Item {
+    fn from(transaction: Transaction) -> Self {
+        let mut values = HashMap::new();
+        values.insert(ID_ATTRIBUTE.to_string(), AttributeValue::S(transaction.id));
+        for (key, value) in [
+            ("source_id", transaction.source_id),
+            ("destination_id", transaction.destination_id),
+            ("expense_id", transaction.expense_id),
+        ] {
+            if let Some(value) = value {
+                values.insert(key.to_string(), AttributeValue::S(value));
+            }
+        }
+        values.insert(
+            "description".to_string(),
+            AttributeValue::S(transaction.description),
+        );
+        values.insert(
+            "date".to_string(),
+            AttributeValue::S(format_date(&transaction.date)),
+        );
+        values.insert(
+            "month".to_string(),
+            AttributeValue::N(transaction.month.to_string()),
+        );
+        values.insert(
+            "year".to_string(),
+            AttributeValue::N(transaction.year.to_string()),
+        );
+        values.insert(
+            "value".to_string(),
+            AttributeValue::M(transaction.value.into()),
+        );
+        values.insert(
+            "operation".to_string(),
+            AttributeValue::S(transaction.operation.to_string()),
+        );
+        values.insert(
+            "type".to_string(),
+            AttributeValue::S(transaction.transaction_type.to_string()),
+        );
+        values.insert(
+            "status".to_string(),
+            AttributeValue::S(transaction.status.to_string()),
+        );
+        values
+    }
+}
+
+impl From<Expense> for Item {
+    fn from(expense: Expense) -> Self {
+        let mut values = HashMap::new();
+        values.insert(ID_ATTRIBUTE.to_string(), AttributeValue::S(expense.id));
+        values.insert(
+            "description".to_string(),
+            AttributeValue::S(expense.description),
+        );
+        values.insert(
+            "date_added".to_string(),
+            AttributeValue::S(format_date(&expense.date_added)),
+        );
+        values.insert(
+            "value_history".to_string(),
+            AttributeValue::L(
+                expense
+                    .value_history
+                    .into_iter()
+                    .map(|value| AttributeValue::M(value.into()))
+                    .collect(),
+            ),
+        );
+        values.insert(
+            "payments".to_string(),
+            AttributeValue::L(
+                expense
+                    .payments
+                    .into_iter()
+                    .map(|payment| AttributeValue::M(payment.into()))
+                    .collect(),
+            ),
+        );
+        values.insert(
+            "type".to_string(),
+            AttributeValue::S(expense.expense_type.to_string()),
+        );
+        values.insert(
+            "status".to_string(),
+            AttributeValue::S(expense.status.to_string()),
+        );
+        if let Some(recurrence) = expense.recurrence {
+            values.insert(
+                "recurrence".to_string(),
+                AttributeValue::M(recurrence.into()),
+            );
+        }
+        values.insert(
+            VERSION_ATTRIBUTE.to_string(),
+            AttributeValue::N(expense.version.to_string()),
+        );
+        values
+    }
+}
+
+impl From<ExpenseValue> for Item {
+    fn from(value: ExpenseValue) -> Self {
+        let mut values = HashMap::new();
+        values.insert(
+            "due_date".to_string(),
+            AttributeValue::S(format_date(&value.due_date)),
+        );
+        values.insert(
+            "added_date".to_string(),
+            AttributeValue::S(format_date(&value.added_date)),
+        );
+        values.insert("value".to_string(), AttributeValue::M(value.value.into()));
+        values
+    }
+}
+
+impl From<ExpensePayment> for Item {
+    fn from(payment: ExpensePayment) -> Self {
+        let mut values = HashMap::new();
+        values.insert(
+            "due_date".to_string(),
+            AttributeValue::S(format_date(&payment.due_date)),
+        );
+        for (key, date) in [
+            ("payment_date", payment.payment_date),
+            ("receive_date", payment.receive_date),
+        ] {
+            if let Some(date) = date {
+                values.insert(key.to_string(), AttributeValue::S(format_date(&date)));
+            }
+        }
+        values.insert(
+            "status".to_string(),
+            AttributeValue::S(payment.status.to_string()),
+        );
+        if let Some(transaction_id) = payment.transaction_id {
+            values.insert(
+                "transaction_id".to_string(),
+                AttributeValue::S(transaction_id),
+            );
+        }
+        values
+    }
+}
+
+impl From<Recurrence> for Item {
+    fn from(recurrence: Recurrence) -> Self {
+        let mut values = HashMap::new();
+        values.insert(
+            "day_of_month".to_string(),
+            AttributeValue::N(recurrence.day_of_month.to_string()),
+        );
+        values.insert(
+            "interval_months".to_string(),
+            AttributeValue::N(recurrence.interval_months.to_string()),
+        );
+        if let Some(end_date) = recurrence.end_date {
+            values.insert(
+                "end_date".to_string(),
+                AttributeValue::S(format_date(&end_date)),
+            );
+        }
+        values
+    }
+}
+
+impl From<FxRate> for Item {
+    fn from(rate: FxRate) -> Self {
+        let mut values = HashMap::new();
+        values.insert(ID_ATTRIBUTE.to_string(), AttributeValue::S(rate.id()));
+        values.insert(
+            "source".to_string(),
+            AttributeValue::S(rate.source.to_string()),
+        );
+        values.insert(
+            "target".to_string(),
+            AttributeValue::S(rate.target.to_string()),
+        );
+        values.insert("rate".to_string(), AttributeValue::N(rate.rate.to_string()));
+        values.insert(
+            "valid_from".to_string(),
+            AttributeValue::S(format_date(&rate.valid_from)),
+        );
+        values
+    }
+}
+
+impl From<Cursor> for Item {
+    fn from(value: Cursor) -> Self {
+        value
+            .key
+            .into_iter()
+            .map(|(name, value)| match value {
+                CursorValue::S(value) => (name, AttributeValue::S(value)),
+                CursorValue::N(value) => (name, AttributeValue::N(value)),
+            })
+            .collect()
+    }
+}
+
+impl From<Item> for Cursor {
+    fn from(values: Item) -> Self {
+        Cursor {
+            key: values
+                .into_iter()
+                .filter_map(|(name, value)| match value {
+                    AttributeValue::S(value) => Some((name, CursorValue::S(value))),
+                    AttributeValue::N(value) => Some((name, CursorValue::N(value))),
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+}
+
+pub fn extract_string(key: &str, values: &Item) -> Result<Option<String>, DecodeError> {
+    values
+        .get(key)
+        .map(|value| {
+            value
+                .as_s()
+                .cloned()
+                .map_err(|_| DecodeError::new(key, "expected a string attribute"))
+        })
+        .transpose()
+}
+
+pub fn extract_number(key: &str, values: &Item) -> Result<Option<Decimal>, DecodeError> {
+    extract_numeric::<Decimal>(key, values)
+}
+
+/// Reads a whole number, failing when it does not fit `T` instead of wrapping around.
+pub fn extract_integer<T: TryFrom<i64>>(
+    key: &str,
+    values: &Item,
+) -> Result<Option<T>, DecodeError> {
+    extract_numeric::<i64>(key, values)?
+        .map(|value| T::try_from(value).map_err(|_| DecodeError::new(key, "number out of range")))
+        .transpose()
+}
+
+pub fn extract_list(key: &str, values: &Item) -> Result<Option<Vec<AttributeValue>>, DecodeError> {
+    values
+        .get(key)
+        .map(|value| {
+            value
+                .as_l()
+                .cloned()
+                .map_err(|_| DecodeError::new(key, "expected a list attribute"))
+        })
+        .transpose()
+}
+
+pub fn extract_map(key: &str, values: &Item) -> Result<Option<Item>, DecodeError> {
+    values
+        .get(key)
+        .map(|value| {
+            value
+                .as_m()
+                .cloned()
+                .map_err(|_| DecodeError::new(key, "expected a map attribute"))
+        })
+        .transpose()
+}
+
+fn extract_numeric<T: FromStr>(key: &str, values: &Item) -> Result<Option<T>, DecodeError> {
+    values
+        .get(key)
+        .map(|value| {
+            value
+                .as_n()
+                .map_err(|_| DecodeError::new(key, "expected a number attribute"))?
+                .parse::<T>()
+                .map_err(|_| DecodeError::new(key, "invalid number"))
+        })
+        .transpose()
+}
+
+fn extract_enum<T>(key: &str, values: &Item) -> Result<Option<T>, DecodeError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    extract_string(key, values)?
+        .map(|value| {
+            T::from_str(value.as_str())
+                .map_err(|error| DecodeError::new(key, error.to_string().as_str()))
+        })
+        .transpose()
+}
+
+fn extract_date(key: &str, values: &Item) -> Result<Option<DateTime<Utc>>, DecodeError> {
+    extract_string(key, values)?
+        .map(|value| convert_date(value).map_err(|_| DecodeError::new(key, "invalid date")))
+        .transpose()
+}
+
+fn extract_items<T>(
+    key: &str,
+    values: &Item,
+    decode: fn(&Item) -> Result<T, DecodeError>,
+) -> Result<Vec<T>, DecodeError> {
+    required(key, extract_list(key, values))?
+        .iter()
+        .enumerate()
+        .map(|(index, attribute)| {
+            let path = format!("{key}[{index}]");
+            let item = attribute
+                .as_m()
+                .map_err(|_| DecodeError::new(path.as_str(), "expected a map attribute"))?;
+            decode(item).map_err(|error| error.within(path.as_str()))
+        })
+        .collect()
+}
+
+fn decode_nested<T>(
+    key: &str,
+    values: &Item,
+    decode: fn(&Item) -> Result<T, DecodeError>,
+) -> Result<T, DecodeError> {
+    decode(&required(key, extract_map(key, values))?).map_err(|error| error.within(key))
+}
+
+fn required<T>(key: &str, value: Result<Option<T>, DecodeError>) -> Result<T, DecodeError> {
+    value?.ok_or_else(|| DecodeError::new(key, "missing required attribute"))
+}
+
+fn extract_id(values: &Item) -> Option<String> {
+    extract_string(ID_ATTRIBUTE, values).ok().flatten()
+}
+
+/// Dates are written as RFC 3339, but items stored before that use `dd/mm/yyyy`, with or without
+/// a time and offset. Values without an offset are taken as UTC.
+fn convert_date(value: String) -> Result<DateTime<Utc>, chrono::ParseError> {
+    DateTime::parse_from_rfc3339(value.as_str())
+        .or_else(|_| DateTime::parse_from_str(value.as_str(), "%d/%m/%Y %H:%M:%S%z"))
+        .map(|date| date.with_timezone(&Utc))
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(value.as_str(), "%d/%m/%Y %H:%M:%S")
+                .map(|date| date.and_utc())
+        })
+        .or_else(|_| {
+            NaiveDate::parse_from_str(value.as_str(), "%d/%m/%Y")
+                .map(|date| date.and_hms_opt(0, 0, 0).unwrap().and_utc())
+        })
+}
+
+/// Always writes nine fractional digits: dates keep their full precision through a round trip,
+/// and the stored strings, used as index sort keys, sort in time order.
+fn format_date(value: &DateTime<Utc>) -> String {
+    value.to_rfc3339_opts(SecondsFormat::Nanos, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::account::{AccountStatus, AccountType};
+    use crate::model::expense::{ExpenseStatus, ExpenseType, PaymentStatus};
+    use crate::model::transaction::{TransactionOperation, TransactionStatus, TransactionType};
+    use crate::model::CurrencyCode;
+    use rstest::rstest;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn should_extract_string_when_available() {
+        let key = "batata";
+        let mut values = HashMap::new();
+        values.insert(key.to_string(), AttributeValue::S("frita".to_string()));
+
+        let result = extract_string(key, &values).unwrap();
+
+        assert!(result.is_some());
+        assert_eq!("frita".to_string(), result.unwrap());
+    }
+
+    #[test]
+    fn should_return_none_when_string_is_not_available() {
+        let key = "batata";
+        let values = HashMap::new();
+
+        let result = extract_string(key, &values).unwrap();
+
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn should_fail_when_attribute_is_not_string() {
+        let key = "batata";
+        let mut values = HashMap::new();
+        values.insert(key.to_string(), AttributeValue::N("1".to_string()));
+
+        let result = extract_string(key, &values);
+
+        assert_eq!(key, result.unwrap_err().attribute);
+    }
+
+    #[test]
+    fn should_extract_number_when_available() {
+        let key = "batata";
+        let mut values = HashMap::new();
+        values.insert(key.to_string(), AttributeValue::N("1.1".to_string()));
+
+        let result = extract_number(key, &values).unwrap();
+
+        assert!(result.is_some());
+        assert_eq!(dec!(1.1), result.unwrap());
+    }
+
+    #[test]
+    fn should_return_none_when_number_is_not_available() {
+        let key = "batata";
+        let values = HashMap::new();
+
+        let result = extract_number(key, &values).unwrap();
+
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn should_fail_when_attribute_is_not_number() {
+        let key = "batata";
+        let mut values = HashMap::new();
+        values.insert(key.to_string(), AttributeValue::S("batata".to_string()));
+
+        let result = extract_number(key, &values);
+
+        assert_eq!(key, result.unwrap_err().attribute);
+    }
+
+    #[test]
+    fn should_fail_when_attribute_is_not_parseable_number() {
+        let key = "batata";
+        let mut values = HashMap::new();
+        values.insert(key.to_string(), AttributeValue::N("batata".to_string()));
+
+        let result = extract_number(key, &values);
+
+        assert_eq!(key, result.unwrap_err().attribute);
+    }
+
+    #[rstest]
+    #[case("month", "4294967298")]
+    #[case("month", "-1")]
+    #[case("year", "2147483648")]
+    fn should_fail_when_transaction_integer_is_out_of_range(
+        #[case] key: &str,
+        #[case] value: &str,
+    ) {
+        let mut values = build_transaction_item();
+        values.insert(key.to_string(), AttributeValue::N(value.to_string()));
+
+        let error = Transaction::try_from(values).unwrap_err();
+
+        assert_eq!(key, error.attribute);
+        assert_eq!("number out of range", error.message);
+    }
+
+    #[rstest]
+    #[case("day_of_month", "-1")]
+    #[case("interval_months", "4294967297")]
+    fn should_fail_when_recurrence_integer_is_out_of_range(#[case] key: &str, #[case] value: &str) {
+        let mut values = Item::from(Recurrence {
+            day_of_month: 15,
+            interval_months: 1,
+            end_date: None,
+        });
+        values.insert(key.to_string(), AttributeValue::N(value.to_string()));
+
+        let error = decode_recurrence(&values).unwrap_err();
+
+        assert_eq!(key, error.attribute);
+        assert_eq!("number out of range", error.message);
+    }
+
+    #[test]
+    fn should_extract_list_when_available() {
+        let key = "batata";
+        let mut values = HashMap::new();
+        values.insert(
+            key.to_string(),
+            AttributeValue::L(vec![AttributeValue::S("test".to_string())]),
+        );
+
+        let result = extract_list(key, &values).unwrap();
+
+        assert!(result.is_some());
+        assert_eq!(vec![AttributeValue::S("test".to_string())], result.unwrap());
+    }
+
+    #[test]
+    fn should_return_none_when_list_is_not_available() {
+        let key = "batata";
+        let values = HashMap::new();
+
+        let result = extract_list(key, &values).unwrap();
+
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn should_fail_when_attribute_is_not_list() {
+        let key = "batata";
+        let mut values = HashMap::new();
+        values.insert(key.to_string(), AttributeValue::N("1".to_string()));
+
+        let result = extract_list(key, &values);
+
+        assert_eq!(key, result.unwrap_err().attribute);
+    }
+
+    #[test]
+    fn should_extract_map_when_available() {
+        let key = "batata";
+
+        let mut map = HashMap::new();
+        map.insert("batata".to_string(), AttributeValue::Bool(true));
+
+        let mut values = HashMap::new();
+        values.insert(key.to_string(), AttributeValue::M(map.clone()));
+
+        let result = extract_map(key, &values).unwrap();
+
+        assert!(result.is_some());
+        assert_eq!(map.clone(), result.unwrap());
+    }
+
+    #[test]
+    fn should_return_none_when_map_is_not_available() {
+        let key = "batata";
+        let values = HashMap::new();
+
+        let result = extract_map(key, &values).unwrap();
+
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn should_fail_when_attribute_is_not_map() {
+        let key = "batata";
+        let mut values = HashMap::new();
+        values.insert(key.to_string(), AttributeValue::N("1".to_string()));
+
+        let result = extract_map(key, &values);
+
+        assert_eq!(key, result.unwrap_err().attribute);
+    }
+
+    #[rstest]
+    #[case("2023-02-15T10:51:12Z")]
+    #[case("2023-02-15T13:51:12+03:00")]
+    #[case("15/02/2023 13:51:12+03:00")]
+    #[case("15/02/2023 10:51:12+00:00")]
+    #[case("15/02/2023 10:51:12")]
+    fn should_convert_valid_date(#[case] value: &str) {
+        let expected_date = NaiveDate::from_ymd_opt(2023, 2, 15)
+            .unwrap()
+            .and_hms_opt(10, 51, 12)
+            .unwrap()
+            .and_utc();
+
+        assert_eq!(expected_date, convert_date(value.to_string()).unwrap())
+    }
+
+    #[test]
+    fn should_convert_legacy_date_without_time_to_midnight() {
+        assert_eq!(
+            "2023-02-15T00:00:00.000000000Z",
+            format_date(&convert_date("15/02/2023".to_string()).unwrap())
+        );
+    }
+
+    #[test]
+    fn should_fail_when_converting_invalid_date() {
+        assert!(convert_date("15/02/2023 13:51".to_string()).is_err());
+    }
+
+    #[test]
+    fn should_round_trip_formatted_date() {
+        let date = convert_date("15/02/2023 13:51:12+00:00".to_string()).unwrap();
+
+        assert_eq!("2023-02-15T13:51:12.000000000Z", format_date(&date));
+        assert_eq!(date, convert_date(format_date(&date)).unwrap());
+    }
+
+    #[rstest]
+    #[case("2023-02-15T13:51:12.5Z", "2023-02-15T13:51:12.500000000Z")]
+    #[case("2023-02-15T13:51:12.123456Z", "2023-02-15T13:51:12.123456000Z")]
+    #[case("2023-02-15T13:51:12.000000001Z", "2023-02-15T13:51:12.000000001Z")]
+    fn should_round_trip_date_with_fractional_seconds(#[case] value: &str, #[case] expected: &str) {
+        let date = convert_date(value.to_string()).unwrap();
+
+        assert_eq!(expected, format_date(&date));
+        assert_eq!(date, convert_date(format_date(&date)).unwrap());
+    }
+
+    #[test]
+    fn should_format_dates_in_time_order() {
+        let dates: Vec<DateTime<Utc>> = [
+            "2023-02-15T13:51:11.999999999Z",
+            "2023-02-15T13:51:12Z",
+            "2023-02-15T13:51:12.000000001Z",
+            "2023-02-15T13:51:12.5Z",
+            "2023-02-15T13:51:13Z",
+        ]
+        .map(|value| convert_date(value.to_string()).unwrap())
+        .into();
+
+        let mut formatted: Vec<String> = dates.iter().rev().map(format_date).collect();
+        formatted.sort();
+
+        assert_eq!(dates.iter().map(format_date).collect::<Vec<_>>(), formatted);
+    }
+
+    #[test]
+    fn should_round_trip_account_attributes() {
+        let account = Account {
+            id: "id".to_string(),
+            name: "name".to_string(),
+            bank_name: "bank".to_string(),
+            open_date: convert_date("15/02/2023 13:51:12+00:00".to_string()).unwrap(),
+            close_date: None,
+            account_type: AccountType::Checking,
+            balances: vec![AccountBalance {
+                date: convert_date("28/02/2023 23:59:59+00:00".to_string()).unwrap(),
+                balance: Currency {
+                    code: CurrencyCode::BRL,
+                    value: dec!(10.5),
+                },
+            }],
+            status: AccountStatus::Open,
+        };
+
+        let values = Item::from(account);
+
+        assert!(!values.contains_key("close_date"));
+        assert_eq!(
+            Some(&AttributeValue::Ss(vec!["2023-02".to_string()])),
+            values.get(BALANCE_MONTHS_ATTRIBUTE)
+        );
+        assert_eq!(
+            Some(&AttributeValue::S("CHECKING".to_string())),
+            values.get("type")
+        );
+
+        let converted = Account::try_from(values).unwrap();
+        assert_eq!("id", converted.id);
+        assert_eq!(None, converted.close_date);
+        assert_eq!(dec!(10.5), converted.balances[0].balance.value);
+    }
+
+    #[test]
+    fn should_round_trip_transaction_attributes() {
+        let values = build_transaction_item();
+
+        assert!(!values.contains_key("destination_id"));
+        assert_eq!(
+            Some(&AttributeValue::N("2".to_string())),
+            values.get("month")
+        );
+        assert_eq!(
+            Some(&AttributeValue::S("PAYMENT".to_string())),
+            values.get("type")
+        );
+
+        let converted = Transaction::try_from(values).unwrap();
+        assert_eq!(Some("source".to_string()), converted.source_id);
+        assert_eq!(None, converted.expense_id);
+        assert_eq!(2023, converted.year);
+        assert_eq!(TransactionOperation::Debit, converted.operation);
+    }
+
+    #[test]
+    fn should_round_trip_expense_attributes() {
+        let date = convert_date("15/02/2023 00:00:00+00:00".to_string()).unwrap();
+        let expense = Expense {
+            id: "id".to_string(),
+            description: "description".to_string(),
+            date_added: date,
+            value_history: vec![ExpenseValue {
+                due_date: date,
+                added_date: date,
+                value: Currency {
+                    code: CurrencyCode::BRL,
+                    value: dec!(100),
+                },
+            }],
+            payments: vec![ExpensePayment {
+                due_date: date,
+                payment_date: Some(date),
+                receive_date: None,
+                status: PaymentStatus::Paid,
+                transaction_id: Some("transaction".to_string()),
+            }],
+            expense_type: ExpenseType::OneTime,
+            status: ExpenseStatus::Closed,
+            recurrence: Some(Recurrence {
+                day_of_month: 15,
+                interval_months: 1,
+                end_date: None,
+            }),
+            version: 3,
+        };
+
+        let mut values = Item::from(expense);
+
+        assert_eq!(
+            Some(&AttributeValue::S("ONE_TIME".to_string())),
+            values.get("type")
+        );
+
+        let converted = Expense::try_from(values.clone()).unwrap();
+        assert_eq!("id", converted.id);
+        assert_eq!(dec!(100), converted.value_history[0].value.value);
+        assert_eq!(Some(date), converted.payments[0].payment_date);
+        assert_eq!(None, converted.payments[0].receive_date);
+        assert_eq!(PaymentStatus::Paid, converted.payments[0].status);
+        assert_eq!(
+            Some("transaction".to_string()),
+            converted.payments[0].transaction_id
+        );
+        assert_eq!(15, converted.recurrence.unwrap().day_of_month);
+        assert_eq!(3, converted.version);
+        values.remove(VERSION_ATTRIBUTE);
+        assert_eq!(0, Expense::try_from(values).unwrap().version);
+    }
+
+    #[test]
+    fn should_round_trip_fx_rate_attributes() {
+        let rate = FxRate {
+            source: CurrencyCode::USD,
+            target: CurrencyCode::BRL,
+            rate: dec!(4.9875),
+            valid_from: convert_date("15/02/2023 00:00:00+00:00".to_string()).unwrap(),
+        };
+
+        let values = Item::from(rate.clone());
+
+        assert_eq!(
+            Some(&AttributeValue::S("USD-BRL-20230215000000".to_string())),
+            values.get(ID_ATTRIBUTE)
+        );
+        assert_eq!(rate, FxRate::try_from(values).unwrap());
+    }
+
+    #[test]
+    fn should_fail_when_enum_attribute_is_invalid() {
+        let mut values = HashMap::new();
+        values.insert("type".to_string(), AttributeValue::S("batata".to_string()));
+
+        let result = extract_enum::<AccountType>("type", &values);
+
+        assert_eq!("type", result.unwrap_err().attribute);
+    }
+
+    #[test]
+    fn should_name_attribute_and_item_when_account_is_malformed() {
+        let mut account = build_account_item();
+        account.insert(
+            "status".to_string(),
+            AttributeValue::S("batata".to_string()),
+        );
+
+        let error = Account::try_from(account).unwrap_err();
+
+        assert_eq!("status", error.attribute);
+        assert_eq!(Some("id".to_string()), error.item_id);
+    }
+
+    #[test]
+    fn should_name_nested_attribute_when_balance_is_malformed() {
+        let mut balance = HashMap::new();
+        balance.insert("date".to_string(), AttributeValue::S("batata".to_string()));
+
+        let mut account = build_account_item();
+        account.insert(
+            "balances".to_string(),
+            AttributeValue::L(vec![AttributeValue::M(balance)]),
+        );
+
+        let error = Account::try_from(account).unwrap_err();
+
+        assert_eq!("balances[0].date", error.attribute);
+        assert_eq!(Some("id".to_string()), error.item_id);
+    }
+
+    #[test]
+    fn should_fail_when_required_attribute_is_missing() {
+        let mut account = build_account_item();
+        account.remove("name");
+
+        let error = Account::try_from(account).unwrap_err();
+
+        assert_eq!("name", error.attribute);
+        assert_eq!("missing required attribute", error.message);
+    }
+
+    fn build_transaction_item() -> Item {
+        Item::from(Transaction {
+            id: "id".to_string(),
+            source_id: Some("source".to_string()),
+            destination_id: None,
+            expense_id: None,
+            description: "description".to_string(),
+            date: convert_date("15/02/2023 13:51:12+00:00".to_string()).unwrap(),
+            month: 2,
+            year: 2023,
+            value: Currency {
+                code: CurrencyCode::BRL,
+                value: dec!(10.5),
+            },
+            operation: TransactionOperation::Debit,
+            transaction_type: TransactionType::Payment,
+            status: TransactionStatus::Open,
+        })
+    }
+
+    fn build_account_item() -> Item {
+        Item::from(Account {
+            id: "id".to_string(),
+            name: "name".to_string(),
+            bank_name: "bank".to_string(),
+            open_date: convert_date("15/02/2023 13:51:12+00:00".to_string()).unwrap(),
+            close_date: None,
+            account_type: AccountType::Checking,
+            balances: vec![],
+            status: AccountStatus::Open,
+        })
+    }
+}
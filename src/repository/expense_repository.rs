@@ -0,0 +1,103 @@
+use crate::config::settings::Settings;
+use crate::error::ApiError;
+use crate::model::expense::{Expense, ExpenseStatus, ExpenseType};
+use crate::repository::dynamo_item::VERSION_ATTRIBUTE;
+use crate::repository::{decode_items, Condition, DatabaseClient, DecodeMode, Item, TransactWrite};
+use aws_sdk_dynamodb::types::AttributeValue;
+use std::sync::Arc;
+
+static TABLE_NAME: &str = "Expense";
+static EXPENSE_TYPE_PARAMETER: &str = "type";
+static EXPENSE_STATUS_PARAMETER: &str = "status";
+
+pub struct ExpenseRepository {
+    client: Arc<dyn DatabaseClient>,
+    decode_mode: DecodeMode,
+}
+
+impl ExpenseRepository {
+    pub fn new(client: Arc<dyn DatabaseClient>, settings: &Settings) -> Self {
+        ExpenseRepository {
+            client,
+            decode_mode: settings.decode_mode,
+        }
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn list_expenses(
+        &self,
+        expense_type: Option<ExpenseType>,
+        expense_status: Option<ExpenseStatus>,
+    ) -> Result<Vec<Expense>, ApiError> {
+        let query_attributes = vec![
+            (
+                EXPENSE_TYPE_PARAMETER.to_string(),
+                expense_type.map(|item| item.to_string()),
+            ),
+            (
+                EXPENSE_STATUS_PARAMETER.to_string(),
+                expense_status.map(|item| item.to_string()),
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, value)| value.is_some())
+        .map(|(parameter, value)| (parameter, AttributeValue::S(value.unwrap())))
+        .collect();
+
+        decode_items(
+            self.client
+                .list(TABLE_NAME.to_string(), query_attributes, None, None)
+                .await?
+                .items,
+            self.decode_mode,
+        )
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_expense(&self, id: String) -> Result<Option<Expense>, ApiError> {
+        self.client
+            .get(TABLE_NAME.to_string(), id)
+            .await?
+            .map(|item| Expense::try_from(item).map_err(ApiError::from))
+            .transpose()
+    }
+
+    /// Fails with `Conflict` when the expense was saved since it was read.
+    #[tracing::instrument(skip(self))]
+    pub async fn save_expense(&self, expense: Expense) -> Result<(), ApiError> {
+        let (item, condition) = versioned(expense);
+        self.client
+            .put(TABLE_NAME.to_string(), item, Some(condition))
+            .await
+    }
+
+    /// The write saving `expense` as part of a transaction, applied only while the stored expense
+    /// is still at the version `expense` was read at.
+    pub fn save_expense_write(&self, expense: Expense) -> TransactWrite {
+        let (item, condition) = versioned(expense);
+        TransactWrite::Put {
+            table_name: TABLE_NAME.to_string(),
+            item,
+            condition: Some(condition),
+        }
+    }
+}
+
+/// The item of `expense` at its next version, and the condition that the stored expense is still
+/// at the version `expense` was read at.
+fn versioned(expense: Expense) -> (Item, Condition) {
+    let expected = expense.version;
+    let item = Expense {
+        version: expected + 1,
+        ..expense
+    }
+    .into();
+
+    (
+        item,
+        Condition::Version {
+            attribute: VERSION_ATTRIBUTE.to_string(),
+            expected,
+        },
+    )
+}
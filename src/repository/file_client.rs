@@ -0,0 +1,591 @@
+use crate::error::ApiError;
+use crate::repository::dynamo_item::Item;
+use crate::repository::{
+    Condition, DatabaseClient, InMemoryClient, ItemPage, ItemUpdate, ListAppend, TransactWrite,
+};
+use async_trait::async_trait;
+use aws_sdk_dynamodb::types::AttributeValue;
+use aws_smithy_types::Blob;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::fs::{read, rename, File, OpenOptions};
+use std::io::{BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+/// Persists every write as a line of DynamoDB-style JSON and serves reads from memory. The log is
+/// replayed and compacted to the latest version of each item when the client is opened, dropping
+/// a last line left incomplete by a crash.
+///
+/// Meant for local development only: every write blocks the runtime thread on the disk while
+/// holding the lock that serializes writes.
+pub struct FileClient {
+    items: InMemoryClient,
+    file: Mutex<File>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Record {
+    table: String,
+    item: Map<String, Value>,
+}
+
+impl FileClient {
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        let items = InMemoryClient::default();
+        let mut records = 0;
+        if path.exists() {
+            let content = read(path)?;
+            let lines: Vec<&[u8]> = content.split(|byte| *byte == b'\n').collect();
+            for (number, line) in lines.iter().enumerate() {
+                if line.trim_ascii().is_empty() {
+                    continue;
+                }
+                let decoded = match std::str::from_utf8(line) {
+                    Ok(line) => decode_line(line),
+                    Err(error) => Err(error.to_string()),
+                };
+                let decoded = match decoded {
+                    Ok(decoded) => decoded,
+                    // Only the last line lacks its newline, and only when its write was torn
+                    Err(message) if number == lines.len() - 1 => {
+                        warn!(
+                            "Dropping incomplete record at line {}: {message}",
+                            number + 1
+                        );
+                        continue;
+                    }
+                    Err(message) => {
+                        return Err(std::io::Error::new(
+                            ErrorKind::InvalidData,
+                            format!("Invalid record at line {}: {message}", number + 1),
+                        ))
+                    }
+                };
+                records += decoded.len();
+                items.insert_all(decoded).map_err(|error| {
+                    std::io::Error::new(ErrorKind::InvalidData, error.to_string())
+                })?;
+            }
+        }
+
+        compact(path, &items)?;
+        info!("Loaded {records} records from {}", path.display());
+
+        Ok(FileClient {
+            items,
+            file: Mutex::new(OpenOptions::new().append(true).create(true).open(path)?),
+        })
+    }
+}
+
+#[async_trait]
+impl DatabaseClient for FileClient {
+    #[tracing::instrument(skip(self))]
+    async fn list(
+        &self,
+        table_name: String,
+        conditions: Vec<(String, AttributeValue)>,
+        limit: Option<i32>,
+        start_key: Option<Item>,
+    ) -> Result<ItemPage, ApiError> {
+        self.items
+            .list(table_name, conditions, limit, start_key)
+            .await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn get(&self, table_name: String, id: String) -> Result<Option<Item>, ApiError> {
+        self.items.get(table_name, id).await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn put(
+        &self,
+        table_name: String,
+        item: Item,
+        condition: Option<Condition>,
+    ) -> Result<(), ApiError> {
+        let mut file = self.file.lock().unwrap();
+        // Resolving the put first rejects an item without id, or failing its condition, before it
+        // reaches the file
+        let items = self.items.transacted(&[TransactWrite::Put {
+            table_name,
+            item,
+            condition,
+        }])?;
+        persist(&mut file, &items)?;
+
+        self.items.insert_all(items)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn update(
+        &self,
+        table_name: String,
+        id: String,
+        update: ItemUpdate,
+    ) -> Result<(), ApiError> {
+        let mut file = self.file.lock().unwrap();
+        let item = self.items.updated(&table_name, &id, &update)?;
+        let items = vec![(table_name, item)];
+        persist(&mut file, &items)?;
+
+        self.items.insert_all(items)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn append(
+        &self,
+        table_name: String,
+        id: String,
+        append: ListAppend,
+    ) -> Result<(), ApiError> {
+        // Holding the file lock while reading keeps concurrent appends from losing each other
+        let mut file = self.file.lock().unwrap();
+        let item = self.items.appended(&table_name, &id, &append)?;
+        let items = vec![(table_name, item)];
+        persist(&mut file, &items)?;
+
+        self.items.insert_all(items)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn transact_write(
+        &self,
+        writes: Vec<TransactWrite>,
+        client_request_token: Option<String>,
+    ) -> Result<(), ApiError> {
+        let mut file = self.file.lock().unwrap();
+        if self.items.is_applied(client_request_token.as_ref()) {
+            return Ok(());
+        }
+        let items = self.items.transacted(&writes)?;
+        persist(&mut file, &items)?;
+
+        self.items.insert_all(items)?;
+        self.items.record_applied(client_request_token);
+        Ok(())
+    }
+}
+
+/// Writes the items as a single line, an array of records when there are several, and waits for
+/// it to reach the disk. A torn write then loses the whole transaction rather than part of it, and
+/// a failed one is cut off so the next write starts on a line of its own.
+fn persist(file: &mut File, items: &[(String, Item)]) -> Result<(), ApiError> {
+    let records = items
+        .iter()
+        .map(|(table_name, item)| encode_record(table_name, item))
+        .collect::<Result<Vec<_>, _>>()?;
+    let line = match records.as_slice() {
+        [record] => record.clone(),
+        records => format!("[{}]", records.join(",")),
+    };
+
+    let persist_error = |error| ApiError::Permanent {
+        message: Some("Failed to persist items".to_string()),
+        source: Box::new(error),
+    };
+    let length = file.metadata().map_err(persist_error)?.len();
+    file.write_all(format!("{line}\n").as_bytes())
+        .and_then(|_| file.sync_data())
+        .map_err(|error| {
+            if let Err(truncate_error) = file.set_len(length) {
+                warn!("Failed to cut off partial record: {truncate_error}");
+            }
+            persist_error(error)
+        })
+}
+
+fn compact(path: &Path, items: &InMemoryClient) -> std::io::Result<()> {
+    let mut temporary = PathBuf::from(path);
+    temporary.set_extension("compacting");
+
+    let mut writer = BufWriter::new(File::create(&temporary)?);
+    for (table, item) in items.snapshot() {
+        let line = encode_record(&table, &item)
+            .map_err(|error| std::io::Error::new(ErrorKind::InvalidData, error.to_string()))?;
+        writeln!(writer, "{line}")?;
+    }
+    writer.flush()?;
+    writer.get_ref().sync_all()?;
+
+    rename(temporary, path)
+}
+
+fn encode_record(table: &str, item: &Item) -> Result<String, ApiError> {
+    let item = item
+        .iter()
+        .map(|(name, value)| encode_value(value).map(|value| (name.clone(), value)))
+        .collect::<Result<Map<String, Value>, String>>()
+        .map_err(|message| ApiError::Permanent {
+            message: Some(format!("Failed to encode item of {table}")),
+            source: message.into(),
+        })?;
+
+    Ok(serde_json::to_string(&Record {
+        table: table.to_string(),
+        item,
+    })
+    .unwrap())
+}
+
+fn decode_line(line: &str) -> Result<Vec<(String, Item)>, String> {
+    if line.trim_start().starts_with('[') {
+        serde_json::from_str::<Vec<Record>>(line)
+            .map_err(|error| error.to_string())?
+            .into_iter()
+            .map(decode_item)
+            .collect()
+    } else {
+        decode_record(line).map(|record| vec![record])
+    }
+}
+
+fn decode_record(line: &str) -> Result<(String, Item), String> {
+    decode_item(serde_json::from_str(line).map_err(|error| error.to_string())?)
+}
+
+fn decode_item(record: Record) -> Result<(String, Item), String> {
+    let item = record
+        .item
+        .into_iter()
+        .map(|(name, value)| decode_value(value).map(|value| (name, value)))
+        .collect::<Result<Item, String>>()?;
+
+    Ok((record.table, item))
+}
+
+fn encode_value(value: &AttributeValue) -> Result<Value, String> {
+    Ok(match value {
+        AttributeValue::S(value) => json!({ "S": value }),
+        AttributeValue::N(value) => json!({ "N": value }),
+        AttributeValue::Bool(value) => json!({ "BOOL": value }),
+        AttributeValue::Null(value) => json!({ "NULL": value }),
+        AttributeValue::B(value) => json!({ "B": STANDARD.encode(value.as_ref()) }),
+        AttributeValue::Ss(values) => json!({ "SS": values }),
+        AttributeValue::Ns(values) => json!({ "NS": values }),
+        AttributeValue::Bs(values) => json!({
+            "BS": values.iter().map(|value| STANDARD.encode(value.as_ref())).collect::<Vec<_>>()
+        }),
+        AttributeValue::L(values) => json!({
+            "L": values.iter().map(encode_value).collect::<Result<Vec<_>, _>>()?
+        }),
+        AttributeValue::M(values) => json!({
+            "M": values
+                .iter()
+                .map(|(name, value)| encode_value(value).map(|value| (name.clone(), value)))
+                .collect::<Result<Map<_, _>, _>>()?
+        }),
+        _ => return Err("unsupported attribute type".to_string()),
+    })
+}
+
+fn decode_value(value: Value) -> Result<AttributeValue, String> {
+    let Value::Object(value) = value else {
+        return Err(format!("expected an attribute object, found {value}"));
+    };
+    let Some((kind, value)) = value.into_iter().next() else {
+        return Err("empty attribute object".to_string());
+    };
+
+    let invalid = || format!("invalid {kind} attribute");
+    Ok(match kind.as_str() {
+        "S" => AttributeValue::S(value.as_str().ok_or_else(invalid)?.to_string()),
+        "N" => AttributeValue::N(value.as_str().ok_or_else(invalid)?.to_string()),
+        "BOOL" => AttributeValue::Bool(value.as_bool().ok_or_else(invalid)?),
+        "NULL" => AttributeValue::Null(value.as_bool().ok_or_else(invalid)?),
+        "B" => AttributeValue::B(decode_blob(&value).ok_or_else(invalid)?),
+        "SS" => AttributeValue::Ss(serde_json::from_value(value).map_err(|_| invalid())?),
+        "NS" => AttributeValue::Ns(serde_json::from_value(value).map_err(|_| invalid())?),
+        "BS" => AttributeValue::Bs(
+            value
+                .as_array()
+                .ok_or_else(invalid)?
+                .iter()
+                .map(|value| decode_blob(value).ok_or_else(invalid))
+                .collect::<Result<_, _>>()?,
+        ),
+        "L" => AttributeValue::L(
+            serde_json::from_value::<Vec<Value>>(value)
+                .map_err(|_| invalid())?
+                .into_iter()
+                .map(decode_value)
+                .collect::<Result<_, _>>()?,
+        ),
+        "M" => AttributeValue::M(
+            serde_json::from_value::<Map<String, Value>>(value)
+                .map_err(|_| invalid())?
+                .into_iter()
+                .map(|(name, value)| decode_value(value).map(|value| (name, value)))
+                .collect::<Result<_, _>>()?,
+        ),
+        _ => return Err(format!("unknown attribute type {kind}")),
+    })
+}
+
+fn decode_blob(value: &Value) -> Option<Blob> {
+    STANDARD.decode(value.as_str()?).ok().map(Blob::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::collections::HashMap;
+    use std::env::temp_dir;
+    use std::fs::{read_to_string, remove_file};
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn should_keep_items_across_restarts() {
+        let path = build_path();
+
+        let client = FileClient::open(&path).unwrap();
+        client
+            .put("Account".to_string(), build_item("a", "first"), None)
+            .await
+            .unwrap();
+        client
+            .put("Account".to_string(), build_item("a", "second"), None)
+            .await
+            .unwrap();
+        drop(client);
+
+        let reopened = FileClient::open(&path).unwrap();
+        let result = reopened.get("Account".to_string(), "a".to_string()).await;
+
+        assert_eq!(Some(build_item("a", "second")), result.unwrap());
+        assert_eq!(1, read_to_string(&path).unwrap().lines().count());
+        remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn should_list_with_same_filters_as_other_backends() {
+        let path = build_path();
+        let client = FileClient::open(&path).unwrap();
+        for (id, name) in [("a", "first"), ("b", "second")] {
+            client
+                .put("Account".to_string(), build_item(id, name), None)
+                .await
+                .unwrap();
+        }
+
+        let result = client
+            .list(
+                "Account".to_string(),
+                vec![("name".to_string(), AttributeValue::S("second".to_string()))],
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(vec![build_item("b", "second")], result.items);
+        remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn should_keep_appended_values_across_restarts() {
+        let path = build_path();
+        let client = FileClient::open(&path).unwrap();
+        client
+            .put("Account".to_string(), build_item("a", "first"), None)
+            .await
+            .unwrap();
+        client
+            .append(
+                "Account".to_string(),
+                "a".to_string(),
+                ListAppend {
+                    attribute: "balances".to_string(),
+                    value: AttributeValue::S("balance".to_string()),
+                    marker_attribute: "balance_months".to_string(),
+                    marker: "2023-02".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        drop(client);
+
+        let reopened = FileClient::open(&path).unwrap();
+        let item = reopened
+            .get("Account".to_string(), "a".to_string())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            Some(&AttributeValue::Ss(vec!["2023-02".to_string()])),
+            item.get("balance_months")
+        );
+        remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn should_keep_transaction_items_across_restarts() {
+        let path = build_path();
+        let client = FileClient::open(&path).unwrap();
+        client
+            .transact_write(
+                vec![
+                    TransactWrite::Put {
+                        table_name: "Account".to_string(),
+                        item: build_item("a", "first"),
+                        condition: None,
+                    },
+                    TransactWrite::Put {
+                        table_name: "Account".to_string(),
+                        item: build_item("b", "second"),
+                        condition: None,
+                    },
+                ],
+                None,
+            )
+            .await
+            .unwrap();
+        drop(client);
+
+        let reopened = FileClient::open(&path).unwrap();
+        let result = reopened
+            .list("Account".to_string(), vec![], None, None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            vec![build_item("a", "first"), build_item("b", "second")],
+            result.items
+        );
+        remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn should_not_persist_item_without_id() {
+        let path = build_path();
+        let client = FileClient::open(&path).unwrap();
+
+        let result = client.put("Account".to_string(), Item::new(), None).await;
+        drop(client);
+
+        assert!(result.is_err());
+        assert!(FileClient::open(&path).is_ok());
+        assert_eq!("", read_to_string(&path).unwrap());
+        remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn should_not_persist_item_failing_condition() {
+        let path = build_path();
+        let client = FileClient::open(&path).unwrap();
+
+        let result = client
+            .put(
+                "Account".to_string(),
+                build_item("a", "first"),
+                Some(Condition::Version {
+                    attribute: "version".to_string(),
+                    expected: 1,
+                }),
+            )
+            .await;
+        drop(client);
+
+        assert!(matches!(result, Err(ApiError::Conflict { .. })));
+        assert_eq!("", read_to_string(&path).unwrap());
+        remove_file(path).unwrap();
+    }
+
+    #[rstest]
+    #[case("{\"table\":\"Account\",\"item\":{\"id\":{\"S\":\"b")]
+    #[case("[{\"table\":\"Account\",\"item\":{\"id\":{\"S\":\"b\"}}},{\"ta")]
+    #[tokio::test]
+    async fn should_drop_incomplete_last_line(#[case] torn: &str) {
+        let path = build_path();
+        let complete = encode_record("Account", &build_item("a", "first")).unwrap();
+        std::fs::write(&path, format!("{complete}\n{torn}")).unwrap();
+
+        let client = FileClient::open(&path).unwrap();
+        let result = client
+            .list("Account".to_string(), vec![], None, None)
+            .await
+            .unwrap();
+
+        assert_eq!(vec![build_item("a", "first")], result.items);
+        assert_eq!(format!("{complete}\n"), read_to_string(&path).unwrap());
+        remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn should_fail_to_open_corrupted_file() {
+        let path = build_path();
+        std::fs::write(
+            &path,
+            "{\"table\":\"Account\",\"item\":{\"id\":{\"X\":1}}}\n",
+        )
+        .unwrap();
+
+        let result = FileClient::open(&path);
+
+        assert_eq!(ErrorKind::InvalidData, result.err().unwrap().kind());
+        remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn should_round_trip_every_attribute_type() {
+        let item = Item::from([
+            ("id".to_string(), AttributeValue::S("a".to_string())),
+            ("number".to_string(), AttributeValue::N("10.5".to_string())),
+            ("flag".to_string(), AttributeValue::Bool(true)),
+            ("empty".to_string(), AttributeValue::Null(true)),
+            ("blob".to_string(), AttributeValue::B(Blob::new("blob"))),
+            (
+                "strings".to_string(),
+                AttributeValue::Ss(vec!["a".to_string(), "b".to_string()]),
+            ),
+            (
+                "numbers".to_string(),
+                AttributeValue::Ns(vec!["1".to_string()]),
+            ),
+            (
+                "blobs".to_string(),
+                AttributeValue::Bs(vec![Blob::new("b")]),
+            ),
+            (
+                "list".to_string(),
+                AttributeValue::L(vec![AttributeValue::S("nested".to_string())]),
+            ),
+            (
+                "map".to_string(),
+                AttributeValue::M(HashMap::from([(
+                    "key".to_string(),
+                    AttributeValue::N("1".to_string()),
+                )])),
+            ),
+        ]);
+
+        let line = encode_record("Account", &item).unwrap();
+
+        assert_eq!(("Account".to_string(), item), decode_record(&line).unwrap());
+    }
+
+    #[test]
+    fn file_client_should_be_thread_safe() {
+        is_thread_safe::<FileClient>();
+    }
+
+    fn is_thread_safe<T: Sized + Send + Sync + Unpin>() {}
+
+    fn build_path() -> PathBuf {
+        temp_dir().join(format!("playground-{}.jsonl", Uuid::new_v4()))
+    }
+
+    fn build_item(id: &str, name: &str) -> Item {
+        Item::from([
+            ("id".to_string(), AttributeValue::S(id.to_string())),
+            ("name".to_string(), AttributeValue::S(name.to_string())),
+        ])
+    }
+}
@@ -0,0 +1,202 @@
+use crate::config::settings::Settings;
+use crate::error::ApiError;
+use crate::model::fx_rate::FxRate;
+use crate::model::CurrencyCode;
+use crate::repository::{decode_items, DatabaseClient, DecodeMode};
+use async_trait::async_trait;
+use aws_sdk_dynamodb::types::AttributeValue;
+#[cfg(test)]
+use mockall::automock;
+use std::fs::File;
+use std::io::{BufReader, ErrorKind};
+use std::path::Path;
+use std::sync::Arc;
+use tracing::info;
+
+static TABLE_NAME: &str = "FxRate";
+static SOURCE_PARAMETER: &str = "source";
+static TARGET_PARAMETER: &str = "target";
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait FxRateProvider: Send + Sync {
+    /// Every known rate converting `source` into `target`, in no particular order.
+    async fn rates(
+        &self,
+        source: CurrencyCode,
+        target: CurrencyCode,
+    ) -> Result<Vec<FxRate>, ApiError>;
+}
+
+/// Reads rates from the `FxRate` table through the configured storage backend.
+pub struct TableFxRateProvider {
+    client: Arc<dyn DatabaseClient>,
+    decode_mode: DecodeMode,
+}
+
+impl TableFxRateProvider {
+    pub fn new(client: Arc<dyn DatabaseClient>, settings: &Settings) -> Self {
+        TableFxRateProvider {
+            client,
+            decode_mode: settings.decode_mode,
+        }
+    }
+}
+
+#[async_trait]
+impl FxRateProvider for TableFxRateProvider {
+    #[tracing::instrument(skip(self))]
+    async fn rates(
+        &self,
+        source: CurrencyCode,
+        target: CurrencyCode,
+    ) -> Result<Vec<FxRate>, ApiError> {
+        let conditions = vec![
+            (
+                SOURCE_PARAMETER.to_string(),
+                AttributeValue::S(source.to_string()),
+            ),
+            (
+                TARGET_PARAMETER.to_string(),
+                AttributeValue::S(target.to_string()),
+            ),
+        ];
+
+        decode_items(
+            self.client
+                .list(TABLE_NAME.to_string(), conditions, None, None)
+                .await?
+                .items,
+            self.decode_mode,
+        )
+    }
+}
+
+/// Serves a fixed set of rates loaded once from a JSON array of `FxRate`.
+pub struct StaticFxRateProvider {
+    rates: Vec<FxRate>,
+}
+
+impl StaticFxRateProvider {
+    pub fn new(rates: Vec<FxRate>) -> Self {
+        StaticFxRateProvider { rates }
+    }
+
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let rates: Vec<FxRate> = serde_json::from_reader(BufReader::new(File::open(path)?))
+            .map_err(|error| std::io::Error::new(ErrorKind::InvalidData, error))?;
+        info!("Loaded {} FX rates from {}", rates.len(), path.display());
+
+        Ok(StaticFxRateProvider::new(rates))
+    }
+}
+
+#[async_trait]
+impl FxRateProvider for StaticFxRateProvider {
+    async fn rates(
+        &self,
+        source: CurrencyCode,
+        target: CurrencyCode,
+    ) -> Result<Vec<FxRate>, ApiError> {
+        Ok(self
+            .rates
+            .iter()
+            .filter(|rate| rate.source == source && rate.target == target)
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::InMemoryClient;
+    use chrono::NaiveDateTime;
+    use rust_decimal_macros::dec;
+    use std::env::temp_dir;
+    use std::fs::{remove_file, write};
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn should_read_rates_of_pair_from_table() {
+        let client = Arc::new(InMemoryClient::default());
+        for rate in [
+            build_rate(CurrencyCode::USD, CurrencyCode::BRL, "01/01/2023 00:00:00"),
+            build_rate(CurrencyCode::EUR, CurrencyCode::BRL, "01/01/2023 00:00:00"),
+            build_rate(CurrencyCode::USD, CurrencyCode::BRL, "01/02/2023 00:00:00"),
+        ] {
+            client
+                .put(TABLE_NAME.to_string(), rate.into(), None)
+                .await
+                .unwrap();
+        }
+        let provider = TableFxRateProvider::new(client, &Settings::default());
+
+        let result = provider
+            .rates(CurrencyCode::USD, CurrencyCode::BRL)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                build_rate(CurrencyCode::USD, CurrencyCode::BRL, "01/01/2023 00:00:00"),
+                build_rate(CurrencyCode::USD, CurrencyCode::BRL, "01/02/2023 00:00:00"),
+            ],
+            result
+        );
+    }
+
+    #[tokio::test]
+    async fn should_load_rates_from_file() {
+        let path = temp_dir().join(format!("fx-rates-{}.json", Uuid::new_v4()));
+        write(
+            &path,
+            r#"[{"source":"USD","target":"BRL","rate":"5.1","valid_from":"2023-01-01T00:00:00Z"}]"#,
+        )
+        .unwrap();
+
+        let provider = StaticFxRateProvider::load(&path).unwrap();
+        let found = provider
+            .rates(CurrencyCode::USD, CurrencyCode::BRL)
+            .await
+            .unwrap();
+        let missing = provider
+            .rates(CurrencyCode::BRL, CurrencyCode::USD)
+            .await
+            .unwrap();
+
+        assert_eq!(dec!(5.1), found[0].rate);
+        assert!(missing.is_empty());
+        remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn should_fail_to_load_malformed_file() {
+        let path = temp_dir().join(format!("fx-rates-{}.json", Uuid::new_v4()));
+        write(&path, "batata").unwrap();
+
+        let result = StaticFxRateProvider::load(&path);
+
+        assert_eq!(ErrorKind::InvalidData, result.err().unwrap().kind());
+        remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn fx_rate_providers_should_be_thread_safe() {
+        is_thread_safe::<TableFxRateProvider>();
+        is_thread_safe::<StaticFxRateProvider>();
+    }
+
+    fn is_thread_safe<T: Sized + Send + Sync + Unpin>() {}
+
+    fn build_rate(source: CurrencyCode, target: CurrencyCode, valid_from: &str) -> FxRate {
+        FxRate {
+            source,
+            target,
+            rate: dec!(5),
+            valid_from: NaiveDateTime::parse_from_str(valid_from, "%d/%m/%Y %H:%M:%S")
+                .unwrap()
+                .and_utc(),
+        }
+    }
+}
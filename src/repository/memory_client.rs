@@ -0,0 +1,703 @@
+use crate::error::ApiError;
+use crate::repository::dynamo_item::{Item, ID_ATTRIBUTE};
+use crate::repository::{
+    apply_append, apply_update, check_condition, matches, Condition, DatabaseClient, ItemPage,
+    ItemUpdate, ListAppend, TransactWrite,
+};
+use async_trait::async_trait;
+use aws_sdk_dynamodb::types::AttributeValue;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::ops::Bound;
+use std::sync::{Mutex, RwLock};
+
+type Tables = HashMap<String, BTreeMap<String, Item>>;
+
+/// How many client request tokens of applied transactions are remembered.
+static MAX_APPLIED_TOKENS: usize = 1000;
+
+/// Keeps every table in memory, ordered by id so pages are stable across calls.
+#[derive(Default)]
+pub struct InMemoryClient {
+    tables: RwLock<Tables>,
+    applied_tokens: Mutex<VecDeque<String>>,
+}
+
+impl InMemoryClient {
+    /// Stores every item at once, or none when any of them has no id.
+    pub(super) fn insert_all(&self, items: Vec<(String, Item)>) -> Result<(), ApiError> {
+        let items = items
+            .into_iter()
+            .map(|(table_name, item)| Ok((item_id(&table_name, &item)?, table_name, item)))
+            .collect::<Result<Vec<_>, ApiError>>()?;
+
+        let mut tables = self.tables.write().unwrap();
+        for (id, table_name, item) in items {
+            tables.entry(table_name).or_default().insert(id, item);
+        }
+
+        Ok(())
+    }
+
+    /// The items `writes` would store, without storing them.
+    pub(super) fn transacted(
+        &self,
+        writes: &[TransactWrite],
+    ) -> Result<Vec<(String, Item)>, ApiError> {
+        Ok(resolve_writes(&self.tables.read().unwrap(), writes)?
+            .into_iter()
+            .map(|(table_name, _, item)| (table_name, item))
+            .collect())
+    }
+
+    /// Whether a transaction with `client_request_token` was already applied.
+    pub(super) fn is_applied(&self, client_request_token: Option<&String>) -> bool {
+        client_request_token
+            .is_some_and(|token| self.applied_tokens.lock().unwrap().contains(token))
+    }
+
+    /// Remembers `client_request_token` as applied, forgetting the oldest tokens past a limit.
+    pub(super) fn record_applied(&self, client_request_token: Option<String>) {
+        let Some(token) = client_request_token else {
+            return;
+        };
+        let mut tokens = self.applied_tokens.lock().unwrap();
+        tokens.push_back(token);
+        if tokens.len() > MAX_APPLIED_TOKENS {
+            tokens.pop_front();
+        }
+    }
+
+    /// The item as it would be after `append`, without storing it.
+    pub(super) fn appended(
+        &self,
+        table_name: &str,
+        id: &str,
+        append: &ListAppend,
+    ) -> Result<Item, ApiError> {
+        let tables = self.tables.read().unwrap();
+        let item = tables.get(table_name).and_then(|table| table.get(id));
+        apply_append(table_name, id, item, append)
+    }
+
+    /// The item as it would be after `update`, without storing it.
+    pub(super) fn updated(
+        &self,
+        table_name: &str,
+        id: &str,
+        update: &ItemUpdate,
+    ) -> Result<Item, ApiError> {
+        let tables = self.tables.read().unwrap();
+        let item = tables.get(table_name).and_then(|table| table.get(id));
+        apply_update(table_name, id, item, update)
+    }
+
+    pub(super) fn snapshot(&self) -> Vec<(String, Item)> {
+        self.tables
+            .read()
+            .unwrap()
+            .iter()
+            .flat_map(|(table_name, table)| {
+                table
+                    .values()
+                    .map(|item| (table_name.clone(), item.clone()))
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl DatabaseClient for InMemoryClient {
+    #[tracing::instrument(skip(self))]
+    async fn list(
+        &self,
+        table_name: String,
+        conditions: Vec<(String, AttributeValue)>,
+        limit: Option<i32>,
+        start_key: Option<Item>,
+    ) -> Result<ItemPage, ApiError> {
+        let tables = self.tables.read().unwrap();
+        let Some(table) = tables.get(&table_name) else {
+            return Ok(ItemPage::default());
+        };
+
+        let start = match start_key.as_ref().and_then(|key| key.get(ID_ATTRIBUTE)) {
+            Some(AttributeValue::S(id)) => Bound::Excluded(id.clone()),
+            _ => Bound::Unbounded,
+        };
+        let mut matching = table
+            .range((start, Bound::Unbounded))
+            .map(|(_, item)| item)
+            .filter(|item| matches(item, &conditions));
+
+        let items: Vec<Item> = match limit {
+            Some(limit) => matching.by_ref().take(limit as usize).cloned().collect(),
+            None => matching.by_ref().cloned().collect(),
+        };
+        let last_evaluated_key = match matching.next() {
+            Some(_) => items.last().map(|item| {
+                item.iter()
+                    .filter(|(name, _)| name.as_str() == ID_ATTRIBUTE)
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect()
+            }),
+            None => None,
+        };
+
+        Ok(ItemPage {
+            items,
+            last_evaluated_key,
+        })
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn get(&self, table_name: String, id: String) -> Result<Option<Item>, ApiError> {
+        Ok(self
+            .tables
+            .read()
+            .unwrap()
+            .get(&table_name)
+            .and_then(|table| table.get(&id))
+            .cloned())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn put(
+        &self,
+        table_name: String,
+        item: Item,
+        condition: Option<Condition>,
+    ) -> Result<(), ApiError> {
+        let mut tables = self.tables.write().unwrap();
+        let writes = [TransactWrite::Put {
+            table_name,
+            item,
+            condition,
+        }];
+        for (table_name, id, item) in resolve_writes(&tables, &writes)? {
+            tables.entry(table_name).or_default().insert(id, item);
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn update(
+        &self,
+        table_name: String,
+        id: String,
+        update: ItemUpdate,
+    ) -> Result<(), ApiError> {
+        let mut tables = self.tables.write().unwrap();
+        let table = tables.entry(table_name.clone()).or_default();
+        let item = apply_update(&table_name, &id, table.get(&id), &update)?;
+        table.insert(id, item);
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn append(
+        &self,
+        table_name: String,
+        id: String,
+        append: ListAppend,
+    ) -> Result<(), ApiError> {
+        let mut tables = self.tables.write().unwrap();
+        let table = tables.entry(table_name.clone()).or_default();
+        let item = apply_append(&table_name, &id, table.get(&id), &append)?;
+        table.insert(id, item);
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn transact_write(
+        &self,
+        writes: Vec<TransactWrite>,
+        client_request_token: Option<String>,
+    ) -> Result<(), ApiError> {
+        let mut tables = self.tables.write().unwrap();
+        if self.is_applied(client_request_token.as_ref()) {
+            return Ok(());
+        }
+        for (table_name, id, item) in resolve_writes(&tables, &writes)? {
+            tables.entry(table_name).or_default().insert(id, item);
+        }
+        self.record_applied(client_request_token);
+
+        Ok(())
+    }
+}
+
+/// Resolves every write against the current tables, rejecting transactions that target an item
+/// twice the way DynamoDB does.
+fn resolve_writes(
+    tables: &Tables,
+    writes: &[TransactWrite],
+) -> Result<Vec<(String, String, Item)>, ApiError> {
+    let mut targets: Vec<(&String, String)> = vec![];
+    let mut items: Vec<(String, String, Item)> = vec![];
+    for write in writes {
+        let (table_name, id, item) = match write {
+            TransactWrite::Put {
+                table_name,
+                item,
+                condition,
+            } => {
+                let id = item_id(table_name, item)?;
+                if let Some(condition) = condition {
+                    let current = tables.get(table_name).and_then(|table| table.get(&id));
+                    check_condition(table_name, &id, current, condition)?;
+                }
+                (table_name, id, Some(item.clone()))
+            }
+            TransactWrite::Append {
+                table_name,
+                id,
+                append,
+                condition,
+            } => {
+                let current = tables.get(table_name).and_then(|table| table.get(id));
+                if let Some(condition) = condition {
+                    check_condition(table_name, id, current, condition)?;
+                }
+                let item = apply_append(table_name, id, current, append)?;
+                (table_name, id.clone(), Some(item))
+            }
+            TransactWrite::Check {
+                table_name,
+                id,
+                condition,
+            } => {
+                let current = tables.get(table_name).and_then(|table| table.get(id));
+                check_condition(table_name, id, current, condition)?;
+                (table_name, id.clone(), None)
+            }
+        };
+        if targets.contains(&(table_name, id.clone())) {
+            return Err(ApiError::Permanent {
+                message: Some(format!("Item {id} of {table_name} targeted more than once")),
+                source: "duplicate transaction item".into(),
+            });
+        }
+        targets.push((table_name, id.clone()));
+        if let Some(item) = item {
+            items.push((table_name.clone(), id, item));
+        }
+    }
+
+    Ok(items)
+}
+
+fn item_id(table_name: &str, item: &Item) -> Result<String, ApiError> {
+    match item.get(ID_ATTRIBUTE) {
+        Some(AttributeValue::S(id)) => Ok(id.clone()),
+        _ => Err(ApiError::Permanent {
+            message: Some(format!("Item of {table_name} has no {ID_ATTRIBUTE}")),
+            source: "missing key attribute".into(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::Condition;
+    use rstest::rstest;
+
+    #[tokio::test]
+    async fn should_get_item_after_put() {
+        let client = InMemoryClient::default();
+
+        client
+            .put("Account".to_string(), build_item("a", "CHECKING"), None)
+            .await
+            .unwrap();
+
+        let result = client.get("Account".to_string(), "a".to_string()).await;
+        assert_eq!(Some(build_item("a", "CHECKING")), result.unwrap());
+        let missing = client.get("Account".to_string(), "b".to_string()).await;
+        assert_eq!(None, missing.unwrap());
+    }
+
+    #[tokio::test]
+    async fn should_put_only_while_condition_holds() {
+        let client = InMemoryClient::default();
+        let version = |expected| {
+            Some(Condition::Version {
+                attribute: "version".to_string(),
+                expected,
+            })
+        };
+
+        client
+            .put(
+                "Account".to_string(),
+                build_item("a", "SAVINGS"),
+                version(0),
+            )
+            .await
+            .unwrap();
+        let stale = client
+            .put(
+                "Account".to_string(),
+                build_item("a", "INVESTMENT"),
+                version(1),
+            )
+            .await;
+
+        assert!(matches!(stale, Err(ApiError::Conflict { .. })));
+        let item = client.get("Account".to_string(), "a".to_string()).await;
+        assert_eq!(Some(build_item("a", "SAVINGS")), item.unwrap());
+    }
+
+    #[tokio::test]
+    async fn should_reject_item_without_id() {
+        let client = InMemoryClient::default();
+
+        let result = client.put("Account".to_string(), Item::new(), None).await;
+
+        assert!(result.is_err());
+    }
+
+    #[rstest]
+    #[case(vec![], vec!["a", "b", "c"])]
+    #[case(vec![("type", "CHECKING")], vec!["a", "c"])]
+    #[case(vec![("type", "CHECKING"), ("id", "c")], vec!["c"])]
+    #[case(vec![("type", "INVESTMENT")], vec![])]
+    #[tokio::test]
+    async fn should_list_items_matching_every_condition(
+        #[case] conditions: Vec<(&str, &str)>,
+        #[case] expected_ids: Vec<&str>,
+    ) {
+        let client = build_client().await;
+        let conditions = conditions
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), AttributeValue::S(value.to_string())))
+            .collect();
+
+        let result = client
+            .list("Account".to_string(), conditions, None, None)
+            .await
+            .unwrap();
+
+        assert_eq!(expected_ids, ids(&result.items));
+        assert_eq!(None, result.last_evaluated_key);
+    }
+
+    #[tokio::test]
+    async fn should_page_through_items_with_last_evaluated_key() {
+        let client = build_client().await;
+
+        let first = client
+            .list("Account".to_string(), vec![], Some(2), None)
+            .await
+            .unwrap();
+        let second = client
+            .list(
+                "Account".to_string(),
+                vec![],
+                Some(2),
+                first.last_evaluated_key.clone(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(vec!["a", "b"], ids(&first.items));
+        assert_eq!(vec!["c"], ids(&second.items));
+        assert_eq!(None, second.last_evaluated_key);
+    }
+
+    #[tokio::test]
+    async fn should_append_to_stored_item_once() {
+        let client = build_client().await;
+        let append = ListAppend {
+            attribute: "balances".to_string(),
+            value: AttributeValue::S("balance".to_string()),
+            marker_attribute: "balance_months".to_string(),
+            marker: "2023-02".to_string(),
+        };
+
+        client
+            .append("Account".to_string(), "a".to_string(), append.clone())
+            .await
+            .unwrap();
+        let repeated = client
+            .append("Account".to_string(), "a".to_string(), append.clone())
+            .await;
+        let missing = client
+            .append("Account".to_string(), "d".to_string(), append)
+            .await;
+
+        let item = client
+            .get("Account".to_string(), "a".to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            Some(&AttributeValue::L(vec![AttributeValue::S(
+                "balance".to_string()
+            )])),
+            item.get("balances")
+        );
+        assert!(matches!(repeated, Err(ApiError::Conflict { .. })));
+        assert!(matches!(missing, Err(ApiError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn should_update_stored_item_only() {
+        let client = build_client().await;
+        let update = ItemUpdate {
+            values: Item::from([("type".to_string(), AttributeValue::S("SAVINGS".to_string()))]),
+            removed: vec![],
+        };
+
+        client
+            .update("Account".to_string(), "a".to_string(), update.clone())
+            .await
+            .unwrap();
+        let missing = client
+            .update("Account".to_string(), "d".to_string(), update)
+            .await;
+
+        let item = client.get("Account".to_string(), "a".to_string()).await;
+        assert_eq!(Some(build_item("a", "SAVINGS")), item.unwrap());
+        assert!(matches!(missing, Err(ApiError::NotFound { .. })));
+        let created = client.get("Account".to_string(), "d".to_string()).await;
+        assert_eq!(None, created.unwrap());
+    }
+
+    #[tokio::test]
+    async fn should_write_every_item_of_transaction() {
+        let client = InMemoryClient::default();
+
+        client
+            .transact_write(
+                vec![
+                    TransactWrite::Put {
+                        table_name: "Account".to_string(),
+                        item: build_item("a", "CHECKING"),
+                        condition: None,
+                    },
+                    TransactWrite::Put {
+                        table_name: "Transaction".to_string(),
+                        item: build_item("a", "PAYMENT"),
+                        condition: None,
+                    },
+                ],
+                None,
+            )
+            .await
+            .unwrap();
+
+        let account = client.get("Account".to_string(), "a".to_string()).await;
+        let transaction = client.get("Transaction".to_string(), "a".to_string()).await;
+        assert_eq!(Some(build_item("a", "CHECKING")), account.unwrap());
+        assert_eq!(Some(build_item("a", "PAYMENT")), transaction.unwrap());
+    }
+
+    #[rstest]
+    #[case(build_item("a", "SAVINGS"))]
+    #[case(Item::new())]
+    #[tokio::test]
+    async fn should_write_nothing_when_transaction_is_invalid(#[case] second: Item) {
+        let client = InMemoryClient::default();
+
+        let result = client
+            .transact_write(
+                vec![
+                    TransactWrite::Put {
+                        table_name: "Account".to_string(),
+                        item: build_item("a", "CHECKING"),
+                        condition: None,
+                    },
+                    TransactWrite::Put {
+                        table_name: "Account".to_string(),
+                        item: second,
+                        condition: None,
+                    },
+                ],
+                None,
+            )
+            .await;
+
+        assert!(result.is_err());
+        let stored = client.get("Account".to_string(), "a".to_string()).await;
+        assert_eq!(None, stored.unwrap());
+    }
+
+    #[tokio::test]
+    async fn should_write_nothing_when_transaction_append_conflicts() {
+        let client = build_client().await;
+        let append = TransactWrite::Append {
+            table_name: "Account".to_string(),
+            id: "a".to_string(),
+            append: ListAppend {
+                attribute: "balances".to_string(),
+                value: AttributeValue::S("balance".to_string()),
+                marker_attribute: "balance_months".to_string(),
+                marker: "2023-02".to_string(),
+            },
+            condition: None,
+        };
+        let put = TransactWrite::Put {
+            table_name: "Transaction".to_string(),
+            item: build_item("t", "TRANSFER"),
+            condition: None,
+        };
+
+        client
+            .transact_write(vec![append.clone()], None)
+            .await
+            .unwrap();
+        let repeated = client.transact_write(vec![put, append], None).await;
+
+        assert!(matches!(repeated, Err(ApiError::Conflict { .. })));
+        let transaction = client.get("Transaction".to_string(), "t".to_string()).await;
+        assert_eq!(None, transaction.unwrap());
+    }
+
+    #[tokio::test]
+    async fn should_write_nothing_when_transaction_condition_fails() {
+        let client = build_client().await;
+        let put = |account_type: &str, expected: u64| TransactWrite::Put {
+            table_name: "Account".to_string(),
+            item: build_item("a", account_type),
+            condition: Some(Condition::Version {
+                attribute: "version".to_string(),
+                expected,
+            }),
+        };
+
+        client
+            .transact_write(vec![put("SAVINGS", 0)], None)
+            .await
+            .unwrap();
+        let stale = client
+            .transact_write(vec![put("INVESTMENT", 1)], None)
+            .await;
+
+        assert!(matches!(stale, Err(ApiError::Conflict { .. })));
+        let item = client.get("Account".to_string(), "a".to_string()).await;
+        assert_eq!(Some(build_item("a", "SAVINGS")), item.unwrap());
+    }
+
+    #[rstest]
+    #[case("a", vec![])]
+    #[case("d", vec![])]
+    #[case("a", vec![TransactWrite::Put { table_name: "Account".to_string(), item: build_item("a", "SAVINGS"), condition: None }])]
+    #[tokio::test]
+    async fn should_write_nothing_when_transaction_check_fails(
+        #[case] id: &str,
+        #[case] writes: Vec<TransactWrite>,
+    ) {
+        let client = build_client().await;
+        let check = TransactWrite::Check {
+            table_name: "Account".to_string(),
+            id: id.to_string(),
+            condition: Condition::NotEqual {
+                attribute: "type".to_string(),
+                value: AttributeValue::S("CHECKING".to_string()),
+            },
+        };
+        let put = TransactWrite::Put {
+            table_name: "Transaction".to_string(),
+            item: build_item("t", "TRANSFER"),
+            condition: None,
+        };
+
+        let result = client
+            .transact_write([vec![check, put], writes].concat(), None)
+            .await;
+
+        assert!(result.is_err());
+        let transaction = client.get("Transaction".to_string(), "t".to_string()).await;
+        assert_eq!(None, transaction.unwrap());
+    }
+
+    #[tokio::test]
+    async fn should_apply_transaction_once_per_client_request_token() {
+        let client = build_client().await;
+        let append = TransactWrite::Append {
+            table_name: "Account".to_string(),
+            id: "a".to_string(),
+            append: ListAppend {
+                attribute: "balances".to_string(),
+                value: AttributeValue::S("balance".to_string()),
+                marker_attribute: "balance_months".to_string(),
+                marker: "2023-02".to_string(),
+            },
+            condition: None,
+        };
+
+        for _ in 0..2 {
+            client
+                .transact_write(vec![append.clone()], Some("token".to_string()))
+                .await
+                .unwrap();
+        }
+        let other = client
+            .transact_write(vec![append], Some("other".to_string()))
+            .await;
+
+        assert!(matches!(other, Err(ApiError::Conflict { .. })));
+        let item = client
+            .get("Account".to_string(), "a".to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            Some(&AttributeValue::L(vec![AttributeValue::S(
+                "balance".to_string()
+            )])),
+            item.get("balances")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_list_nothing_when_table_is_empty() {
+        let client = InMemoryClient::default();
+
+        let result = client
+            .list("Account".to_string(), vec![], None, None)
+            .await
+            .unwrap();
+
+        assert_eq!(ItemPage::default(), result);
+    }
+
+    #[test]
+    fn in_memory_client_should_be_thread_safe() {
+        is_thread_safe::<InMemoryClient>();
+    }
+
+    fn is_thread_safe<T: Sized + Send + Sync + Unpin>() {}
+
+    async fn build_client() -> InMemoryClient {
+        let client = InMemoryClient::default();
+        for (id, account_type) in [("c", "CHECKING"), ("a", "CHECKING"), ("b", "SAVINGS")] {
+            client
+                .put("Account".to_string(), build_item(id, account_type), None)
+                .await
+                .unwrap();
+        }
+        client
+    }
+
+    fn build_item(id: &str, account_type: &str) -> Item {
+        Item::from([
+            ("id".to_string(), AttributeValue::S(id.to_string())),
+            (
+                "type".to_string(),
+                AttributeValue::S(account_type.to_string()),
+            ),
+        ])
+    }
+
+    fn ids(items: &[Item]) -> Vec<&str> {
+        items
+            .iter()
+            .map(|item| item.get("id").unwrap().as_s().unwrap().as_str())
+            .collect()
+    }
+}
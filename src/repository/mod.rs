@@ -1,33 +1,691 @@
 mod account_repository;
 mod dynamo_client;
+mod dynamo_item;
+mod expense_repository;
+mod file_client;
+mod fx_rate_provider;
+mod memory_client;
+mod retry;
+mod transaction_repository;
 
-use crate::error::PermanentError;
-use crate::model::account::Account;
+use crate::config::settings::Settings;
+use crate::error::ApiError;
+use crate::model::page::{Cursor, Page};
+use crate::model::ParseEnumError;
 pub use account_repository::AccountRepository;
 use async_trait::async_trait;
+use aws_config::retry::RetryConfig;
 use aws_config::{from_env, SdkConfig};
 use aws_credential_types::cache::CredentialsCache;
+use aws_sdk_dynamodb::types::AttributeValue;
+use dynamo_client::DynamoDbClient;
+pub use dynamo_item::{DynamoItem, Item};
+pub use expense_repository::ExpenseRepository;
+pub use file_client::FileClient;
+#[cfg(test)]
+pub use fx_rate_provider::MockFxRateProvider;
+pub use fx_rate_provider::{FxRateProvider, StaticFxRateProvider, TableFxRateProvider};
+pub use memory_client::InMemoryClient;
 #[cfg(test)]
 use mockall::automock;
+pub use retry::{RetryPolicy, RetryingClient};
+use rust_decimal::Decimal;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{info, warn};
+pub use transaction_repository::TransactionRepository;
 
 #[derive(Default)]
 pub struct ConfigProvider {}
 
 impl ConfigProvider {
     pub async fn provide(&self) -> SdkConfig {
+        // Retries are handled by `RetryingClient`, which knows which failures are transient
         from_env()
             .credentials_cache(CredentialsCache::lazy())
+            .retry_config(RetryConfig::disabled())
             .load()
             .await
     }
 }
 
-#[async_trait]
 #[cfg_attr(test, automock)]
-pub trait DatabaseClient {
+#[async_trait]
+pub trait DatabaseClient: Send + Sync {
+    /// Lists the items matching every condition, starting after `start_key`. Without a `limit`
+    /// every remaining page is read.
     async fn list(
         &self,
         table_name: String,
-        conditions: Vec<(String, String)>,
-    ) -> Result<Vec<Account>, PermanentError>;
+        conditions: Vec<(String, AttributeValue)>,
+        limit: Option<i32>,
+        start_key: Option<Item>,
+    ) -> Result<ItemPage, ApiError>;
+
+    async fn get(&self, table_name: String, id: String) -> Result<Option<Item>, ApiError>;
+
+    /// Stores `item`, replacing the stored one. Fails with `Conflict` when `condition` does not
+    /// hold for the stored item.
+    async fn put(
+        &self,
+        table_name: String,
+        item: Item,
+        condition: Option<Condition>,
+    ) -> Result<(), ApiError>;
+
+    /// Changes some attributes of an existing item, leaving the others untouched. Fails with
+    /// `NotFound` when the item does not exist.
+    async fn update(
+        &self,
+        table_name: String,
+        id: String,
+        update: ItemUpdate,
+    ) -> Result<(), ApiError>;
+
+    /// Appends to a list attribute of an existing item. Fails with `NotFound` when the item does
+    /// not exist and with `Conflict` when its marker was already recorded, so the same entry is
+    /// never appended twice.
+    async fn append(
+        &self,
+        table_name: String,
+        id: String,
+        append: ListAppend,
+    ) -> Result<(), ApiError>;
+
+    /// Applies every write or none of them. A transaction may target each item only once.
+    /// Repeating a transaction with the same `client_request_token`, at most 36 characters,
+    /// applies it only once.
+    async fn transact_write(
+        &self,
+        writes: Vec<TransactWrite>,
+        client_request_token: Option<String>,
+    ) -> Result<(), ApiError>;
+}
+
+#[derive(Default, PartialEq, Debug)]
+pub struct ItemPage {
+    pub items: Vec<Item>,
+    pub last_evaluated_key: Option<Item>,
+}
+
+/// Sets every attribute in `values` and removes the `removed` ones.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ItemUpdate {
+    pub values: Item,
+    pub removed: Vec<String>,
+}
+
+/// Adds `value` to the `attribute` list and `marker` to the `marker_attribute` string set.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ListAppend {
+    pub attribute: String,
+    pub value: AttributeValue,
+    pub marker_attribute: String,
+    pub marker: String,
+}
+
+/// A single write of a `transact_write`.
+#[derive(PartialEq, Debug, Clone)]
+pub enum TransactWrite {
+    /// Stores `item`, replacing the stored one only when it meets `condition`
+    Put {
+        table_name: String,
+        item: Item,
+        condition: Option<Condition>,
+    },
+    /// Same as `DatabaseClient::append`, failing the whole transaction when the append fails or
+    /// the item does not meet `condition`
+    Append {
+        table_name: String,
+        id: String,
+        append: ListAppend,
+        condition: Option<Condition>,
+    },
+    /// Writes nothing, failing the whole transaction when the item does not meet `condition`
+    Check {
+        table_name: String,
+        id: String,
+        condition: Condition,
+    },
+}
+
+/// What the stored item must meet for a write to apply. A failed condition fails the write, or
+/// the whole transaction, with `Conflict`.
+#[derive(PartialEq, Debug, Clone)]
+pub enum Condition {
+    /// The number in `attribute` is `expected`, a missing item or attribute counting as 0
+    Version { attribute: String, expected: u64 },
+    /// The item exists and its `attribute` is not `value`
+    NotEqual {
+        attribute: String,
+        value: AttributeValue,
+    },
+}
+
+#[derive(PartialEq, Debug, Copy, Clone, Default)]
+pub enum StorageBackend {
+    #[default]
+    DynamoDb,
+    Memory,
+    File,
+}
+
+/// Where FX rates are read from: the `FxRate` table of the storage backend or a static JSON file.
+#[derive(PartialEq, Debug, Copy, Clone, Default)]
+pub enum FxRateSource {
+    #[default]
+    Table,
+    File,
+}
+
+/// Defines how listings react to items that cannot be decoded: `Strict` fails the whole request
+/// while `Lenient` logs and skips the malformed item.
+#[derive(PartialEq, Debug, Copy, Clone, Default)]
+pub enum DecodeMode {
+    Strict,
+    #[default]
+    Lenient,
+}
+
+impl Display for DecodeMode {
+    fn fmt(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        match self {
+            DecodeMode::Strict => write!(formatter, "STRICT"),
+            DecodeMode::Lenient => write!(formatter, "LENIENT"),
+        }
+    }
+}
+
+impl Display for StorageBackend {
+    fn fmt(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        match self {
+            StorageBackend::DynamoDb => write!(formatter, "DYNAMODB"),
+            StorageBackend::Memory => write!(formatter, "MEMORY"),
+            StorageBackend::File => write!(formatter, "FILE"),
+        }
+    }
+}
+
+impl FromStr for StorageBackend {
+    type Err = ParseEnumError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "DYNAMODB" => Ok(StorageBackend::DynamoDb),
+            "MEMORY" => Ok(StorageBackend::Memory),
+            "FILE" => Ok(StorageBackend::File),
+            _ => Err(ParseEnumError {
+                message: format!("Invalid StorageBackend {value}"),
+            }),
+        }
+    }
+}
+
+impl Display for FxRateSource {
+    fn fmt(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        match self {
+            FxRateSource::Table => write!(formatter, "TABLE"),
+            FxRateSource::File => write!(formatter, "FILE"),
+        }
+    }
+}
+
+impl FromStr for FxRateSource {
+    type Err = ParseEnumError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "TABLE" => Ok(FxRateSource::Table),
+            "FILE" => Ok(FxRateSource::File),
+            _ => Err(ParseEnumError {
+                message: format!("Invalid FxRateSource {value}"),
+            }),
+        }
+    }
+}
+
+impl FromStr for DecodeMode {
+    type Err = ParseEnumError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "STRICT" => Ok(DecodeMode::Strict),
+            "LENIENT" => Ok(DecodeMode::Lenient),
+            _ => Err(ParseEnumError {
+                message: format!("Invalid DecodeMode {value}"),
+            }),
+        }
+    }
+}
+
+pub async fn build_database_client(
+    settings: &Settings,
+) -> std::io::Result<Arc<dyn DatabaseClient>> {
+    info!("Using {} storage backend", settings.storage_backend);
+    Ok(match settings.storage_backend {
+        StorageBackend::DynamoDb => {
+            let config = ConfigProvider::default().provide().await;
+            Arc::new(RetryingClient::new(
+                Arc::new(DynamoDbClient::new(&config, settings)),
+                settings.retry_policy.clone(),
+            ))
+        }
+        StorageBackend::Memory => Arc::new(InMemoryClient::default()),
+        StorageBackend::File => Arc::new(FileClient::open(&settings.storage_path)?),
+    })
+}
+
+pub fn build_fx_rate_provider(
+    client: Arc<dyn DatabaseClient>,
+    settings: &Settings,
+) -> std::io::Result<Arc<dyn FxRateProvider>> {
+    info!("Using {} FX rate source", settings.fx_rate_source);
+    Ok(match settings.fx_rate_source {
+        FxRateSource::Table => Arc::new(TableFxRateProvider::new(client, settings)),
+        FxRateSource::File => Arc::new(StaticFxRateProvider::load(&settings.fx_rate_path)?),
+    })
+}
+
+/// Applies `update` the way the conditional update sent to DynamoDB does, for the local backends.
+fn apply_update(
+    table_name: &str,
+    id: &str,
+    item: Option<&Item>,
+    update: &ItemUpdate,
+) -> Result<Item, ApiError> {
+    let Some(item) = item else {
+        return Err(ApiError::NotFound {
+            message: format!("Item {id} of {table_name} does not exist"),
+        });
+    };
+
+    let mut item = item.clone();
+    for attribute in &update.removed {
+        item.remove(attribute);
+    }
+    item.extend(update.values.clone());
+    Ok(item)
+}
+
+/// Applies `append` the way the conditional update sent to DynamoDB does, for the local backends.
+fn apply_append(
+    table_name: &str,
+    id: &str,
+    item: Option<&Item>,
+    append: &ListAppend,
+) -> Result<Item, ApiError> {
+    let Some(item) = item else {
+        return Err(ApiError::NotFound {
+            message: format!("Item {id} of {table_name} does not exist"),
+        });
+    };
+
+    let mut markers = match item.get(&append.marker_attribute) {
+        Some(AttributeValue::Ss(markers)) => markers.clone(),
+        _ => vec![],
+    };
+    if markers.contains(&append.marker) {
+        return Err(ApiError::Conflict {
+            message: format!(
+                "{} already recorded in {} of {id}",
+                append.marker, append.attribute
+            ),
+        });
+    }
+    markers.push(append.marker.clone());
+
+    let mut values = match item.get(&append.attribute) {
+        Some(AttributeValue::L(values)) => values.clone(),
+        _ => vec![],
+    };
+    values.push(append.value.clone());
+
+    let mut item = item.clone();
+    item.insert(append.attribute.clone(), AttributeValue::L(values));
+    item.insert(append.marker_attribute.clone(), AttributeValue::Ss(markers));
+    Ok(item)
+}
+
+/// Checks `condition` the way the condition expression sent to DynamoDB does, for the local
+/// backends.
+fn check_condition(
+    table_name: &str,
+    id: &str,
+    item: Option<&Item>,
+    condition: &Condition,
+) -> Result<(), ApiError> {
+    match condition {
+        Condition::Version {
+            attribute,
+            expected,
+        } => {
+            let version = match item.and_then(|item| item.get(attribute)) {
+                Some(AttributeValue::N(version)) => version.parse::<u64>().ok(),
+                Some(_) => None,
+                None => Some(0),
+            };
+            if version != Some(*expected) {
+                return Err(ApiError::Conflict {
+                    message: format!(
+                        "Item {id} of {table_name} is no longer at version {expected}"
+                    ),
+                });
+            }
+        }
+        Condition::NotEqual { attribute, value } => {
+            let Some(item) = item else {
+                return Err(ApiError::Conflict {
+                    message: format!("Item {id} of {table_name} does not exist"),
+                });
+            };
+            if item.get(attribute) == Some(value) {
+                return Err(ApiError::Conflict {
+                    message: format!("{attribute} of item {id} of {table_name} is {value:?}"),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Equality filter shared by the local backends, mirroring the `#attr = :attr` expressions sent
+/// to DynamoDB: numbers compare by value and every condition must hold.
+fn matches(item: &Item, conditions: &[(String, AttributeValue)]) -> bool {
+    conditions.iter().all(
+        |(attribute_name, expected)| match (item.get(attribute_name), expected) {
+            (Some(AttributeValue::N(actual)), AttributeValue::N(expected)) => {
+                match (Decimal::from_str(actual), Decimal::from_str(expected)) {
+                    (Ok(actual), Ok(expected)) => actual == expected,
+                    _ => actual == expected,
+                }
+            }
+            (Some(actual), expected) => actual == expected,
+            (None, _) => false,
+        },
+    )
+}
+
+pub fn decode_items<T: DynamoItem>(items: Vec<Item>, mode: DecodeMode) -> Result<Vec<T>, ApiError> {
+    let mut decoded = Vec::with_capacity(items.len());
+    for item in items {
+        match T::try_from(item) {
+            Ok(value) => decoded.push(value),
+            Err(error) if mode == DecodeMode::Lenient => {
+                warn!(
+                    attribute = error.attribute.as_str(),
+                    item_id = error.item_id.as_deref(),
+                    "Skipping malformed item: {error}"
+                );
+            }
+            Err(error) => return Err(ApiError::from(error)),
+        }
+    }
+
+    Ok(decoded)
+}
+
+pub fn decode_page<T: DynamoItem>(page: ItemPage, mode: DecodeMode) -> Result<Page<T>, ApiError> {
+    Ok(Page {
+        items: decode_items(page.items, mode)?,
+        next_cursor: page
+            .last_evaluated_key
+            .map(|key| Cursor::from(key).to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::account::Account;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(DecodeMode::Strict, "STRICT")]
+    #[case(DecodeMode::Lenient, "LENIENT")]
+    fn should_round_trip_decode_mode(#[case] mode: DecodeMode, #[case] mode_string: &str) {
+        assert_eq!(mode_string, mode.to_string());
+        assert_eq!(mode, DecodeMode::from_str(mode_string).unwrap());
+    }
+
+    #[rstest]
+    #[case(StorageBackend::DynamoDb, "DYNAMODB")]
+    #[case(StorageBackend::Memory, "MEMORY")]
+    #[case(StorageBackend::File, "FILE")]
+    fn should_round_trip_storage_backend(
+        #[case] backend: StorageBackend,
+        #[case] backend_string: &str,
+    ) {
+        assert_eq!(backend_string, backend.to_string());
+        assert_eq!(backend, StorageBackend::from_str(backend_string).unwrap());
+    }
+
+    #[rstest]
+    #[case(FxRateSource::Table, "TABLE")]
+    #[case(FxRateSource::File, "FILE")]
+    fn should_round_trip_fx_rate_source(#[case] source: FxRateSource, #[case] source_string: &str) {
+        assert_eq!(source_string, source.to_string());
+        assert_eq!(source, FxRateSource::from_str(source_string).unwrap());
+    }
+
+    #[test]
+    fn should_return_err_when_storage_backend_does_not_exist() {
+        assert!(StorageBackend::from_str("batata").is_err());
+    }
+
+    #[rstest]
+    #[case(vec![], true)]
+    #[case(vec![("id", AttributeValue::S("first".to_string()))], true)]
+    #[case(vec![("year", AttributeValue::N("2023.0".to_string()))], true)]
+    #[case(vec![("year", AttributeValue::N("2024".to_string()))], false)]
+    #[case(vec![("id", AttributeValue::S("second".to_string()))], false)]
+    #[case(vec![("name", AttributeValue::S("first".to_string()))], false)]
+    fn should_match_item_against_every_condition(
+        #[case] conditions: Vec<(&str, AttributeValue)>,
+        #[case] expected: bool,
+    ) {
+        let mut item = build_malformed_item("first");
+        item.insert("year".to_string(), AttributeValue::N("2023".to_string()));
+        let conditions: Vec<(String, AttributeValue)> = conditions
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+
+        assert_eq!(expected, matches(&item, &conditions));
+    }
+
+    #[test]
+    fn should_return_err_when_decode_mode_does_not_exist() {
+        assert!(DecodeMode::from_str("batata").is_err());
+    }
+
+    #[test]
+    fn should_skip_malformed_items_when_lenient() {
+        let items = vec![
+            build_malformed_item("first"),
+            build_malformed_item("second"),
+        ];
+
+        let result = decode_items::<Account>(items, DecodeMode::Lenient);
+
+        assert!(result.unwrap().is_empty());
+    }
+
+    #[test]
+    fn should_fail_on_malformed_item_when_strict() {
+        let items = vec![build_malformed_item("first")];
+
+        let result = decode_items::<Account>(items, DecodeMode::Strict);
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("attribute name of item first"));
+    }
+
+    #[test]
+    fn should_encode_last_evaluated_key_as_next_cursor() {
+        let page = ItemPage {
+            items: vec![],
+            last_evaluated_key: Some(build_malformed_item("last")),
+        };
+
+        let result = decode_page::<Account>(page, DecodeMode::Strict).unwrap();
+
+        let cursor = Cursor::from_str(result.next_cursor.unwrap().as_str()).unwrap();
+        assert_eq!(build_malformed_item("last"), Item::from(cursor));
+    }
+
+    #[test]
+    fn should_append_value_and_record_marker() {
+        let item = build_malformed_item("first");
+
+        let appended = apply_append("Account", "first", Some(&item), &build_append("2023-02"));
+        let result = apply_append(
+            "Account",
+            "first",
+            Some(&appended.unwrap()),
+            &build_append("2023-03"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            Some(&AttributeValue::L(vec![
+                AttributeValue::S("2023-02".to_string()),
+                AttributeValue::S("2023-03".to_string())
+            ])),
+            result.get("values")
+        );
+        assert_eq!(
+            Some(&AttributeValue::Ss(vec![
+                "2023-02".to_string(),
+                "2023-03".to_string()
+            ])),
+            result.get("markers")
+        );
+    }
+
+    #[test]
+    fn should_reject_append_of_recorded_marker() {
+        let item = apply_append(
+            "Account",
+            "first",
+            Some(&build_malformed_item("first")),
+            &build_append("2023-02"),
+        )
+        .unwrap();
+
+        let result = apply_append("Account", "first", Some(&item), &build_append("2023-02"));
+
+        assert!(matches!(result, Err(ApiError::Conflict { .. })));
+    }
+
+    #[test]
+    fn should_reject_append_to_missing_item() {
+        let result = apply_append("Account", "first", None, &build_append("2023-02"));
+
+        assert!(matches!(result, Err(ApiError::NotFound { .. })));
+    }
+
+    #[test]
+    fn should_update_only_given_attributes() {
+        let mut item = build_malformed_item("first");
+        item.insert("name".to_string(), AttributeValue::S("old".to_string()));
+        item.insert("closed".to_string(), AttributeValue::Bool(true));
+        item.insert("kept".to_string(), AttributeValue::Bool(true));
+        let update = ItemUpdate {
+            values: Item::from([("name".to_string(), AttributeValue::S("new".to_string()))]),
+            removed: vec!["closed".to_string()],
+        };
+
+        let result = apply_update("Account", "first", Some(&item), &update).unwrap();
+
+        let mut expected = build_malformed_item("first");
+        expected.insert("name".to_string(), AttributeValue::S("new".to_string()));
+        expected.insert("kept".to_string(), AttributeValue::Bool(true));
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn should_reject_update_of_missing_item() {
+        let update = ItemUpdate {
+            values: Item::new(),
+            removed: vec![],
+        };
+
+        let result = apply_update("Account", "first", None, &update);
+
+        assert!(matches!(result, Err(ApiError::NotFound { .. })));
+    }
+
+    #[rstest]
+    #[case(None, 0, true)]
+    #[case(None, 1, false)]
+    #[case(Some("2"), 2, true)]
+    #[case(Some("2"), 1, false)]
+    #[case(Some("2"), 0, false)]
+    fn should_check_version_condition(
+        #[case] version: Option<&str>,
+        #[case] expected: u64,
+        #[case] accepted: bool,
+    ) {
+        let mut item = build_malformed_item("first");
+        if let Some(version) = version {
+            item.insert(
+                "version".to_string(),
+                AttributeValue::N(version.to_string()),
+            );
+        }
+        let condition = Condition::Version {
+            attribute: "version".to_string(),
+            expected,
+        };
+
+        let result = check_condition("Expense", "first", Some(&item), &condition);
+
+        assert_eq!(accepted, result.is_ok());
+        assert_eq!(
+            expected == 0,
+            check_condition("Expense", "first", None, &condition).is_ok()
+        );
+    }
+
+    #[rstest]
+    #[case(Some("OPEN"), true)]
+    #[case(None, true)]
+    #[case(Some("CLOSED"), false)]
+    fn should_check_not_equal_condition(#[case] status: Option<&str>, #[case] accepted: bool) {
+        let mut item = build_malformed_item("first");
+        if let Some(status) = status {
+            item.insert("status".to_string(), AttributeValue::S(status.to_string()));
+        }
+        let condition = Condition::NotEqual {
+            attribute: "status".to_string(),
+            value: AttributeValue::S("CLOSED".to_string()),
+        };
+
+        let result = check_condition("Account", "first", Some(&item), &condition);
+
+        assert_eq!(accepted, result.is_ok());
+        assert!(matches!(
+            check_condition("Account", "first", None, &condition),
+            Err(ApiError::Conflict { .. })
+        ));
+    }
+
+    fn build_append(marker: &str) -> ListAppend {
+        ListAppend {
+            attribute: "values".to_string(),
+            value: AttributeValue::S(marker.to_string()),
+            marker_attribute: "markers".to_string(),
+            marker: marker.to_string(),
+        }
+    }
+
+    fn build_malformed_item(id: &str) -> Item {
+        let mut item = Item::new();
+        item.insert("id".to_string(), AttributeValue::S(id.to_string()));
+        item
+    }
 }
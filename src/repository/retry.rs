@@ -0,0 +1,418 @@
+use crate::error::ApiError;
+use crate::repository::{
+    Condition, DatabaseClient, Item, ItemPage, ItemUpdate, ListAppend, TransactWrite,
+};
+use async_trait::async_trait;
+use aws_sdk_dynamodb::types::AttributeValue;
+use rand::Rng;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{sleep, timeout, Instant};
+use tracing::{debug, warn};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Budget for a whole operation, shared by every attempt and the waits between them
+    pub deadline: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(50),
+            max_delay: Duration::from_secs(1),
+            deadline: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff with full jitter: a random wait up to `base_delay * 2^attempt`,
+    /// capped at `max_delay`.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+
+        ceiling.mul_f64(rand::thread_rng().gen_range(0.0..=1.0))
+    }
+}
+
+/// Retries transient failures of the wrapped client following a `RetryPolicy`. Appends and
+/// transactions without a client request token are attempted once, since repeating one whose
+/// response was lost would apply it twice.
+pub struct RetryingClient {
+    client: Arc<dyn DatabaseClient>,
+    policy: RetryPolicy,
+}
+
+impl RetryingClient {
+    pub fn new(client: Arc<dyn DatabaseClient>, policy: RetryPolicy) -> Self {
+        RetryingClient { client, policy }
+    }
+
+    async fn retry<T, F, Fut>(&self, operation: &str, call: F) -> Result<T, ApiError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, ApiError>>,
+    {
+        self.attempt(operation, self.policy.max_attempts, call)
+            .await
+    }
+
+    /// Makes a single attempt within the deadline, for operations that are not idempotent.
+    async fn once<T, F, Fut>(&self, operation: &str, call: F) -> Result<T, ApiError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, ApiError>>,
+    {
+        self.attempt(operation, 1, call).await
+    }
+
+    async fn attempt<T, F, Fut>(
+        &self,
+        operation: &str,
+        max_attempts: u32,
+        mut call: F,
+    ) -> Result<T, ApiError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, ApiError>>,
+    {
+        let deadline = Instant::now() + self.policy.deadline;
+        let mut attempt = 1;
+        loop {
+            debug!(operation, attempt, "Starting attempt");
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let error = match timeout(remaining, call()).await {
+                Ok(Ok(value)) => return Ok(value),
+                Ok(Err(error)) if error.is_retryable() => error,
+                Ok(Err(error)) => return Err(error),
+                Err(elapsed) => {
+                    warn!(operation, attempt, "Deadline exceeded");
+                    return Err(ApiError::Transient {
+                        message: Some(format!("{operation} exceeded its deadline")),
+                        source: Box::new(elapsed),
+                    });
+                }
+            };
+
+            let delay = self.policy.backoff(attempt);
+            if attempt >= max_attempts || Instant::now() + delay >= deadline {
+                warn!(
+                    operation,
+                    attempt, "Giving up after transient failure: {error}"
+                );
+                return Err(error);
+            }
+
+            warn!(
+                operation,
+                attempt,
+                delay_ms = delay.as_millis() as u64,
+                "Retrying after transient failure: {error}"
+            );
+            sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+#[async_trait]
+impl DatabaseClient for RetryingClient {
+    async fn list(
+        &self,
+        table_name: String,
+        conditions: Vec<(String, AttributeValue)>,
+        limit: Option<i32>,
+        start_key: Option<Item>,
+    ) -> Result<ItemPage, ApiError> {
+        self.retry("list", || {
+            self.client.list(
+                table_name.clone(),
+                conditions.clone(),
+                limit,
+                start_key.clone(),
+            )
+        })
+        .await
+    }
+
+    async fn get(&self, table_name: String, id: String) -> Result<Option<Item>, ApiError> {
+        self.retry("get", || self.client.get(table_name.clone(), id.clone()))
+            .await
+    }
+
+    async fn put(
+        &self,
+        table_name: String,
+        item: Item,
+        condition: Option<Condition>,
+    ) -> Result<(), ApiError> {
+        self.retry("put", || {
+            self.client
+                .put(table_name.clone(), item.clone(), condition.clone())
+        })
+        .await
+    }
+
+    async fn update(
+        &self,
+        table_name: String,
+        id: String,
+        update: ItemUpdate,
+    ) -> Result<(), ApiError> {
+        self.retry("update", || {
+            self.client
+                .update(table_name.clone(), id.clone(), update.clone())
+        })
+        .await
+    }
+
+    async fn append(
+        &self,
+        table_name: String,
+        id: String,
+        append: ListAppend,
+    ) -> Result<(), ApiError> {
+        // A repeated append fails with `Conflict` when the first one was applied
+        self.once("append", || {
+            self.client
+                .append(table_name.clone(), id.clone(), append.clone())
+        })
+        .await
+    }
+
+    async fn transact_write(
+        &self,
+        writes: Vec<TransactWrite>,
+        client_request_token: Option<String>,
+    ) -> Result<(), ApiError> {
+        let call = || {
+            self.client
+                .transact_write(writes.clone(), client_request_token.clone())
+        };
+        match client_request_token {
+            Some(_) => self.retry("transact_write", call).await,
+            None => self.once("transact_write", call).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::MockDatabaseClient;
+    use rstest::rstest;
+    use std::fmt::{Display, Formatter};
+
+    #[tokio::test]
+    async fn should_retry_transient_failures_until_success() {
+        let mut client = MockDatabaseClient::new();
+        let mut failures = 2;
+        client.expect_get().times(3).returning(move |_, _| {
+            if failures > 0 {
+                failures -= 1;
+                Err(transient_error())
+            } else {
+                Ok(None)
+            }
+        });
+
+        let result = build_client(client, 3)
+            .get("Account".to_string(), "id".to_string())
+            .await;
+
+        assert_eq!(None, result.unwrap());
+    }
+
+    #[tokio::test]
+    async fn should_give_up_after_max_attempts() {
+        let mut client = MockDatabaseClient::new();
+        client
+            .expect_put()
+            .times(2)
+            .returning(|_, _, _| Err(transient_error()));
+
+        let result = build_client(client, 2)
+            .put("Account".to_string(), Item::new(), None)
+            .await;
+
+        assert!(result.unwrap_err().is_retryable());
+    }
+
+    #[tokio::test]
+    async fn should_not_retry_append() {
+        let mut client = MockDatabaseClient::new();
+        client
+            .expect_append()
+            .times(1)
+            .returning(|_, _, _| Err(transient_error()));
+
+        let result = build_client(client, 3)
+            .append(
+                "Account".to_string(),
+                "id".to_string(),
+                ListAppend {
+                    attribute: "balances".to_string(),
+                    value: AttributeValue::S("balance".to_string()),
+                    marker_attribute: "balance_months".to_string(),
+                    marker: "2023-02".to_string(),
+                },
+            )
+            .await;
+
+        assert!(result.unwrap_err().is_retryable());
+    }
+
+    #[rstest]
+    #[case(None, 1)]
+    #[case(Some("token"), 3)]
+    #[tokio::test]
+    async fn should_retry_transaction_only_with_client_request_token(
+        #[case] client_request_token: Option<&str>,
+        #[case] attempts: usize,
+    ) {
+        let mut client = MockDatabaseClient::new();
+        client
+            .expect_transact_write()
+            .times(attempts)
+            .returning(|_, _| Err(transient_error()));
+
+        let result = build_client(client, 3)
+            .transact_write(vec![], client_request_token.map(String::from))
+            .await;
+
+        assert!(result.unwrap_err().is_retryable());
+    }
+
+    #[tokio::test]
+    async fn should_not_retry_permanent_failures() {
+        let mut client = MockDatabaseClient::new();
+        client.expect_get().times(1).returning(|_, _| {
+            Err(ApiError::Permanent {
+                source: Box::new(TestError {}),
+                message: None,
+            })
+        });
+
+        let result = build_client(client, 3)
+            .get("Account".to_string(), "id".to_string())
+            .await;
+
+        assert!(matches!(result, Err(ApiError::Permanent { .. })));
+    }
+
+    #[tokio::test]
+    async fn should_fail_when_deadline_is_exceeded() {
+        let client = RetryingClient::new(
+            Arc::new(SlowClient {}),
+            RetryPolicy {
+                deadline: Duration::from_millis(10),
+                ..RetryPolicy::default()
+            },
+        );
+
+        let result = client.get("Account".to_string(), "id".to_string()).await;
+
+        assert!(result.unwrap_err().to_string().contains("deadline"));
+    }
+
+    #[test]
+    fn should_cap_backoff_at_max_delay() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(300),
+            ..RetryPolicy::default()
+        };
+
+        for attempt in 1..10 {
+            assert!(policy.backoff(attempt) <= Duration::from_millis(300));
+        }
+        assert!(policy.backoff(1) <= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn retrying_client_should_be_thread_safe() {
+        is_thread_safe::<RetryingClient>();
+    }
+
+    fn is_thread_safe<T: Sized + Send + Sync + Unpin>() {}
+
+    fn build_client(client: MockDatabaseClient, max_attempts: u32) -> RetryingClient {
+        RetryingClient::new(
+            Arc::new(client),
+            RetryPolicy {
+                max_attempts,
+                base_delay: Duration::from_millis(1),
+                max_delay: Duration::from_millis(1),
+                deadline: Duration::from_secs(1),
+            },
+        )
+    }
+
+    fn transient_error() -> ApiError {
+        ApiError::Transient {
+            source: Box::new(TestError {}),
+            message: Some("throttled".to_string()),
+        }
+    }
+
+    /// Answers every call successfully, only `get` after a second.
+    struct SlowClient {}
+
+    #[async_trait]
+    impl DatabaseClient for SlowClient {
+        async fn list(
+            &self,
+            _: String,
+            _: Vec<(String, AttributeValue)>,
+            _: Option<i32>,
+            _: Option<Item>,
+        ) -> Result<ItemPage, ApiError> {
+            Ok(ItemPage::default())
+        }
+
+        async fn get(&self, _: String, _: String) -> Result<Option<Item>, ApiError> {
+            sleep(Duration::from_secs(1)).await;
+            Ok(None)
+        }
+
+        async fn put(&self, _: String, _: Item, _: Option<Condition>) -> Result<(), ApiError> {
+            Ok(())
+        }
+
+        async fn update(&self, _: String, _: String, _: ItemUpdate) -> Result<(), ApiError> {
+            Ok(())
+        }
+
+        async fn append(&self, _: String, _: String, _: ListAppend) -> Result<(), ApiError> {
+            Ok(())
+        }
+
+        async fn transact_write(
+            &self,
+            _: Vec<TransactWrite>,
+            _: Option<String>,
+        ) -> Result<(), ApiError> {
+            Ok(())
+        }
+    }
+
+    #[derive(Debug)]
+    struct TestError {}
+
+    impl Display for TestError {
+        fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(formatter, "error")
+        }
+    }
+
+    impl std::error::Error for TestError {}
+}
@@ -0,0 +1,129 @@
+use crate::config::settings::Settings;
+use crate::error::ApiError;
+use crate::model::transaction::{Transaction, TransactionFilter};
+use crate::repository::{decode_items, DatabaseClient, DecodeMode, TransactWrite};
+use aws_sdk_dynamodb::types::AttributeValue;
+use std::sync::Arc;
+
+static TABLE_NAME: &str = "Transaction";
+static SOURCE_ID_PARAMETER: &str = "source_id";
+static DESTINATION_ID_PARAMETER: &str = "destination_id";
+static EXPENSE_ID_PARAMETER: &str = "expense_id";
+static OPERATION_PARAMETER: &str = "operation";
+static TRANSACTION_TYPE_PARAMETER: &str = "type";
+static TRANSACTION_STATUS_PARAMETER: &str = "status";
+static MONTH_PARAMETER: &str = "month";
+static YEAR_PARAMETER: &str = "year";
+
+pub struct TransactionRepository {
+    client: Arc<dyn DatabaseClient>,
+    decode_mode: DecodeMode,
+}
+
+impl TransactionRepository {
+    pub fn new(client: Arc<dyn DatabaseClient>, settings: &Settings) -> Self {
+        TransactionRepository {
+            client,
+            decode_mode: settings.decode_mode,
+        }
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn list_transactions(
+        &self,
+        filter: TransactionFilter,
+    ) -> Result<Vec<Transaction>, ApiError> {
+        let query_attributes = vec![
+            (
+                SOURCE_ID_PARAMETER.to_string(),
+                filter.source_id.map(AttributeValue::S),
+            ),
+            (
+                DESTINATION_ID_PARAMETER.to_string(),
+                filter.destination_id.map(AttributeValue::S),
+            ),
+            (
+                EXPENSE_ID_PARAMETER.to_string(),
+                filter.expense_id.map(AttributeValue::S),
+            ),
+            (
+                OPERATION_PARAMETER.to_string(),
+                filter
+                    .operation
+                    .map(|item| AttributeValue::S(item.to_string())),
+            ),
+            (
+                TRANSACTION_TYPE_PARAMETER.to_string(),
+                filter
+                    .transaction_type
+                    .map(|item| AttributeValue::S(item.to_string())),
+            ),
+            (
+                TRANSACTION_STATUS_PARAMETER.to_string(),
+                filter
+                    .status
+                    .map(|item| AttributeValue::S(item.to_string())),
+            ),
+            (
+                MONTH_PARAMETER.to_string(),
+                filter.month.map(|item| AttributeValue::N(item.to_string())),
+            ),
+            (
+                YEAR_PARAMETER.to_string(),
+                filter.year.map(|item| AttributeValue::N(item.to_string())),
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, value)| value.is_some())
+        .map(|(parameter, value)| (parameter, value.unwrap()))
+        .collect();
+
+        decode_items(
+            self.client
+                .list(TABLE_NAME.to_string(), query_attributes, None, None)
+                .await?
+                .items,
+            self.decode_mode,
+        )
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_transaction(&self, id: String) -> Result<Option<Transaction>, ApiError> {
+        self.client
+            .get(TABLE_NAME.to_string(), id)
+            .await?
+            .map(|item| Transaction::try_from(item).map_err(ApiError::from))
+            .transpose()
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn save_transaction(&self, transaction: Transaction) -> Result<(), ApiError> {
+        self.client
+            .put(TABLE_NAME.to_string(), transaction.into(), None)
+            .await
+    }
+
+    /// Saves the transactions together with `writes` to other tables, all or nothing. A repeated
+    /// call with the same `client_request_token` is applied only once.
+    #[tracing::instrument(skip(self))]
+    pub async fn save_transactions_with(
+        &self,
+        transactions: Vec<Transaction>,
+        writes: Vec<TransactWrite>,
+        client_request_token: Option<String>,
+    ) -> Result<(), ApiError> {
+        let transaction_writes = transactions
+            .into_iter()
+            .map(|transaction| TransactWrite::Put {
+                table_name: TABLE_NAME.to_string(),
+                item: transaction.into(),
+                condition: None,
+            })
+            .chain(writes)
+            .collect();
+
+        self.client
+            .transact_write(transaction_writes, client_request_token)
+            .await
+    }
+}
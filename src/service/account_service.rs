@@ -1,16 +1,29 @@
-use crate::error::PermanentError;
-use crate::model::account::{Account, AccountStatus, AccountType};
-use crate::repository::AccountRepository;
-use aws_config::SdkConfig;
+use crate::config::settings::Settings;
+use crate::error::ApiError;
+use crate::model::account::{
+    Account, AccountBalance, AccountRequest, AccountStatus, AccountType, BalanceFilter,
+};
+use crate::model::page::{Page, PageRequest};
+use crate::model::{CurrencyCode, ResourceId};
+use crate::repository::{AccountRepository, DatabaseClient, FxRateProvider};
+use crate::service::ConversionService;
+use std::sync::Arc;
+use uuid::Uuid;
 
 pub struct AccountService {
     repository: AccountRepository,
+    conversion_service: ConversionService,
 }
 
 impl AccountService {
-    pub fn new(config: &SdkConfig) -> Self {
+    pub fn new(
+        client: Arc<dyn DatabaseClient>,
+        fx_rates: Arc<dyn FxRateProvider>,
+        settings: &Settings,
+    ) -> Self {
         AccountService {
-            repository: AccountRepository::new(config),
+            repository: AccountRepository::new(client, settings),
+            conversion_service: ConversionService::new(fx_rates),
         }
     }
 
@@ -19,9 +32,273 @@ impl AccountService {
         &self,
         account_type: Option<AccountType>,
         account_status: Option<AccountStatus>,
-    ) -> Result<Vec<Account>, PermanentError> {
+        page: PageRequest,
+        report_currency: Option<CurrencyCode>,
+    ) -> Result<Page<Account>, ApiError> {
+        let mut accounts = self
+            .repository
+            .list_accounts(account_type, account_status, page)
+            .await?;
+
+        if let Some(report_currency) = report_currency {
+            let balances = accounts
+                .items
+                .iter_mut()
+                .flat_map(|account| account.balances.iter_mut())
+                .map(|balance| (&mut balance.balance, balance.date))
+                .collect();
+            self.conversion_service
+                .convert_all(balances, report_currency)
+                .await?;
+        }
+
+        Ok(accounts)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_account(&self, id: String) -> Result<Option<Account>, ApiError> {
+        self.repository.get_account(id).await
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn create_account(&self, request: AccountRequest) -> Result<ResourceId, ApiError> {
+        let id = Uuid::new_v4().to_string();
         self.repository
-            .list_accounts(account_type, account_status)
+            .save_account(request.into_account(id.clone()))
+            .await?;
+
+        Ok(ResourceId { id })
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn update_account(
+        &self,
+        id: String,
+        request: AccountRequest,
+    ) -> Result<ResourceId, ApiError> {
+        self.repository
+            .update_account(request.into_account(id.clone()))
+            .await?;
+
+        Ok(ResourceId { id })
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn add_balance(
+        &self,
+        id: String,
+        balance: AccountBalance,
+    ) -> Result<ResourceId, ApiError> {
+        let balance = AccountBalance {
+            balance: balance.balance.rounded(),
+            ..balance
+        };
+        if self.find_account(&id).await?.check_new_balance(&balance)? {
+            return Ok(ResourceId { id });
+        }
+
+        match self
+            .repository
+            .append_balance(id.clone(), balance.clone())
             .await
+        {
+            Ok(()) => Ok(ResourceId { id }),
+            // A concurrent or retried request may have recorded this very balance meanwhile
+            Err(error @ ApiError::Conflict { .. }) => {
+                match self.find_account(&id).await?.check_new_balance(&balance) {
+                    Ok(true) => Ok(ResourceId { id }),
+                    Ok(false) => Err(error),
+                    Err(error) => Err(error),
+                }
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn balance_history(
+        &self,
+        id: String,
+        filter: BalanceFilter,
+        report_currency: Option<CurrencyCode>,
+    ) -> Result<Vec<AccountBalance>, ApiError> {
+        let mut balances = self.find_account(&id).await?.balance_history(&filter);
+
+        if let Some(report_currency) = report_currency {
+            let values = balances
+                .iter_mut()
+                .map(|balance| (&mut balance.balance, balance.date))
+                .collect();
+            self.conversion_service
+                .convert_all(values, report_currency)
+                .await?;
+        }
+
+        Ok(balances)
+    }
+
+    async fn find_account(&self, id: &str) -> Result<Account, ApiError> {
+        self.repository
+            .get_account(id.to_string())
+            .await?
+            .ok_or(ApiError::NotFound {
+                message: format!("Account {id} not found"),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::fx_rate::FxRate;
+    use crate::model::Currency;
+    use crate::repository::{InMemoryClient, StaticFxRateProvider};
+    use chrono::{DateTime, NaiveDateTime, Utc};
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn should_add_same_balance_only_once() {
+        let service = build_service();
+        let id = service
+            .create_account(build_account_request())
+            .await
+            .unwrap()
+            .id;
+
+        for _ in 0..2 {
+            service
+                .add_balance(id.clone(), build_balance(dec!(10.004)))
+                .await
+                .unwrap();
+        }
+        let conflicting = service
+            .add_balance(id.clone(), build_balance(dec!(20)))
+            .await;
+
+        let account = service.get_account(id).await.unwrap().unwrap();
+        assert_eq!(vec![build_balance(dec!(10))], account.balances);
+        assert!(matches!(conflicting, Err(ApiError::Conflict { .. })));
+    }
+
+    #[tokio::test]
+    async fn should_keep_balances_when_updating_account() {
+        let service = build_service();
+        let id = service
+            .create_account(AccountRequest {
+                close_date: Some(parse_date("01/03/2023 00:00:00")),
+                ..build_account_request()
+            })
+            .await
+            .unwrap()
+            .id;
+        service
+            .add_balance(id.clone(), build_balance(dec!(10)))
+            .await
+            .unwrap();
+
+        service
+            .update_account(
+                id.clone(),
+                AccountRequest {
+                    name: "renamed".to_string(),
+                    ..build_account_request()
+                },
+            )
+            .await
+            .unwrap();
+
+        let account = service.get_account(id).await.unwrap().unwrap();
+        assert_eq!("renamed", account.name);
+        assert_eq!(None, account.close_date);
+        assert_eq!(vec![build_balance(dec!(10))], account.balances);
+    }
+
+    #[tokio::test]
+    async fn should_not_update_missing_account() {
+        let service = build_service();
+
+        let result = service
+            .update_account("missing".to_string(), build_account_request())
+            .await;
+
+        assert!(matches!(result, Err(ApiError::NotFound { .. })));
+        let created = service.get_account("missing".to_string()).await.unwrap();
+        assert_eq!(None, created);
+    }
+
+    #[tokio::test]
+    async fn should_not_add_balance_to_missing_account() {
+        let service = build_service();
+
+        let result = service
+            .add_balance("missing".to_string(), build_balance(dec!(10)))
+            .await;
+
+        assert!(matches!(result, Err(ApiError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn should_convert_balance_history_to_report_currency() {
+        let service = build_service();
+        let id = service
+            .create_account(build_account_request())
+            .await
+            .unwrap()
+            .id;
+        service
+            .add_balance(id.clone(), build_balance(dec!(10)))
+            .await
+            .unwrap();
+
+        let history = service
+            .balance_history(id, BalanceFilter::default(), Some(CurrencyCode::USD))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            vec![Currency::new(CurrencyCode::USD, dec!(2))],
+            history
+                .into_iter()
+                .map(|balance| balance.balance)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    fn build_service() -> AccountService {
+        AccountService::new(
+            Arc::new(InMemoryClient::default()),
+            Arc::new(StaticFxRateProvider::new(vec![FxRate {
+                source: CurrencyCode::BRL,
+                target: CurrencyCode::USD,
+                rate: dec!(0.2),
+                valid_from: parse_date("01/01/2023 00:00:00"),
+            }])),
+            &Settings::default(),
+        )
+    }
+
+    fn build_balance(value: Decimal) -> AccountBalance {
+        AccountBalance {
+            date: parse_date("28/02/2023 23:59:59"),
+            balance: Currency::new(CurrencyCode::BRL, value),
+        }
+    }
+
+    fn build_account_request() -> AccountRequest {
+        AccountRequest {
+            name: "name".to_string(),
+            bank_name: "bank".to_string(),
+            open_date: parse_date("15/02/2023 13:51:12"),
+            close_date: None,
+            account_type: AccountType::Checking,
+            status: AccountStatus::Open,
+        }
+    }
+
+    fn parse_date(value: &str) -> DateTime<Utc> {
+        NaiveDateTime::parse_from_str(value, "%d/%m/%Y %H:%M:%S")
+            .unwrap()
+            .and_utc()
     }
 }
@@ -0,0 +1,253 @@
+use crate::error::ApiError;
+use crate::model::fx_rate::FxRate;
+use crate::model::{Currency, CurrencyCode};
+use crate::repository::FxRateProvider;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub struct ConversionService {
+    provider: Arc<dyn FxRateProvider>,
+}
+
+impl ConversionService {
+    pub fn new(provider: Arc<dyn FxRateProvider>) -> Self {
+        ConversionService { provider }
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn convert(
+        &self,
+        amount: &Currency,
+        target: CurrencyCode,
+        date: DateTime<Utc>,
+    ) -> Result<Currency, ApiError> {
+        let mut converted = amount.clone();
+        self.convert_all(vec![(&mut converted, date)], target)
+            .await?;
+
+        Ok(converted)
+    }
+
+    /// Converts every amount in place using the rate valid at its date, fetching the rates of
+    /// each currency pair only once.
+    pub async fn convert_all(
+        &self,
+        amounts: Vec<(&mut Currency, DateTime<Utc>)>,
+        target: CurrencyCode,
+    ) -> Result<(), ApiError> {
+        let mut pairs: HashMap<CurrencyCode, Vec<(FxRate, bool)>> = HashMap::new();
+        for (amount, date) in amounts {
+            if amount.code == target {
+                continue;
+            }
+
+            let source = amount.code;
+            if let Entry::Vacant(entry) = pairs.entry(source) {
+                entry.insert(self.pair_rates(source, target).await?);
+            }
+            let rate = select_rate(&pairs[&source], date).ok_or(ApiError::Validation {
+                message: format!(
+                    "No {source} to {target} rate available on {}",
+                    date.format("%Y-%m-%d %H:%M:%S")
+                ),
+            })?;
+
+            let value = amount.value.checked_mul(rate).ok_or(ApiError::Validation {
+                message: format!("{source} amount out of range when converted to {target}"),
+            })?;
+            *amount = Currency::new(target, value).rounded();
+        }
+
+        Ok(())
+    }
+
+    /// Rates quoted in either direction, flagged when they must be inverted.
+    async fn pair_rates(
+        &self,
+        source: CurrencyCode,
+        target: CurrencyCode,
+    ) -> Result<Vec<(FxRate, bool)>, ApiError> {
+        let direct = self.provider.rates(source, target).await?;
+        let inverse = self.provider.rates(target, source).await?;
+
+        Ok(direct
+            .into_iter()
+            .map(|rate| (rate, false))
+            .chain(inverse.into_iter().map(|rate| (rate, true)))
+            .collect())
+    }
+}
+
+/// Picks the most recent rate already in effect at `date`, preferring a direct quote when both
+/// directions start at the same moment.
+fn select_rate(rates: &[(FxRate, bool)], date: DateTime<Utc>) -> Option<Decimal> {
+    rates
+        .iter()
+        .filter(|(rate, _)| rate.valid_from <= date && !rate.rate.is_zero())
+        .max_by_key(|(rate, inverted)| (rate.valid_from, !inverted))
+        .map(|(rate, inverted)| match inverted {
+            true => Decimal::ONE / rate.rate,
+            false => rate.rate,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::{MockFxRateProvider, StaticFxRateProvider};
+    use chrono::NaiveDateTime;
+    use rstest::rstest;
+    use rust_decimal_macros::dec;
+
+    #[rstest]
+    #[case("10/01/2023 00:00:00", dec!(50))]
+    #[case("01/02/2023 00:00:00", dec!(52))]
+    #[case("20/03/2023 00:00:00", dec!(55))]
+    #[tokio::test]
+    async fn should_use_rate_valid_at_date(#[case] date: &str, #[case] expected: Decimal) {
+        let service = build_service();
+
+        let result = service
+            .convert(
+                &Currency::new(CurrencyCode::USD, dec!(10)),
+                CurrencyCode::BRL,
+                parse_date(date),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(Currency::new(CurrencyCode::BRL, expected), result);
+    }
+
+    #[tokio::test]
+    async fn should_invert_rate_quoted_the_other_way() {
+        let service = build_service();
+
+        let result = service
+            .convert(
+                &Currency::new(CurrencyCode::BRL, dec!(100)),
+                CurrencyCode::USD,
+                parse_date("10/01/2023 00:00:00"),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(Currency::new(CurrencyCode::USD, dec!(20)), result);
+    }
+
+    #[tokio::test]
+    async fn should_reject_conversion_without_rate() {
+        let service = build_service();
+
+        let result = service
+            .convert(
+                &Currency::new(CurrencyCode::USD, dec!(10)),
+                CurrencyCode::BRL,
+                parse_date("31/12/2022 00:00:00"),
+            )
+            .await;
+
+        assert!(matches!(result, Err(ApiError::Validation { .. })));
+    }
+
+    #[tokio::test]
+    async fn should_reject_conversion_overflowing_amount() {
+        let service = build_service();
+
+        let result = service
+            .convert(
+                &Currency::new(CurrencyCode::USD, Decimal::MAX),
+                CurrencyCode::BRL,
+                parse_date("10/01/2023 00:00:00"),
+            )
+            .await;
+
+        assert!(matches!(result, Err(ApiError::Validation { .. })));
+    }
+
+    #[tokio::test]
+    async fn should_fetch_rates_once_per_pair() {
+        let mut provider = MockFxRateProvider::new();
+        provider
+            .expect_rates()
+            .times(2)
+            .returning(|source, target| {
+                Ok(vec![build_rate(
+                    source,
+                    target,
+                    "01/01/2023 00:00:00",
+                    dec!(2),
+                )])
+            });
+        let service = ConversionService::new(Arc::new(provider));
+        let mut first = Currency::new(CurrencyCode::USD, dec!(1));
+        let mut second = Currency::new(CurrencyCode::USD, dec!(2));
+        let mut same = Currency::new(CurrencyCode::BRL, dec!(3));
+        let date = parse_date("01/02/2023 00:00:00");
+
+        service
+            .convert_all(
+                vec![(&mut first, date), (&mut second, date), (&mut same, date)],
+                CurrencyCode::BRL,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(dec!(2), first.value);
+        assert_eq!(dec!(4), second.value);
+        assert_eq!(Currency::new(CurrencyCode::BRL, dec!(3)), same);
+    }
+
+    #[test]
+    fn conversion_service_should_be_thread_safe() {
+        is_thread_safe::<ConversionService>();
+    }
+
+    fn is_thread_safe<T: Sized + Send + Sync + Unpin>() {}
+
+    fn build_service() -> ConversionService {
+        ConversionService::new(Arc::new(StaticFxRateProvider::new(vec![
+            build_rate(
+                CurrencyCode::USD,
+                CurrencyCode::BRL,
+                "01/01/2023 00:00:00",
+                dec!(5),
+            ),
+            build_rate(
+                CurrencyCode::USD,
+                CurrencyCode::BRL,
+                "01/02/2023 00:00:00",
+                dec!(5.2),
+            ),
+            build_rate(
+                CurrencyCode::BRL,
+                CurrencyCode::USD,
+                "01/03/2023 00:00:00",
+                dec!(0.18181818),
+            ),
+        ])))
+    }
+
+    fn build_rate(
+        source: CurrencyCode,
+        target: CurrencyCode,
+        valid_from: &str,
+        rate: Decimal,
+    ) -> FxRate {
+        FxRate {
+            source,
+            target,
+            rate,
+            valid_from: parse_date(valid_from),
+        }
+    }
+
+    fn parse_date(value: &str) -> DateTime<Utc> {
+        NaiveDateTime::parse_from_str(value, "%d/%m/%Y %H:%M:%S")
+            .unwrap()
+            .and_utc()
+    }
+}
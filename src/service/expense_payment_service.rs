@@ -0,0 +1,339 @@
+use crate::config::settings::Settings;
+use crate::error::ApiError;
+use crate::model::expense::{DuePayment, ExpensePayment, PaymentUpdate};
+use crate::model::{CurrencyCode, ResourceId};
+use crate::repository::{DatabaseClient, ExpenseRepository, FxRateProvider};
+use crate::service::ConversionService;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+static MAX_SCHEDULE_MONTHS: u32 = 120;
+
+pub struct ExpensePaymentService {
+    repository: ExpenseRepository,
+    conversion_service: ConversionService,
+}
+
+impl ExpensePaymentService {
+    pub fn new(
+        client: Arc<dyn DatabaseClient>,
+        fx_rates: Arc<dyn FxRateProvider>,
+        settings: &Settings,
+    ) -> Self {
+        ExpensePaymentService {
+            repository: ExpenseRepository::new(client, settings),
+            conversion_service: ConversionService::new(fx_rates),
+        }
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn update_payment(
+        &self,
+        id: String,
+        due_date: DateTime<Utc>,
+        update: PaymentUpdate,
+    ) -> Result<ResourceId, ApiError> {
+        let mut expense =
+            self.repository
+                .get_expense(id.clone())
+                .await?
+                .ok_or(ApiError::NotFound {
+                    message: format!("Expense {id} not found"),
+                })?;
+
+        expense.update_payment(due_date, update)?;
+        self.repository.save_expense(expense).await?;
+
+        Ok(ResourceId { id })
+    }
+
+    /// Plans the recurring payments of an expense for the `months` following `from`, returning
+    /// only the ones that were not planned yet.
+    #[tracing::instrument(skip(self))]
+    pub async fn schedule_payments(
+        &self,
+        id: String,
+        from: DateTime<Utc>,
+        months: u32,
+    ) -> Result<Vec<ExpensePayment>, ApiError> {
+        if !(1..=MAX_SCHEDULE_MONTHS).contains(&months) {
+            return Err(ApiError::Validation {
+                message: format!("months must be between 1 and {MAX_SCHEDULE_MONTHS}"),
+            });
+        }
+
+        let mut expense =
+            self.repository
+                .get_expense(id.clone())
+                .await?
+                .ok_or(ApiError::NotFound {
+                    message: format!("Expense {id} not found"),
+                })?;
+
+        let planned = expense.schedule(from, months)?;
+        if !planned.is_empty() {
+            self.repository.save_expense(expense).await?;
+        }
+
+        Ok(planned)
+    }
+
+    /// Pending installments of every expense due before `before`, oldest first.
+    #[tracing::instrument(skip(self))]
+    pub async fn list_due(
+        &self,
+        before: DateTime<Utc>,
+        report_currency: Option<CurrencyCode>,
+    ) -> Result<Vec<DuePayment>, ApiError> {
+        let mut payments: Vec<DuePayment> = self
+            .repository
+            .list_expenses(None, None)
+            .await?
+            .iter()
+            .flat_map(|expense| expense.due_payments(before))
+            .collect();
+        payments.sort_by(|first, second| {
+            (first.due_date, &first.expense_id).cmp(&(second.due_date, &second.expense_id))
+        });
+
+        if let Some(report_currency) = report_currency {
+            let values = payments
+                .iter_mut()
+                .filter_map(|payment| {
+                    payment
+                        .value
+                        .as_mut()
+                        .map(|value| (value, payment.due_date))
+                })
+                .collect();
+            self.conversion_service
+                .convert_all(values, report_currency)
+                .await?;
+        }
+
+        Ok(payments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::expense::{
+        Expense, ExpenseStatus, ExpenseType, ExpenseValue, PaymentStatus, Recurrence,
+    };
+    use crate::model::fx_rate::FxRate;
+    use crate::model::Currency;
+    use crate::repository::{InMemoryClient, StaticFxRateProvider};
+    use chrono::NaiveDate;
+    use rstest::rstest;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn should_mark_payment_paid_and_drop_it_from_due_list() {
+        let (service, repository) = build_service();
+        repository
+            .save_expense(build_expense("a", &["15/01/2023", "15/02/2023"]))
+            .await
+            .unwrap();
+
+        service
+            .update_payment(
+                "a".to_string(),
+                parse_date("15/01/2023"),
+                PaymentUpdate {
+                    status: PaymentStatus::Paid,
+                    payment_date: Some(parse_date("14/01/2023")),
+                    receive_date: None,
+                },
+            )
+            .await
+            .unwrap();
+        let due = service
+            .list_due(parse_date("01/03/2023"), None)
+            .await
+            .unwrap();
+
+        assert_eq!(1, due.len());
+        assert_eq!(parse_date("15/02/2023"), due[0].due_date);
+        assert_eq!(
+            Some(Currency::new(CurrencyCode::BRL, dec!(10))),
+            due[0].value
+        );
+    }
+
+    #[tokio::test]
+    async fn should_not_update_payment_of_missing_expense() {
+        let (service, _) = build_service();
+
+        let result = service
+            .update_payment(
+                "missing".to_string(),
+                parse_date("15/01/2023"),
+                PaymentUpdate {
+                    status: PaymentStatus::Cancelled,
+                    payment_date: None,
+                    receive_date: None,
+                },
+            )
+            .await;
+
+        assert!(matches!(result, Err(ApiError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn should_list_due_payments_oldest_first_in_report_currency() {
+        let (service, repository) = build_service();
+        repository
+            .save_expense(build_expense("a", &["15/02/2023"]))
+            .await
+            .unwrap();
+        repository
+            .save_expense(build_expense("b", &["10/01/2023", "15/05/2023"]))
+            .await
+            .unwrap();
+
+        let due = service
+            .list_due(parse_date("01/03/2023"), Some(CurrencyCode::USD))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            vec!["b", "a"],
+            due.iter()
+                .map(|payment| payment.expense_id.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Some(Currency::new(CurrencyCode::USD, dec!(2))),
+            due[0].value
+        );
+    }
+
+    #[tokio::test]
+    async fn should_reject_saving_expense_changed_since_read() {
+        let (_, repository) = build_service();
+        repository
+            .save_expense(build_expense("a", &["15/01/2023"]))
+            .await
+            .unwrap();
+        let read = || async {
+            repository
+                .get_expense("a".to_string())
+                .await
+                .unwrap()
+                .unwrap()
+        };
+        let (first, second) = (read().await, read().await);
+
+        repository.save_expense(first).await.unwrap();
+        let stale = repository.save_expense(second).await;
+
+        assert!(matches!(stale, Err(ApiError::Conflict { .. })));
+        assert_eq!(2, read().await.version);
+    }
+
+    #[tokio::test]
+    async fn should_schedule_recurring_payments_idempotently() {
+        let (service, repository) = build_service();
+        let mut expense = build_expense("a", &["15/01/2023"]);
+        expense.recurrence = Some(Recurrence {
+            day_of_month: 15,
+            interval_months: 1,
+            end_date: None,
+        });
+        repository.save_expense(expense).await.unwrap();
+
+        let planned = service
+            .schedule_payments("a".to_string(), parse_date("01/01/2023"), 3)
+            .await
+            .unwrap();
+        let replanned = service
+            .schedule_payments("a".to_string(), parse_date("01/01/2023"), 3)
+            .await
+            .unwrap();
+
+        assert_eq!(2, planned.len());
+        assert!(replanned.is_empty());
+        let expense = repository
+            .get_expense("a".to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(3, expense.payments.len());
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(121)]
+    #[tokio::test]
+    async fn should_reject_schedule_out_of_range(#[case] months: u32) {
+        let (service, _) = build_service();
+
+        let result = service
+            .schedule_payments("a".to_string(), parse_date("01/01/2023"), months)
+            .await;
+
+        assert!(matches!(result, Err(ApiError::Validation { .. })));
+    }
+
+    #[test]
+    fn expense_payment_service_should_be_thread_safe() {
+        is_thread_safe::<ExpensePaymentService>();
+    }
+
+    fn is_thread_safe<T: Sized + Send + Sync + Unpin>() {}
+
+    fn build_service() -> (ExpensePaymentService, ExpenseRepository) {
+        let client = Arc::new(InMemoryClient::default());
+        let service = ExpensePaymentService::new(
+            client.clone(),
+            Arc::new(StaticFxRateProvider::new(vec![FxRate {
+                source: CurrencyCode::USD,
+                target: CurrencyCode::BRL,
+                rate: dec!(5),
+                valid_from: parse_date("01/01/2023"),
+            }])),
+            &Settings::default(),
+        );
+
+        (
+            service,
+            ExpenseRepository::new(client, &Settings::default()),
+        )
+    }
+
+    fn build_expense(id: &str, due_dates: &[&str]) -> Expense {
+        Expense {
+            id: id.to_string(),
+            description: "description".to_string(),
+            date_added: parse_date("01/01/2023"),
+            value_history: vec![ExpenseValue {
+                due_date: parse_date("01/01/2023"),
+                added_date: parse_date("01/01/2023"),
+                value: Currency::new(CurrencyCode::BRL, dec!(10)),
+            }],
+            payments: due_dates
+                .iter()
+                .map(|due_date| ExpensePayment {
+                    due_date: parse_date(due_date),
+                    payment_date: None,
+                    receive_date: None,
+                    status: PaymentStatus::Unpaid,
+                    transaction_id: None,
+                })
+                .collect(),
+            expense_type: ExpenseType::Fixed,
+            status: ExpenseStatus::Open,
+            recurrence: None,
+            version: 0,
+        }
+    }
+
+    fn parse_date(value: &str) -> DateTime<Utc> {
+        NaiveDate::parse_from_str(value, "%d/%m/%Y")
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc()
+    }
+}
@@ -0,0 +1,86 @@
+use crate::config::settings::Settings;
+use crate::error::ApiError;
+use crate::model::expense::{Expense, ExpenseRequest, ExpenseStatus, ExpenseType};
+use crate::model::{CurrencyCode, ResourceId};
+use crate::repository::{DatabaseClient, ExpenseRepository, FxRateProvider};
+use crate::service::ConversionService;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub struct ExpenseService {
+    repository: ExpenseRepository,
+    conversion_service: ConversionService,
+}
+
+impl ExpenseService {
+    pub fn new(
+        client: Arc<dyn DatabaseClient>,
+        fx_rates: Arc<dyn FxRateProvider>,
+        settings: &Settings,
+    ) -> Self {
+        ExpenseService {
+            repository: ExpenseRepository::new(client, settings),
+            conversion_service: ConversionService::new(fx_rates),
+        }
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn list_expenses(
+        &self,
+        expense_type: Option<ExpenseType>,
+        expense_status: Option<ExpenseStatus>,
+        report_currency: Option<CurrencyCode>,
+    ) -> Result<Vec<Expense>, ApiError> {
+        let mut expenses = self
+            .repository
+            .list_expenses(expense_type, expense_status)
+            .await?;
+
+        if let Some(report_currency) = report_currency {
+            let values = expenses
+                .iter_mut()
+                .flat_map(|expense| expense.value_history.iter_mut())
+                .map(|entry| (&mut entry.value, entry.due_date))
+                .collect();
+            self.conversion_service
+                .convert_all(values, report_currency)
+                .await?;
+        }
+
+        Ok(expenses)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_expense(&self, id: String) -> Result<Option<Expense>, ApiError> {
+        self.repository.get_expense(id).await
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn create_expense(&self, request: ExpenseRequest) -> Result<ResourceId, ApiError> {
+        let id = Uuid::new_v4().to_string();
+        self.repository
+            .save_expense(request.into_expense(id.clone()))
+            .await?;
+
+        Ok(ResourceId { id })
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn update_expense(
+        &self,
+        id: String,
+        request: ExpenseRequest,
+    ) -> Result<ResourceId, ApiError> {
+        let expense = match self.repository.get_expense(id.clone()).await? {
+            Some(mut expense) => {
+                expense.apply(request);
+                expense
+            }
+            None => request.into_expense(id.clone()),
+        };
+
+        self.repository.save_expense(expense).await?;
+
+        Ok(ResourceId { id })
+    }
+}
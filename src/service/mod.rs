@@ -1,3 +1,13 @@
 mod account_service;
+mod conversion_service;
+mod expense_payment_service;
+mod expense_service;
+mod report_service;
+mod transaction_service;
 
 pub use account_service::AccountService;
+pub use conversion_service::ConversionService;
+pub use expense_payment_service::ExpensePaymentService;
+pub use expense_service::ExpenseService;
+pub use report_service::ReportService;
+pub use transaction_service::TransactionService;
@@ -0,0 +1,581 @@
+use crate::config::settings::Settings;
+use crate::error::ApiError;
+use crate::model::account::AccountType;
+use crate::model::page::PageRequest;
+use crate::model::report::{
+    CashFlowEntry, CashFlowMonth, CashFlowReport, CashFlowTotals, NetWorthFilter, NetWorthGroup,
+    NetWorthReport,
+};
+use crate::model::transaction::{Transaction, TransactionFilter, TransactionOperation};
+use crate::model::{Currency, CurrencyCode};
+use crate::repository::{AccountRepository, DatabaseClient, FxRateProvider, TransactionRepository};
+use crate::service::ConversionService;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+pub struct ReportService {
+    account_repository: AccountRepository,
+    transaction_repository: TransactionRepository,
+    conversion_service: ConversionService,
+}
+
+impl ReportService {
+    pub fn new(
+        client: Arc<dyn DatabaseClient>,
+        fx_rates: Arc<dyn FxRateProvider>,
+        settings: &Settings,
+    ) -> Self {
+        ReportService {
+            account_repository: AccountRepository::new(client.clone(), settings),
+            transaction_repository: TransactionRepository::new(client, settings),
+            conversion_service: ConversionService::new(fx_rates),
+        }
+    }
+
+    /// Sums the latest balance of every account, grouped by account type and bank. Balances are
+    /// converted at the `as_of` date when a report currency is given.
+    #[tracing::instrument(skip(self))]
+    pub async fn net_worth(
+        &self,
+        filter: NetWorthFilter,
+        report_currency: Option<CurrencyCode>,
+    ) -> Result<NetWorthReport, ApiError> {
+        // Without a limit every page is read at once
+        let accounts = self
+            .account_repository
+            .list_accounts(None, None, PageRequest::default())
+            .await?
+            .items;
+
+        let mut balances: Vec<(AccountType, String, Currency)> = accounts
+            .iter()
+            .filter(|account| filter.includes(account))
+            .filter_map(|account| {
+                filter.balance_of(account).map(|balance| {
+                    (
+                        account.account_type,
+                        account.bank_name.clone(),
+                        balance.balance.clone(),
+                    )
+                })
+            })
+            .collect();
+
+        if let Some(report_currency) = report_currency {
+            let values = balances
+                .iter_mut()
+                .map(|(_, _, balance)| (balance, filter.as_of))
+                .collect();
+            self.conversion_service
+                .convert_all(values, report_currency)
+                .await?;
+        }
+
+        let mut totals = BTreeMap::new();
+        let mut groups: BTreeMap<(String, String), (NetWorthGroup, BTreeMap<String, Currency>)> =
+            BTreeMap::new();
+        for (account_type, bank_name, balance) in balances {
+            add(&mut totals, &balance)?;
+
+            let (group, group_totals) = groups
+                .entry((account_type.to_string(), bank_name.clone()))
+                .or_insert_with(|| {
+                    (
+                        NetWorthGroup {
+                            account_type,
+                            bank_name,
+                            accounts: 0,
+                            totals: vec![],
+                        },
+                        BTreeMap::new(),
+                    )
+                });
+            group.accounts += 1;
+            add(group_totals, &balance)?;
+        }
+
+        Ok(NetWorthReport {
+            as_of: filter.as_of,
+            totals: totals.into_values().collect(),
+            groups: groups
+                .into_values()
+                .map(|(group, totals)| NetWorthGroup {
+                    totals: totals.into_values().collect(),
+                    ..group
+                })
+                .collect(),
+        })
+    }
+
+    /// Sums the credits and debits of a year per month, and within each month per account and
+    /// transaction type. Amounts are converted at the transaction date when a report currency is
+    /// given.
+    #[tracing::instrument(skip(self))]
+    pub async fn cash_flow(
+        &self,
+        year: i32,
+        report_currency: Option<CurrencyCode>,
+    ) -> Result<CashFlowReport, ApiError> {
+        let mut transactions = self
+            .transaction_repository
+            .list_transactions(TransactionFilter {
+                year: Some(year),
+                ..TransactionFilter::default()
+            })
+            .await?;
+
+        if let Some(report_currency) = report_currency {
+            let values = transactions
+                .iter_mut()
+                .map(|transaction| (&mut transaction.value, transaction.date))
+                .collect();
+            self.conversion_service
+                .convert_all(values, report_currency)
+                .await?;
+        }
+
+        let mut months: BTreeMap<u32, (Flow, EntryFlows)> = BTreeMap::new();
+        for transaction in transactions {
+            let (month_flow, entries) = months.entry(transaction.month).or_default();
+            month_flow.add(&transaction)?;
+
+            let account_id = transaction.account_id().cloned();
+            let (_, entry_flow) = entries
+                .entry((
+                    account_id.clone().unwrap_or_default(),
+                    transaction.transaction_type.to_string(),
+                ))
+                .or_insert_with(|| {
+                    (
+                        CashFlowEntry {
+                            account_id,
+                            transaction_type: transaction.transaction_type,
+                            totals: Flow::default().into_totals(),
+                        },
+                        Flow::default(),
+                    )
+                });
+            entry_flow.add(&transaction)?;
+        }
+
+        Ok(CashFlowReport {
+            year,
+            months: months
+                .into_iter()
+                .map(|(month, (flow, entries))| CashFlowMonth {
+                    month,
+                    totals: flow.into_totals(),
+                    entries: entries
+                        .into_values()
+                        .map(|(entry, flow)| CashFlowEntry {
+                            totals: flow.into_totals(),
+                            ..entry
+                        })
+                        .collect(),
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Flows of a month keyed by account and transaction type.
+type EntryFlows = BTreeMap<(String, String), (CashFlowEntry, Flow)>;
+
+/// Running inflow, outflow and net totals keyed by currency.
+#[derive(Default)]
+struct Flow {
+    inflow: BTreeMap<String, Currency>,
+    outflow: BTreeMap<String, Currency>,
+    net: BTreeMap<String, Currency>,
+}
+
+impl Flow {
+    fn add(&mut self, transaction: &Transaction) -> Result<(), ApiError> {
+        match transaction.operation {
+            TransactionOperation::Credit => {
+                add(&mut self.inflow, &transaction.value)?;
+                add(&mut self.net, &transaction.value)
+            }
+            TransactionOperation::Debit => {
+                add(&mut self.outflow, &transaction.value)?;
+                add(&mut self.net, &transaction.value.negate())
+            }
+        }
+    }
+
+    fn into_totals(self) -> CashFlowTotals {
+        CashFlowTotals {
+            inflow: self.inflow.into_values().collect(),
+            outflow: self.outflow.into_values().collect(),
+            net: self.net.into_values().collect(),
+        }
+    }
+}
+
+/// Adds `amount` to the running total of its currency.
+fn add(totals: &mut BTreeMap<String, Currency>, amount: &Currency) -> Result<(), ApiError> {
+    let total = totals
+        .entry(amount.code.to_string())
+        .or_insert_with(|| Currency::zero(amount.code));
+    *total = total.try_add(amount)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::account::{Account, AccountBalance, AccountStatus};
+    use crate::model::fx_rate::FxRate;
+    use crate::model::transaction::{TransactionRequest, TransactionStatus, TransactionType};
+    use crate::repository::{InMemoryClient, StaticFxRateProvider};
+    use chrono::{DateTime, NaiveDateTime, Utc};
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn should_total_latest_balances_per_group_and_currency() {
+        let service = build_service(
+            vec![
+                build_account(
+                    "a",
+                    AccountType::Checking,
+                    "bank",
+                    AccountStatus::Open,
+                    CurrencyCode::BRL,
+                    dec!(10),
+                ),
+                build_account(
+                    "b",
+                    AccountType::Checking,
+                    "bank",
+                    AccountStatus::Open,
+                    CurrencyCode::USD,
+                    dec!(5),
+                ),
+                build_account(
+                    "c",
+                    AccountType::Savings,
+                    "bank",
+                    AccountStatus::Open,
+                    CurrencyCode::BRL,
+                    dec!(20),
+                ),
+                build_account(
+                    "d",
+                    AccountType::Checking,
+                    "bank",
+                    AccountStatus::Closed,
+                    CurrencyCode::BRL,
+                    dec!(99),
+                ),
+            ],
+            vec![],
+        )
+        .await;
+
+        let report = service.net_worth(build_filter(false), None).await.unwrap();
+
+        assert_eq!(
+            vec![
+                Currency::new(CurrencyCode::BRL, dec!(30)),
+                Currency::new(CurrencyCode::USD, dec!(5))
+            ],
+            report.totals
+        );
+        assert_eq!(2, report.groups.len());
+        assert_eq!(AccountType::Checking, report.groups[0].account_type);
+        assert_eq!(2, report.groups[0].accounts);
+        assert_eq!(
+            vec![Currency::new(CurrencyCode::BRL, dec!(20))],
+            report.groups[1].totals
+        );
+    }
+
+    #[tokio::test]
+    async fn should_include_account_closed_after_as_of_date() {
+        let service = build_service(
+            vec![Account {
+                close_date: Some(parse_date("01/06/2024 00:00:00")),
+                ..build_account(
+                    "a",
+                    AccountType::Checking,
+                    "bank",
+                    AccountStatus::Closed,
+                    CurrencyCode::BRL,
+                    dec!(10),
+                )
+            }],
+            vec![],
+        )
+        .await;
+
+        let report = service.net_worth(build_filter(false), None).await.unwrap();
+
+        assert_eq!(
+            vec![Currency::new(CurrencyCode::BRL, dec!(10))],
+            report.totals
+        );
+    }
+
+    #[tokio::test]
+    async fn should_include_inactive_accounts_and_convert_totals() {
+        let service = build_service(
+            vec![
+                build_account(
+                    "a",
+                    AccountType::Checking,
+                    "bank",
+                    AccountStatus::Open,
+                    CurrencyCode::BRL,
+                    dec!(10),
+                ),
+                build_account(
+                    "b",
+                    AccountType::Checking,
+                    "other",
+                    AccountStatus::NotInUse,
+                    CurrencyCode::USD,
+                    dec!(5),
+                ),
+            ],
+            vec![],
+        )
+        .await;
+
+        let report = service
+            .net_worth(build_filter(true), Some(CurrencyCode::BRL))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            vec![Currency::new(CurrencyCode::BRL, dec!(35))],
+            report.totals
+        );
+        assert_eq!(2, report.groups.len());
+    }
+
+    #[tokio::test]
+    async fn should_total_cash_flow_per_month_account_and_type() {
+        let service = build_service(
+            vec![],
+            vec![
+                build_transaction(
+                    "1",
+                    "a",
+                    TransactionOperation::Credit,
+                    TransactionType::Salary,
+                    "05/01/2023 00:00:00",
+                    dec!(100),
+                ),
+                build_transaction(
+                    "2",
+                    "a",
+                    TransactionOperation::Debit,
+                    TransactionType::Payment,
+                    "10/01/2023 00:00:00",
+                    dec!(30),
+                ),
+                build_transaction(
+                    "3",
+                    "a",
+                    TransactionOperation::Debit,
+                    TransactionType::Payment,
+                    "20/01/2023 00:00:00",
+                    dec!(20),
+                ),
+                build_transaction(
+                    "4",
+                    "b",
+                    TransactionOperation::Debit,
+                    TransactionType::Payment,
+                    "03/03/2023 00:00:00",
+                    dec!(15),
+                ),
+                build_transaction(
+                    "5",
+                    "a",
+                    TransactionOperation::Credit,
+                    TransactionType::Salary,
+                    "05/01/2024 00:00:00",
+                    dec!(999),
+                ),
+            ],
+        )
+        .await;
+
+        let report = service.cash_flow(2023, None).await.unwrap();
+
+        assert_eq!(2023, report.year);
+        assert_eq!(
+            vec![1, 3],
+            report
+                .months
+                .iter()
+                .map(|month| month.month)
+                .collect::<Vec<_>>()
+        );
+        let january = &report.months[0];
+        assert_eq!(
+            vec![Currency::new(CurrencyCode::BRL, dec!(100))],
+            january.totals.inflow
+        );
+        assert_eq!(
+            vec![Currency::new(CurrencyCode::BRL, dec!(50))],
+            january.totals.outflow
+        );
+        assert_eq!(
+            vec![Currency::new(CurrencyCode::BRL, dec!(50))],
+            january.totals.net
+        );
+        assert_eq!(2, january.entries.len());
+        assert_eq!(
+            TransactionType::Payment,
+            january.entries[0].transaction_type
+        );
+        assert_eq!(
+            vec![Currency::new(CurrencyCode::BRL, dec!(-50))],
+            january.entries[0].totals.net
+        );
+        assert_eq!(
+            Some("b".to_string()),
+            report.months[1].entries[0].account_id
+        );
+        assert!(report.months[1].totals.inflow.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_convert_cash_flow_into_report_currency() {
+        let mut transaction = build_transaction(
+            "1",
+            "a",
+            TransactionOperation::Credit,
+            TransactionType::Salary,
+            "05/01/2023 00:00:00",
+            dec!(10),
+        );
+        transaction.value = Currency::new(CurrencyCode::USD, dec!(10));
+        let service = build_service(
+            vec![],
+            vec![
+                transaction,
+                build_transaction(
+                    "2",
+                    "a",
+                    TransactionOperation::Debit,
+                    TransactionType::Payment,
+                    "10/01/2023 00:00:00",
+                    dec!(30),
+                ),
+            ],
+        )
+        .await;
+
+        let report = service
+            .cash_flow(2023, Some(CurrencyCode::BRL))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            vec![Currency::new(CurrencyCode::BRL, dec!(20))],
+            report.months[0].totals.net
+        );
+    }
+
+    #[test]
+    fn report_service_should_be_thread_safe() {
+        is_thread_safe::<ReportService>();
+    }
+
+    fn is_thread_safe<T: Sized + Send + Sync + Unpin>() {}
+
+    async fn build_service(
+        accounts: Vec<Account>,
+        transactions: Vec<Transaction>,
+    ) -> ReportService {
+        let client = Arc::new(InMemoryClient::default());
+        let repository = AccountRepository::new(client.clone(), &Settings::default());
+        for account in accounts {
+            repository.save_account(account).await.unwrap();
+        }
+        let repository = TransactionRepository::new(client.clone(), &Settings::default());
+        for transaction in transactions {
+            repository.save_transaction(transaction).await.unwrap();
+        }
+
+        ReportService::new(
+            client,
+            Arc::new(StaticFxRateProvider::new(vec![FxRate {
+                source: CurrencyCode::USD,
+                target: CurrencyCode::BRL,
+                rate: dec!(5),
+                valid_from: parse_date("01/01/2023 00:00:00"),
+            }])),
+            &Settings::default(),
+        )
+    }
+
+    fn build_filter(include_inactive: bool) -> NetWorthFilter {
+        NetWorthFilter {
+            as_of: parse_date("31/12/2023 00:00:00"),
+            include_inactive,
+        }
+    }
+
+    fn build_account(
+        id: &str,
+        account_type: AccountType,
+        bank_name: &str,
+        status: AccountStatus,
+        code: CurrencyCode,
+        value: Decimal,
+    ) -> Account {
+        Account {
+            id: id.to_string(),
+            name: "name".to_string(),
+            bank_name: bank_name.to_string(),
+            open_date: parse_date("01/01/2023 00:00:00"),
+            close_date: None,
+            account_type,
+            balances: vec![
+                AccountBalance {
+                    date: parse_date("31/01/2023 23:59:59"),
+                    balance: Currency::new(code, Decimal::ONE),
+                },
+                AccountBalance {
+                    date: parse_date("28/02/2023 23:59:59"),
+                    balance: Currency::new(code, value),
+                },
+            ],
+            status,
+        }
+    }
+
+    fn build_transaction(
+        id: &str,
+        account_id: &str,
+        operation: TransactionOperation,
+        transaction_type: TransactionType,
+        date: &str,
+        value: Decimal,
+    ) -> Transaction {
+        TransactionRequest {
+            source_id: Some(account_id.to_string()),
+            destination_id: Some(account_id.to_string()),
+            expense_id: None,
+            description: "description".to_string(),
+            date: parse_date(date),
+            value: Currency::new(CurrencyCode::BRL, value),
+            operation,
+            transaction_type,
+            status: TransactionStatus::Closed,
+        }
+        .into_transaction(id.to_string())
+    }
+
+    fn parse_date(value: &str) -> DateTime<Utc> {
+        NaiveDateTime::parse_from_str(value, "%d/%m/%Y %H:%M:%S")
+            .unwrap()
+            .and_utc()
+    }
+}
@@ -0,0 +1,689 @@
+use crate::config::settings::Settings;
+use crate::error::ApiError;
+use crate::model::account::AccountBalance;
+use crate::model::transaction::{
+    Transaction, TransactionFilter, TransactionRequest, Transfer, TransferRequest,
+};
+use crate::model::{CurrencyCode, ResourceId};
+use crate::repository::{
+    AccountRepository, DatabaseClient, ExpenseRepository, FxRateProvider, TransactionRepository,
+};
+use crate::service::ConversionService;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub struct TransactionService {
+    repository: TransactionRepository,
+    expense_repository: ExpenseRepository,
+    account_repository: AccountRepository,
+    conversion_service: ConversionService,
+}
+
+impl TransactionService {
+    pub fn new(
+        client: Arc<dyn DatabaseClient>,
+        fx_rates: Arc<dyn FxRateProvider>,
+        settings: &Settings,
+    ) -> Self {
+        TransactionService {
+            repository: TransactionRepository::new(client.clone(), settings),
+            expense_repository: ExpenseRepository::new(client.clone(), settings),
+            account_repository: AccountRepository::new(client, settings),
+            conversion_service: ConversionService::new(fx_rates),
+        }
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn list_transactions(
+        &self,
+        filter: TransactionFilter,
+        report_currency: Option<CurrencyCode>,
+    ) -> Result<Vec<Transaction>, ApiError> {
+        let mut transactions = self.repository.list_transactions(filter).await?;
+
+        if let Some(report_currency) = report_currency {
+            let values = transactions
+                .iter_mut()
+                .map(|transaction| (&mut transaction.value, transaction.date))
+                .collect();
+            self.conversion_service
+                .convert_all(values, report_currency)
+                .await?;
+        }
+
+        Ok(transactions)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_transaction(&self, id: String) -> Result<Option<Transaction>, ApiError> {
+        self.repository.get_transaction(id).await
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn create_transaction(
+        &self,
+        request: TransactionRequest,
+    ) -> Result<ResourceId, ApiError> {
+        self.save_transaction(request.into_transaction(Uuid::new_v4().to_string()), None)
+            .await
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn update_transaction(
+        &self,
+        id: String,
+        request: TransactionRequest,
+    ) -> Result<ResourceId, ApiError> {
+        let previous = self.repository.get_transaction(id.clone()).await?;
+        self.save_transaction(request.into_transaction(id), previous)
+            .await
+    }
+
+    /// Records both legs of a transfer and the balances informed for each account in a single
+    /// write, applied only while both accounts are still open. Repeats with the same
+    /// `idempotency_key` record the transfer once, and a different transfer sent with a key
+    /// already used fails with `Conflict`.
+    #[tracing::instrument(skip(self))]
+    pub async fn transfer(
+        &self,
+        request: TransferRequest,
+        idempotency_key: String,
+    ) -> Result<Transfer, ApiError> {
+        let transfer = Transfer::from_idempotency_key(&idempotency_key);
+        if let Some(debit) = self
+            .repository
+            .get_transaction(transfer.debit_id.clone())
+            .await?
+        {
+            return match request.is_recorded_as(&debit) {
+                true => Ok(transfer),
+                false => Err(ApiError::Conflict {
+                    message: format!(
+                        "Idempotency key {idempotency_key} was used for another transfer"
+                    ),
+                }),
+            };
+        }
+
+        let mut writes = vec![];
+        for (id, balance) in [
+            (&request.source_id, &request.source_balance),
+            (&request.destination_id, &request.destination_balance),
+        ] {
+            let account = self
+                .account_repository
+                .get_account(id.clone())
+                .await?
+                .ok_or(ApiError::Validation {
+                    message: format!("Account {id} not found"),
+                })?;
+            account.check_transfer(request.value.code)?;
+
+            let new_balance = match balance {
+                Some(balance) => {
+                    let balance = AccountBalance {
+                        date: request.date,
+                        balance: balance.rounded(),
+                    };
+                    // The very same balance may already be recorded by a repeated request
+                    (!account.check_new_balance(&balance)?).then_some(balance)
+                }
+                None => None,
+            };
+            // The account may be closed between the read above and the write
+            writes.push(match new_balance {
+                Some(balance) => self
+                    .account_repository
+                    .append_balance_write(id.clone(), balance),
+                None => self.account_repository.check_not_closed_write(id.clone()),
+            });
+        }
+
+        let (debit, credit) = request.into_transactions(&transfer);
+        // The debit id is unique to the idempotency key and fits a client request token
+        self.repository
+            .save_transactions_with(vec![debit, credit], writes, Some(transfer.debit_id.clone()))
+            .await?;
+
+        Ok(transfer)
+    }
+
+    /// Saves `transaction` over `previous`, reverting the installment `previous` settled when
+    /// the transaction no longer settles that expense.
+    async fn save_transaction(
+        &self,
+        transaction: Transaction,
+        previous: Option<Transaction>,
+    ) -> Result<ResourceId, ApiError> {
+        // Settling and reverting installments in the same write keeps every record in step
+        let mut writes = vec![];
+        let expense_id = transaction.settled_expense_id();
+        if let Some(previous_id) = previous
+            .as_ref()
+            .and_then(Transaction::settled_expense_id)
+            .filter(|previous_id| Some(*previous_id) != expense_id)
+        {
+            if let Some(mut expense) = self
+                .expense_repository
+                .get_expense(previous_id.clone())
+                .await?
+            {
+                if expense.unsettle(&transaction.id) {
+                    writes.push(self.expense_repository.save_expense_write(expense));
+                }
+            }
+        }
+        if let Some(expense_id) = expense_id {
+            let mut expense = self
+                .expense_repository
+                .get_expense(expense_id.clone())
+                .await?
+                .ok_or(ApiError::Validation {
+                    message: format!("Expense {expense_id} not found"),
+                })?;
+            expense.settle(&transaction.id, transaction.date)?;
+            writes.push(self.expense_repository.save_expense_write(expense));
+        }
+
+        let id = transaction.id.clone();
+        if writes.is_empty() {
+            self.repository.save_transaction(transaction).await?;
+        } else {
+            self.repository
+                .save_transactions_with(vec![transaction], writes, None)
+                .await?;
+        }
+
+        Ok(ResourceId { id })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::account::{Account, AccountStatus, AccountType};
+    use crate::model::expense::{
+        Expense, ExpensePayment, ExpenseStatus, ExpenseType, PaymentStatus,
+    };
+    use crate::model::transaction::{TransactionOperation, TransactionStatus, TransactionType};
+    use crate::model::Currency;
+    use crate::repository::{
+        Condition, InMemoryClient, Item, ItemPage, ItemUpdate, ListAppend, StaticFxRateProvider,
+        TransactWrite,
+    };
+    use async_trait::async_trait;
+    use aws_sdk_dynamodb::types::AttributeValue;
+    use chrono::{DateTime, NaiveDateTime, Utc};
+    use rstest::rstest;
+    use rust_decimal_macros::dec;
+    use tokio::sync::Barrier;
+
+    #[tokio::test]
+    async fn should_settle_expense_payment_with_transaction() {
+        let (service, expense_repository) = build_service().await;
+
+        let id = service
+            .create_transaction(build_request(Some("expense"), TransactionType::Payment))
+            .await
+            .unwrap()
+            .id;
+        service
+            .update_transaction(
+                id.clone(),
+                build_request(Some("expense"), TransactionType::Payment),
+            )
+            .await
+            .unwrap();
+
+        let expense = expense_repository
+            .get_expense("expense".to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(PaymentStatus::Paid, expense.payments[0].status);
+        assert_eq!(Some(id.clone()), expense.payments[0].transaction_id);
+        assert_eq!(PaymentStatus::Unpaid, expense.payments[1].status);
+        assert!(service.get_transaction(id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn should_settle_installment_once_when_payments_race() {
+        let client = build_client().await;
+        let service = TransactionService::new(
+            Arc::new(LockstepClient {
+                client: client.clone(),
+                barrier: Barrier::new(2),
+            }),
+            Arc::new(StaticFxRateProvider::new(vec![])),
+            &Settings::default(),
+        );
+
+        let (first, second) = tokio::join!(
+            service.create_transaction(build_request(Some("expense"), TransactionType::Payment)),
+            service.create_transaction(build_request(Some("expense"), TransactionType::Payment)),
+        );
+
+        let results = [first, second];
+        assert_eq!(1, results.iter().filter(|result| result.is_ok()).count());
+        assert!(results
+            .iter()
+            .any(|result| matches!(result, Err(ApiError::Conflict { .. }))));
+        let expense = ExpenseRepository::new(client.clone(), &Settings::default())
+            .get_expense("expense".to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(PaymentStatus::Paid, expense.payments[0].status);
+        assert_eq!(PaymentStatus::Unpaid, expense.payments[1].status);
+        let transactions = service
+            .list_transactions(TransactionFilter::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(1, transactions.len());
+    }
+
+    #[rstest]
+    #[case(Some("other"), TransactionType::Payment, Some("other"))]
+    #[case(None, TransactionType::Payment, None)]
+    #[case(Some("expense"), TransactionType::Transfer, None)]
+    #[tokio::test]
+    async fn should_revert_installment_no_longer_settled_by_transaction(
+        #[case] expense_id: Option<&str>,
+        #[case] transaction_type: TransactionType,
+        #[case] settled_id: Option<&str>,
+    ) {
+        let (service, expense_repository) = build_service().await;
+        let id = service
+            .create_transaction(build_request(Some("expense"), TransactionType::Payment))
+            .await
+            .unwrap()
+            .id;
+
+        service
+            .update_transaction(id.clone(), build_request(expense_id, transaction_type))
+            .await
+            .unwrap();
+
+        for expense_id in ["expense", "other"] {
+            let expense = expense_repository
+                .get_expense(expense_id.to_string())
+                .await
+                .unwrap()
+                .unwrap();
+            let settled = Some(expense_id) == settled_id;
+            assert_eq!(settled, expense.payments[0].status == PaymentStatus::Paid);
+            assert_eq!(
+                settled.then(|| id.clone()),
+                expense.payments[0].transaction_id
+            );
+            assert_eq!(settled, expense.payments[0].payment_date.is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn should_not_save_payment_of_missing_expense() {
+        let (service, _) = build_service().await;
+
+        let result = service
+            .create_transaction(build_request(Some("missing"), TransactionType::Payment))
+            .await;
+
+        assert!(matches!(result, Err(ApiError::Validation { .. })));
+        let transactions = service
+            .list_transactions(TransactionFilter::default(), None)
+            .await
+            .unwrap();
+        assert!(transactions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_leave_expense_untouched_by_other_transaction_types() {
+        let (service, expense_repository) = build_service().await;
+
+        service
+            .create_transaction(build_request(Some("expense"), TransactionType::Transfer))
+            .await
+            .unwrap();
+
+        let expense = expense_repository
+            .get_expense("expense".to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(PaymentStatus::Unpaid, expense.payments[0].status);
+    }
+
+    #[tokio::test]
+    async fn should_record_both_legs_and_balances_of_transfer() {
+        let (service, _) = build_service().await;
+
+        let transfer = service
+            .transfer(build_transfer("destination"), "key".to_string())
+            .await
+            .unwrap();
+
+        let debit = service
+            .get_transaction(transfer.debit_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let credit = service
+            .get_transaction(transfer.credit_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(TransactionOperation::Debit, debit.operation);
+        assert_eq!(TransactionOperation::Credit, credit.operation);
+        let source = service
+            .account_repository
+            .get_account("source".to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(2, source.balances.len());
+        assert_eq!(dec!(90), source.balances[1].balance.value);
+    }
+
+    #[rstest]
+    #[case("closed")]
+    #[case("dollars")]
+    #[case("missing")]
+    #[tokio::test]
+    async fn should_reject_transfer_to_invalid_account(#[case] destination_id: &str) {
+        let (service, _) = build_service().await;
+
+        let result = service
+            .transfer(build_transfer(destination_id), "key".to_string())
+            .await;
+
+        assert!(matches!(result, Err(ApiError::Validation { .. })));
+        let transactions = service
+            .list_transactions(TransactionFilter::default(), None)
+            .await
+            .unwrap();
+        assert!(transactions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_record_nothing_when_transfer_balance_conflicts() {
+        let (service, _) = build_service().await;
+        service
+            .transfer(build_transfer("destination"), "first".to_string())
+            .await
+            .unwrap();
+
+        let mut request = build_transfer("destination");
+        request.source_balance = Some(Currency::new(CurrencyCode::BRL, dec!(80)));
+        let result = service.transfer(request, "second".to_string()).await;
+
+        assert!(matches!(result, Err(ApiError::Conflict { .. })));
+        let transactions = service
+            .list_transactions(TransactionFilter::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(2, transactions.len());
+    }
+
+    #[tokio::test]
+    async fn should_record_repeated_transfer_once() {
+        let (service, _) = build_service().await;
+        let transfer = service
+            .transfer(build_transfer("destination"), "key".to_string())
+            .await
+            .unwrap();
+
+        let repeated = service
+            .transfer(build_transfer("destination"), "key".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(transfer, repeated);
+        let transactions = service
+            .list_transactions(TransactionFilter::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(2, transactions.len());
+    }
+
+    #[tokio::test]
+    async fn should_reject_other_transfer_with_used_idempotency_key() {
+        let (service, _) = build_service().await;
+        service
+            .transfer(build_transfer("destination"), "key".to_string())
+            .await
+            .unwrap();
+
+        let mut request = build_transfer("destination");
+        request.value = Currency::new(CurrencyCode::BRL, dec!(20));
+        let result = service.transfer(request, "key".to_string()).await;
+
+        assert!(matches!(result, Err(ApiError::Conflict { .. })));
+        let transactions = service
+            .list_transactions(TransactionFilter::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(2, transactions.len());
+        assert!(transactions
+            .iter()
+            .all(|transaction| transaction.value.value == dec!(10)));
+    }
+
+    #[tokio::test]
+    async fn should_record_nothing_when_account_closes_during_transfer() {
+        let client = build_client().await;
+        let lockstep = Arc::new(LockstepClient {
+            client: client.clone(),
+            barrier: Barrier::new(2),
+        });
+        let service = TransactionService::new(
+            lockstep.clone(),
+            Arc::new(StaticFxRateProvider::new(vec![])),
+            &Settings::default(),
+        );
+        let mut request = build_transfer("destination");
+        request.source_balance = None;
+
+        // The transfer reads its debit, the source and then the destination: the source is
+        // closed once read as open
+        let (result, _) = tokio::join!(service.transfer(request, "key".to_string()), async {
+            lockstep.barrier.wait().await;
+            lockstep.barrier.wait().await;
+            AccountRepository::new(client.clone(), &Settings::default())
+                .update_account(build_account(
+                    "source",
+                    AccountStatus::Closed,
+                    CurrencyCode::BRL,
+                ))
+                .await
+                .unwrap();
+            lockstep.barrier.wait().await;
+        });
+
+        assert!(matches!(result, Err(ApiError::Conflict { .. })));
+        let transactions = service
+            .list_transactions(TransactionFilter::default(), None)
+            .await
+            .unwrap();
+        assert!(transactions.is_empty());
+    }
+
+    #[test]
+    fn transaction_service_should_be_thread_safe() {
+        is_thread_safe::<TransactionService>();
+    }
+
+    fn is_thread_safe<T: Sized + Send + Sync + Unpin>() {}
+
+    /// Holds every read until the other caller made one too, so two concurrent requests both
+    /// work on what was stored before either of them wrote.
+    struct LockstepClient {
+        client: Arc<InMemoryClient>,
+        barrier: Barrier,
+    }
+
+    #[async_trait]
+    impl DatabaseClient for LockstepClient {
+        async fn list(
+            &self,
+            table_name: String,
+            conditions: Vec<(String, AttributeValue)>,
+            limit: Option<i32>,
+            start_key: Option<Item>,
+        ) -> Result<ItemPage, ApiError> {
+            self.client
+                .list(table_name, conditions, limit, start_key)
+                .await
+        }
+
+        async fn get(&self, table_name: String, id: String) -> Result<Option<Item>, ApiError> {
+            let item = self.client.get(table_name, id).await;
+            self.barrier.wait().await;
+            item
+        }
+
+        async fn put(
+            &self,
+            table_name: String,
+            item: Item,
+            condition: Option<Condition>,
+        ) -> Result<(), ApiError> {
+            self.client.put(table_name, item, condition).await
+        }
+
+        async fn update(
+            &self,
+            table_name: String,
+            id: String,
+            update: ItemUpdate,
+        ) -> Result<(), ApiError> {
+            self.client.update(table_name, id, update).await
+        }
+
+        async fn append(
+            &self,
+            table_name: String,
+            id: String,
+            append: ListAppend,
+        ) -> Result<(), ApiError> {
+            self.client.append(table_name, id, append).await
+        }
+
+        async fn transact_write(
+            &self,
+            writes: Vec<TransactWrite>,
+            client_request_token: Option<String>,
+        ) -> Result<(), ApiError> {
+            self.client
+                .transact_write(writes, client_request_token)
+                .await
+        }
+    }
+
+    async fn build_service() -> (TransactionService, ExpenseRepository) {
+        let client = build_client().await;
+        (
+            TransactionService::new(
+                client.clone(),
+                Arc::new(StaticFxRateProvider::new(vec![])),
+                &Settings::default(),
+            ),
+            ExpenseRepository::new(client, &Settings::default()),
+        )
+    }
+
+    async fn build_client() -> Arc<InMemoryClient> {
+        let client = Arc::new(InMemoryClient::default());
+        let expense_repository = ExpenseRepository::new(client.clone(), &Settings::default());
+        for id in ["expense", "other"] {
+            expense_repository
+                .save_expense(Expense {
+                    id: id.to_string(),
+                    description: "description".to_string(),
+                    date_added: parse_date("01/01/2023 00:00:00"),
+                    value_history: vec![],
+                    payments: ["15/02/2023 00:00:00", "15/03/2023 00:00:00"]
+                        .into_iter()
+                        .map(|due_date| ExpensePayment {
+                            due_date: parse_date(due_date),
+                            payment_date: None,
+                            receive_date: None,
+                            status: PaymentStatus::Unpaid,
+                            transaction_id: None,
+                        })
+                        .collect(),
+                    expense_type: ExpenseType::Fixed,
+                    status: ExpenseStatus::Open,
+                    recurrence: None,
+                    version: 0,
+                })
+                .await
+                .unwrap();
+        }
+
+        let account_repository = AccountRepository::new(client.clone(), &Settings::default());
+        for (id, status, code) in [
+            ("source", AccountStatus::Open, CurrencyCode::BRL),
+            ("destination", AccountStatus::NotInUse, CurrencyCode::BRL),
+            ("closed", AccountStatus::Closed, CurrencyCode::BRL),
+            ("dollars", AccountStatus::Open, CurrencyCode::USD),
+        ] {
+            account_repository
+                .save_account(build_account(id, status, code))
+                .await
+                .unwrap();
+        }
+
+        client
+    }
+
+    fn build_account(id: &str, status: AccountStatus, code: CurrencyCode) -> Account {
+        Account {
+            id: id.to_string(),
+            name: "name".to_string(),
+            bank_name: "bank".to_string(),
+            open_date: parse_date("01/01/2023 00:00:00"),
+            close_date: None,
+            account_type: AccountType::Checking,
+            balances: vec![AccountBalance {
+                date: parse_date("31/01/2023 23:59:59"),
+                balance: Currency::new(code, dec!(100)),
+            }],
+            status,
+        }
+    }
+
+    fn build_request(
+        expense_id: Option<&str>,
+        transaction_type: TransactionType,
+    ) -> TransactionRequest {
+        TransactionRequest {
+            source_id: Some("account".to_string()),
+            destination_id: None,
+            expense_id: expense_id.map(String::from),
+            description: "description".to_string(),
+            date: parse_date("14/02/2023 10:00:00"),
+            value: Currency::new(CurrencyCode::BRL, dec!(10)),
+            operation: TransactionOperation::Debit,
+            transaction_type,
+            status: TransactionStatus::Closed,
+        }
+    }
+
+    fn build_transfer(destination_id: &str) -> TransferRequest {
+        TransferRequest {
+            source_id: "source".to_string(),
+            destination_id: destination_id.to_string(),
+            description: "description".to_string(),
+            date: parse_date("14/02/2023 10:00:00"),
+            value: Currency::new(CurrencyCode::BRL, dec!(10)),
+            source_balance: Some(Currency::new(CurrencyCode::BRL, dec!(90))),
+            destination_balance: None,
+        }
+    }
+
+    fn parse_date(value: &str) -> DateTime<Utc> {
+        NaiveDateTime::parse_from_str(value, "%d/%m/%Y %H:%M:%S")
+            .unwrap()
+            .and_utc()
+    }
+}
@@ -1,11 +1,15 @@
+#[cfg(feature = "integration")]
 mod agent;
 
 #[cfg(feature = "integration")]
 #[cfg(test)]
 mod tests {
     use crate::agent::{build_playground_api_agent, PlaygroundApiRequest};
-    use playground_api::model::account::Account;
-    use serde_json::from_str;
+    use playground_api::model::account::{Account, AccountRequest, AccountStatus, AccountType};
+    use playground_api::model::page::Page;
+    use playground_api::model::ResourceId;
+    use serde_json::{from_str, to_string};
+    use std::collections::HashSet;
 
     #[tokio::test]
     async fn test_list_accounts() {
@@ -20,8 +24,113 @@ mod tests {
             .await
             .unwrap();
 
-        let accounts: Vec<Account> = from_str(&result.payload).unwrap();
+        let accounts: Page<Account> = from_str(&result.payload).unwrap();
         assert_eq!(result.status, 200);
-        assert!(accounts.len() > 0);
+        assert!(!accounts.items.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_accounts_by_page() {
+        let agent = build_playground_api_agent().await.unwrap();
+
+        let first = agent
+            .call(PlaygroundApiRequest {
+                uri: "/accounts?limit=2".to_string(),
+                http_method: "GET".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let first_page: Page<Account> = from_str(&first.payload).unwrap();
+        assert_eq!(first.status, 200);
+        assert_eq!(2, first_page.items.len());
+        let cursor = first_page.next_cursor.expect("more accounts than one page");
+
+        let second = agent
+            .call(PlaygroundApiRequest {
+                uri: format!("/accounts?limit=2&cursor={cursor}"),
+                http_method: "GET".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let second_page: Page<Account> = from_str(&second.payload).unwrap();
+        assert_eq!(second.status, 200);
+        let first_ids: HashSet<&String> =
+            first_page.items.iter().map(|account| &account.id).collect();
+        assert!(second_page
+            .items
+            .iter()
+            .all(|account| !first_ids.contains(&account.id)));
+    }
+
+    #[tokio::test]
+    async fn test_create_and_update_account() {
+        let agent = build_playground_api_agent().await.unwrap();
+        let request = AccountRequest {
+            name: "Integration Test Account".to_string(),
+            bank_name: "Integration Bank".to_string(),
+            open_date: chrono::Utc::now(),
+            close_date: None,
+            account_type: AccountType::ExternalParty,
+            status: AccountStatus::NotInUse,
+        };
+
+        let created = agent
+            .call(PlaygroundApiRequest {
+                uri: "/accounts".to_string(),
+                http_method: "POST".to_string(),
+                payload: Some(to_string(&request).unwrap()),
+            })
+            .await
+            .unwrap();
+
+        let created_id: ResourceId = from_str(&created.payload).unwrap();
+        assert_eq!(created.status, 201);
+
+        let updated = agent
+            .call(PlaygroundApiRequest {
+                uri: format!("/accounts/{}", created_id.id),
+                http_method: "POST".to_string(),
+                payload: Some(to_string(&request).unwrap()),
+            })
+            .await
+            .unwrap();
+
+        let updated_id: ResourceId = from_str(&updated.payload).unwrap();
+        assert_eq!(updated.status, 200);
+        assert_eq!(created_id, updated_id);
+
+        let fetched = agent
+            .call(PlaygroundApiRequest {
+                uri: format!("/accounts/{}", created_id.id),
+                http_method: "GET".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let account: Account = from_str(&fetched.payload).unwrap();
+        assert_eq!(fetched.status, 200);
+        assert_eq!(created_id.id, account.id);
+        assert_eq!(request.name, account.name);
+    }
+
+    #[tokio::test]
+    async fn test_get_missing_account() {
+        let agent = build_playground_api_agent().await.unwrap();
+
+        let result = agent
+            .call(PlaygroundApiRequest {
+                uri: "/accounts/missing-account".to_string(),
+                http_method: "GET".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(result.status, 404);
     }
 }
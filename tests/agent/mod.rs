@@ -12,7 +12,6 @@ pub struct PlaygroundApiRequest {
     pub uri: String,
     pub http_method: String,
     pub payload: Option<String>,
-    pub path_parameters: Option<Vec<String>>,
 }
 
 pub struct PlaygroundApiResponse {
@@ -29,19 +29,31 @@ impl PlaygroundApiLambdaAgent {
 
         payload.insert("path".to_string(), Value::String(request.uri));
         payload.insert("httpMethod".to_string(), Value::String(request.http_method));
-        payload.insert("multiValueHeaders".to_string(), Value::Object(Map::new()));
         payload.insert("requestContext".to_string(), Value::Object(Map::new()));
 
+        let mut headers = Map::new();
+        if let Some(body) = request.payload {
+            headers.insert(
+                "content-type".to_string(),
+                Value::Array(vec![Value::String("application/json".to_string())]),
+            );
+            payload.insert("body".to_string(), Value::String(body));
+        }
+        payload.insert("multiValueHeaders".to_string(), Value::Object(headers));
+
         Blob::new(to_string(&Value::Object(payload)).unwrap())
     }
 
-    fn extract_response_payload(payload: &Blob) -> String {
+    fn extract_response_payload(payload: &Blob) -> (i32, String) {
         let body = String::from_utf8(payload.as_ref().to_vec()).unwrap();
         let body_json: Value = from_str(body.as_str()).unwrap();
         let body_decoded_bytes = general_purpose::STANDARD
             .decode(body_json["body"].as_str().unwrap())
             .unwrap();
-        String::from_utf8(body_decoded_bytes).unwrap()
+        (
+            body_json["statusCode"].as_i64().unwrap() as i32,
+            String::from_utf8(body_decoded_bytes).unwrap(),
+        )
     }
 }
 
@@ -61,9 +73,9 @@ impl PlaygroundApiAgent for PlaygroundApiLambdaAgent {
             .await
             .unwrap();
 
-        let body_decoded = Self::extract_response_payload(resp.payload().unwrap());
+        let (status, body_decoded) = Self::extract_response_payload(resp.payload().unwrap());
         Ok(PlaygroundApiResponse {
-            status: resp.status_code(),
+            status,
             payload: body_decoded,
         })
     }
@@ -0,0 +1,68 @@
+#[cfg(feature = "integration")]
+mod agent;
+
+#[cfg(feature = "integration")]
+#[cfg(test)]
+mod tests {
+    use crate::agent::{build_playground_api_agent, PlaygroundApiRequest};
+    use playground_api::model::transaction::{
+        Transaction, TransactionOperation, TransactionRequest, TransactionStatus, TransactionType,
+    };
+    use playground_api::model::{Currency, CurrencyCode, ResourceId};
+    use rust_decimal::Decimal;
+    use serde_json::{from_str, to_string};
+    use uuid::Uuid;
+
+    /// Transactions cannot be deleted through the API: every one created here debits this account
+    /// and names its run in the description, so leftovers can be found and removed from the table.
+    static INTEGRATION_SOURCE_ID: &str = "integration-source";
+
+    #[tokio::test]
+    async fn test_create_and_get_transaction() {
+        let agent = build_playground_api_agent().await.unwrap();
+        let request = TransactionRequest {
+            source_id: Some(INTEGRATION_SOURCE_ID.to_string()),
+            destination_id: None,
+            expense_id: None,
+            description: format!("Integration Test Transaction {}", Uuid::new_v4()),
+            date: chrono::Utc::now(),
+            value: Currency {
+                code: CurrencyCode::BRL,
+                value: Decimal::ONE,
+            },
+            operation: TransactionOperation::Debit,
+            transaction_type: TransactionType::Payment,
+            status: TransactionStatus::Closed,
+        };
+
+        let created = agent
+            .call(PlaygroundApiRequest {
+                uri: "/transactions".to_string(),
+                http_method: "POST".to_string(),
+                payload: Some(to_string(&request).unwrap()),
+            })
+            .await
+            .unwrap();
+
+        let created_id: ResourceId = from_str(&created.payload).unwrap();
+        assert_eq!(created.status, 201);
+
+        let fetched = agent
+            .call(PlaygroundApiRequest {
+                uri: format!("/transactions/{}", created_id.id),
+                http_method: "GET".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let transaction: Transaction = from_str(&fetched.payload).unwrap();
+        assert_eq!(fetched.status, 200);
+        assert_eq!(created_id.id, transaction.id);
+        assert_eq!(request.description, transaction.description);
+        assert_eq!(
+            Some(INTEGRATION_SOURCE_ID.to_string()),
+            transaction.source_id
+        );
+    }
+}